@@ -0,0 +1,32 @@
+// apertures: the shape of the opening light passes through into the lens, which is the shape
+// out of focus highlights (bokeh) take
+
+use crate::apertures::circular::CircularAperture;
+use crate::apertures::polygonal::PolygonalAperture;
+use crate::error::{Error, Result};
+use crate::util::rng::Rng;
+
+pub trait Aperture: Send + Sync {
+    /// The name used on the command line and in scene files.
+    fn name(&self) -> &'static str;
+
+    /// Everything that sets the aperture's shape, for telling apart apertures of the same kind (checkpoints are only
+    /// resumed with the same one).
+    fn settings(&self) -> String {
+        self.name().to_string()
+    }
+
+    /// A point on the aperture, picked evenly over how much light each part lets through, with the lens's
+    /// circle (of radius `defocus_angle` sets) as the unit circle. Takes a pair of dimensions from `rng`.
+    fn sample(&self, rng: &mut Rng) -> (f32, f32);
+}
+
+/// An aperture made of `blades` straight blades, turned `rotation` degrees anticlockwise,
+/// or a circle (a lens with rounded blades, or wide open) for 0 blades.
+pub fn aperture_from_blades(blades: u32, rotation: f32) -> Result<Box<dyn Aperture>> {
+    match blades {
+        0 => Ok(Box::new(CircularAperture)),
+        1 | 2 => Err(Error::InvalidSettings(format!("an aperture needs at least 3 blades (or 0 for a circle), got {}", blades))),
+        _ => Ok(Box::new(PolygonalAperture::new(blades, rotation))),
+    }
+}
@@ -0,0 +1,19 @@
+// the round aperture of the books' thin lens
+
+use crate::apertures::aperture::Aperture;
+use crate::util::rng::Rng;
+use crate::util::vector_math::random_in_unit_disk;
+
+/// The whole unit disk, for perfectly round bokeh.
+pub struct CircularAperture;
+
+impl Aperture for CircularAperture {
+    fn name(&self) -> &'static str {
+        "circle"
+    }
+
+    fn sample(&self, rng: &mut Rng) -> (f32, f32) {
+        let p = random_in_unit_disk(rng);
+        return (p.x, p.y);
+    }
+}
@@ -0,0 +1,131 @@
+// an aperture drawn as an image: white lets light through, black blocks it, and greys let some through
+
+use crate::apertures::aperture::Aperture;
+use crate::error::{Error, Result};
+use crate::textures::rtw_image::RTWImage;
+use crate::util::rng::Rng;
+
+/// An aperture mask from a greyscale image, stretched over the square around the lens's circle (keeping its
+/// aspect ratio, with the longer side across the whole square). Points are picked in proportion to how bright
+/// the image is there, so a star or heart shaped mask gives star or heart shaped bokeh.
+pub struct ImageAperture {
+    filename: String,
+    width: usize,
+    height: usize,
+    // the running sums of brightness down the rows, and along each row, for picking a pixel in proportion to it
+    row_cdf: Vec<f32>,
+    column_cdfs: Vec<Vec<f32>>,
+}
+
+impl ImageAperture {
+    /// Loads the mask from `filename`, looked for where image textures are.
+    pub fn load(filename: &str) -> Result<Self> {
+        let image = RTWImage::new_from(filename)?;
+        let (width, height) = (image.width(), image.height());
+
+        let mut row_cdf = Vec::with_capacity(height);
+        let mut column_cdfs = Vec::with_capacity(height);
+        let mut total = 0.0;
+        for j in 0..height {
+            let mut row_total = 0.0;
+            let cdf: Vec<f32> = (0..width).map(|i| {
+                let [r, g, b] = image.pixel_data(i as i32, j as i32);
+                row_total += (r as f32 + g as f32 + b as f32) / (3.0 * 255.0);
+                row_total
+            }).collect();
+            total += row_total;
+            row_cdf.push(total);
+            column_cdfs.push(cdf);
+        }
+        if total <= 0.0 {return Err(Error::InvalidSettings(format!("aperture image '{}' is completely black", filename)))}
+
+        return Ok(Self {filename: filename.to_string(), width, height, row_cdf, column_cdfs});
+    }
+}
+
+// picks the entry of `cdf` that `u` (in [0, 1)) lands in, and how far through it, from 0 to 1
+fn sample_cdf(cdf: &[f32], u: f32) -> (usize, f32) {
+    let total = *cdf.last().unwrap();
+    let target = u * total;
+    let index = cdf.partition_point(|&sum| sum <= target).min(cdf.len() - 1);
+    let start = if index > 0 {cdf[index - 1]} else {0.0};
+    let width = cdf[index] - start;
+    let within = if width > 0.0 {((target - start) / width).clamp(0.0, 1.0)} else {0.5};
+    return (index, within);
+}
+
+impl Aperture for ImageAperture {
+    fn name(&self) -> &'static str {
+        "image"
+    }
+
+    fn settings(&self) -> String {
+        format!("image {}", self.filename)
+    }
+
+    fn sample(&self, rng: &mut Rng) -> (f32, f32) {
+        let (r1, r2) = rng.next_2d();
+        let (row, v) = sample_cdf(&self.row_cdf, r1);
+        let (column, u) = sample_cdf(&self.column_cdfs[row], r2);
+
+        // from pixels to [-1, 1], with y up and the image centered
+        let size = self.width.max(self.height) as f32;
+        let x = (2.0 * (column as f32 + u) - self.width as f32) / size;
+        let y = (self.height as f32 - 2.0 * (row as f32 + v)) / size;
+        return (x, y);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sample_cdf_skips_empty_entries() {
+        // entries 0 and 2 have nothing in them
+        let cdf = [0.0, 1.0, 1.0, 3.0];
+        for step in 0..1000 {
+            let (index, within) = sample_cdf(&cdf, step as f32 / 1000.0);
+            assert!(index == 1 || index == 3, "picked entry {}", index);
+            assert!((0.0..=1.0).contains(&within));
+        }
+        // and the rest in proportion to their size
+        let (index, within) = sample_cdf(&cdf, 0.3);
+        assert!(index == 1 && (within - 0.9).abs() < 1e-5);
+        let (index, within) = sample_cdf(&cdf, 0.5);
+        assert!(index == 3 && (within - 0.25).abs() < 1e-5);
+    }
+
+    #[test]
+    fn samples_never_land_in_black_pixels() {
+        // a 5x3 mask: a black middle row and column, and grey columns between them and the white edges
+        let brightness = |i: u32, j: u32| -> u8 {if i == 2 || j == 1 {0} else if i == 1 || i == 3 {60} else {255}};
+        let path = std::env::temp_dir().join(format!("rusty_raytracer_aperture_{}.png", std::process::id()));
+        image::RgbImage::from_fn(5, 3, |i, j| image::Rgb([brightness(i, j); 3])).save(&path).unwrap();
+        let aperture = ImageAperture::load(path.to_str().unwrap());
+        let _ = std::fs::remove_file(&path);
+        let aperture = aperture.unwrap();
+
+        let mut rng = Rng::new(1);
+        let mut counts = [[0; 5]; 3];
+        for _ in 0..20000 {
+            let (x, y) = aperture.sample(&mut rng);
+            // back from [-1, 1] to pixels
+            let column = ((x * 5.0 + 5.0) / 2.0).floor() as usize;
+            let row = ((3.0 - y * 5.0) / 2.0).floor() as usize;
+            assert!(brightness(column as u32, row as u32) > 0, "({}, {}) is in black pixel ({}, {})", x, y, column, row);
+            counts[row][column] += 1;
+        }
+        // the grey pixels are picked less often than the white ones
+        assert!(counts[0][1] < counts[0][0] / 2);
+    }
+
+    #[test]
+    fn black_image_is_an_error() {
+        let path = std::env::temp_dir().join(format!("rusty_raytracer_black_aperture_{}.png", std::process::id()));
+        image::RgbImage::new(4, 4).save(&path).unwrap();
+        let aperture = ImageAperture::load(path.to_str().unwrap());
+        let _ = std::fs::remove_file(&path);
+        assert!(matches!(aperture, Err(Error::InvalidSettings(_))));
+    }
+}
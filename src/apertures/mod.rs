@@ -0,0 +1,4 @@
+pub mod aperture;
+pub mod circular;
+pub mod image_aperture;
+pub mod polygonal;
@@ -0,0 +1,93 @@
+// a bladed aperture: a regular polygon, as the straight blades of a stopped down lens leave
+
+use std::f32::consts::PI;
+
+use crate::apertures::aperture::Aperture;
+use crate::util::rng::Rng;
+use crate::util::vector_math::degrees_to_radians;
+
+/// A regular polygon with a corner for each blade, inside the unit circle, with its first corner straight up
+/// before it is turned by `rotation` degrees anticlockwise.
+pub struct PolygonalAperture {
+    pub blades: u32,
+    pub rotation: f32,
+}
+
+impl PolygonalAperture {
+    pub fn new(blades: u32, rotation: f32) -> Self {
+        Self {blades, rotation}
+    }
+
+    fn corner(&self, k: u32) -> (f32, f32) {
+        let angle = PI / 2.0 + degrees_to_radians(self.rotation) + 2.0 * PI * k as f32 / self.blades as f32;
+        return (angle.cos(), angle.sin());
+    }
+}
+
+impl Aperture for PolygonalAperture {
+    fn name(&self) -> &'static str {
+        "polygon"
+    }
+
+    fn settings(&self) -> String {
+        format!("polygon of {} blades turned {} degrees", self.blades, self.rotation)
+    }
+
+    fn sample(&self, rng: &mut Rng) -> (f32, f32) {
+        // the polygon is a fan of equal triangles around the center: the first number picks one, and what's
+        // left of it and the second number pick a point in it
+        let (r1, r2) = rng.next_2d();
+        let scaled = r1 * self.blades as f32;
+        let k = (scaled as u32).min(self.blades - 1);
+        let (a, b) = (self.corner(k), self.corner(k + 1));
+
+        // the square root keeps the points evenly spread, as the triangle widens away from the center
+        let s = (scaled - k as f32).sqrt();
+        return (s * ((1.0 - r2) * a.0 + r2 * b.0), s * ((1.0 - r2) * a.1 + r2 * b.1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_lie_inside_the_polygon() {
+        for blades in 3..=9 {
+            for rotation in [0.0, 17.0, 90.0] {
+                let aperture = PolygonalAperture::new(blades, rotation);
+                let mut rng = Rng::new(blades as u64);
+                for _ in 0..2000 {
+                    let (x, y) = aperture.sample(&mut rng);
+                    // the corners go anticlockwise, so the point is left of (or on) every edge
+                    for k in 0..blades {
+                        let (a, b) = (aperture.corner(k), aperture.corner(k + 1));
+                        let cross = (b.0 - a.0) * (y - a.1) - (b.1 - a.1) * (x - a.0);
+                        assert!(cross >= -1e-5, "({}, {}) is outside edge {} of a {} bladed aperture turned {}", x, y, k, blades, rotation);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn samples_cover_the_polygon_evenly() {
+        // as many points in each of the triangles between the center and an edge, and their average at the center
+        let aperture = PolygonalAperture::new(5, 0.0);
+        let mut rng = Rng::new(1);
+        let n = 20000;
+        let mut counts = [0; 5];
+        let (mut sum_x, mut sum_y) = (0.0, 0.0);
+        for _ in 0..n {
+            let (x, y) = aperture.sample(&mut rng);
+            let angle = (y.atan2(x) - PI / 2.0).rem_euclid(2.0 * PI);
+            counts[((angle / (2.0 * PI / 5.0)) as usize).min(4)] += 1;
+            sum_x += x;
+            sum_y += y;
+        }
+        for count in counts {
+            assert!((count as f32 / n as f32 - 0.2).abs() < 0.015, "{:?}", counts);
+        }
+        assert!((sum_x / n as f32).abs() < 0.02 && (sum_y / n as f32).abs() < 0.02);
+    }
+}
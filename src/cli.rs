@@ -0,0 +1,242 @@
+// command-line options for picking a scene and overriding its render settings
+
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use rusty_raytracer::apertures::aperture::aperture_from_blades;
+use rusty_raytracer::apertures::image_aperture::ImageAperture;
+use rusty_raytracer::core::physical_camera::PhysicalCamera;
+use rusty_raytracer::core::projection::projection_from_name;
+use rusty_raytracer::core::shutter::shutter_curve_from_name;
+use rusty_raytracer::core::stereo::{stereo_layout_from_name, StereoRig};
+use rusty_raytracer::filters::filter::filter_from_name;
+use rusty_raytracer::integrators::integrator::integrator_from_name;
+use rusty_raytracer::samplers::sampler::sampler_from_name;
+use rusty_raytracer::{Camera, Result};
+
+#[derive(Parser, Debug)]
+#[command(version, about = "A path tracer following Ray Tracing: The Next Week")]
+pub struct Cli {
+    /// Name of the scene to render (see --list), or the path to a .toml scene file
+    #[arg(default_value = "cornell_box")]
+    pub scene: String,
+
+    /// List the registered scenes and exit
+    #[arg(long)]
+    pub list: bool,
+
+    /// Image width in pixels (height follows the scene's aspect ratio)
+    #[arg(long)]
+    pub width: Option<f32>,
+
+    /// Samples per pixel (the most any pixel takes, with adaptive sampling)
+    #[arg(long)]
+    pub spp: Option<u32>,
+
+    /// Progressive rendering: write the image after every pass of this many samples per pixel.
+    /// By default all of the samples are taken in one pass
+    #[arg(long)]
+    pub pass_spp: Option<u32>,
+
+    /// Save the render so far to this checkpoint file after every pass (16 samples per pixel unless --pass-spp says otherwise),
+    /// so it can be carried on with --resume if it is stopped
+    #[arg(long)]
+    pub checkpoint: Option<PathBuf>,
+
+    /// Carry on the render saved in the --checkpoint file, adding the samples it hadn't taken yet.
+    /// The scene and every setting that changes the image must be the same as when it was started
+    /// (which isn't checked for the OBJ models and images a scene file loads)
+    #[arg(long, requires = "checkpoint")]
+    pub resume: bool,
+
+    /// Adaptive sampling: stop sampling a pixel once the standard error of its mean is below this fraction of it
+    /// (e.g. 0.01). Off by default
+    #[arg(long)]
+    pub adaptive_threshold: Option<f32>,
+
+    /// The fewest samples a pixel takes with adaptive sampling (default 16)
+    #[arg(long)]
+    pub min_spp: Option<u32>,
+
+    /// Also write a heatmap of how many samples each pixel took (blue fewest, red most) to this file
+    #[arg(long)]
+    pub heatmap: Option<PathBuf>,
+
+    /// Maximum number of ray bounces
+    #[arg(long)]
+    pub max_depth: Option<u32>,
+
+    /// Number of bounces after which paths may be ended early by Russian roulette
+    #[arg(long)]
+    pub rr_depth: Option<u32>,
+
+    /// Rendering algorithm: path (naive path tracing), nee (path tracing with light sampling), ao (ambient occlusion),
+    /// direct (direct lighting only), bdpt (bidirectional path tracing) or photon (progressive photon mapping). Defaults to the scene's choice, which is nee unless it says otherwise
+    #[arg(long)]
+    pub integrator: Option<String>,
+
+    /// Reconstruction filter the samples are shared out between nearby pixels with: box, tent, gaussian, mitchell or lanczos.
+    /// Defaults to the scene's choice, which is a box over the pixel unless it says otherwise
+    #[arg(long)]
+    pub filter: Option<String>,
+
+    /// How far the filter reaches from each sample, in pixels (defaults to the filter's own radius)
+    #[arg(long)]
+    pub filter_radius: Option<f32>,
+
+    /// Where each camera sample's random numbers come from: independent, stratified, halton or sobol (Owen-scrambled).
+    /// Defaults to the scene's choice, which is sobol unless it says otherwise
+    #[arg(long)]
+    pub sampler: Option<String>,
+
+    /// Camera projection: perspective, orthographic, equidistant or equisolid (fisheyes), or equirectangular (a 360 degree panorama).
+    /// Defaults to the scene's choice, which is perspective unless it says otherwise
+    #[arg(long)]
+    pub projection: Option<String>,
+
+    /// Height of the orthographic projection's view, in scene units (defaults to the perspective view's height at the look-at point)
+    #[arg(long)]
+    pub ortho_height: Option<f32>,
+
+    /// Field of view across the image height, in degrees, for the perspective and fisheye projections
+    #[arg(long)]
+    pub vfov: Option<u32>,
+
+    /// When the shutter opens, in scene time (moving objects move from time 0 to 1)
+    #[arg(long)]
+    pub shutter_open: Option<f32>,
+
+    /// When the shutter closes, in scene time
+    #[arg(long)]
+    pub shutter_close: Option<f32>,
+
+    /// How open the shutter is while it's open: box (fully, the whole time) or triangle (fully only halfway)
+    #[arg(long)]
+    pub shutter_curve: Option<String>,
+
+    /// Number of aperture blades, for polygonal bokeh (0 for a round aperture)
+    #[arg(long)]
+    pub aperture_blades: Option<u32>,
+
+    /// Rotation of the polygonal aperture, in degrees anticlockwise
+    #[arg(long, requires = "aperture_blades")]
+    pub aperture_rotation: Option<f32>,
+
+    /// A greyscale image to use as the aperture's shape (white lets light through), in place of --aperture-blades
+    #[arg(long, conflicts_with = "aperture_blades")]
+    pub aperture_image: Option<String>,
+
+    /// Optical vignetting, from 0 (none) to 1: how much the lens barrel clips the aperture towards the corners,
+    /// for cat's eye bokeh and darker corners
+    #[arg(long)]
+    pub cat_eye: Option<f32>,
+
+    /// Focal length of a physical camera's lens, in millimetres. Any of the physical camera's settings turns it on
+    /// (starting from a 50mm lens at f/8, 1/125s and ISO 100), in place of --vfov and the defocus angle
+    #[arg(long)]
+    pub focal_length: Option<f32>,
+
+    /// The physical camera's f-number
+    #[arg(long)]
+    pub f_number: Option<f32>,
+
+    /// The physical camera's shutter speed, in seconds
+    #[arg(long)]
+    pub shutter_speed: Option<f32>,
+
+    /// The physical camera's ISO
+    #[arg(long)]
+    pub iso: Option<f32>,
+
+    /// The physical camera's sensor width, in millimetres
+    #[arg(long)]
+    pub sensor_width: Option<f32>,
+
+    /// Render in stereo, with the eyes this far apart in scene units. Any of the stereo settings turns on the
+    /// stereo rig (starting from eyes 0.065 apart, side by side)
+    #[arg(long)]
+    pub interocular: Option<f32>,
+
+    /// How far away the stereo views line up, for the perspective projection (defaults to the focus distance)
+    #[arg(long)]
+    pub convergence: Option<f32>,
+
+    /// How the stereo views are laid out: side_by_side, top_bottom, or separate images (name_left and name_right)
+    #[arg(long)]
+    pub stereo_layout: Option<String>,
+
+    /// Photons traced per pass by the photon integrator (defaults to one per pixel)
+    #[arg(long)]
+    pub photons: Option<u32>,
+
+    /// Radius photons are gathered within on the first pass, in scene units (defaults to five pixels' width at the look-at point)
+    #[arg(long)]
+    pub photon_radius: Option<f32>,
+
+    /// Where to write the rendered image; the format comes from the extension (.png, .ppm, .hdr or .exr)
+    #[arg(short, long, default_value = "rendered_image.png")]
+    pub output: PathBuf,
+
+    /// Bits per channel for PNG output (8 or 16)
+    #[arg(long, default_value_t = 8)]
+    pub bit_depth: u8,
+
+    /// Tonemapper applied before the image is encoded (none, reinhard or aces)
+    #[arg(long, default_value = "none")]
+    pub tonemap: String,
+
+    /// Number of worker threads (defaults to one per core)
+    #[arg(long)]
+    pub threads: Option<usize>,
+
+    /// Seed for scene generation and sampling; the same seed renders the same image
+    #[arg(long, default_value_t = 0)]
+    pub seed: u64,
+}
+
+impl Cli {
+    /// Applies any render settings given on the command line to the scene's camera.
+    pub fn apply_overrides(&self, cam: &mut Camera) -> Result<()> {
+        if let Some(width) = self.width {cam.image_width = width}
+        if let Some(spp) = self.spp {cam.samples_per_pixel = spp}
+        if let Some(adaptive_threshold) = self.adaptive_threshold {cam.adaptive_threshold = adaptive_threshold}
+        if let Some(min_spp) = self.min_spp {cam.min_samples_per_pixel = min_spp}
+        if let Some(pass_spp) = self.pass_spp {cam.samples_per_pass = pass_spp}
+        if let Some(max_depth) = self.max_depth {cam.max_depth = max_depth}
+        if let Some(rr_depth) = self.rr_depth {cam.russian_roulette_depth = rr_depth}
+        if let Some(integrator) = &self.integrator {cam.integrator = integrator_from_name(integrator)?}
+        if self.filter.is_some() || self.filter_radius.is_some() {
+            let name = self.filter.as_deref().unwrap_or(cam.filter.name());
+            cam.filter = filter_from_name(name, self.filter_radius)?;
+        }
+        if let Some(sampler) = &self.sampler {cam.sampler = sampler_from_name(sampler)?}
+        if let Some(projection) = &self.projection {cam.projection = projection_from_name(projection)?}
+        if let Some(ortho_height) = self.ortho_height {cam.ortho_height = ortho_height}
+        if let Some(vfov) = self.vfov {cam.vfov = vfov}
+        if let Some(shutter_open) = self.shutter_open {cam.shutter_open = shutter_open}
+        if let Some(shutter_close) = self.shutter_close {cam.shutter_close = shutter_close}
+        if let Some(shutter_curve) = &self.shutter_curve {cam.shutter_curve = shutter_curve_from_name(shutter_curve)?}
+        if let Some(blades) = self.aperture_blades {cam.aperture = aperture_from_blades(blades, self.aperture_rotation.unwrap_or(0.0))?}
+        if let Some(image) = &self.aperture_image {cam.aperture = Box::new(ImageAperture::load(image)?)}
+        if let Some(cat_eye) = self.cat_eye {cam.cat_eye = cat_eye}
+        let physical_settings = [self.focal_length, self.f_number, self.shutter_speed, self.iso, self.sensor_width];
+        if physical_settings.iter().any(Option::is_some) {
+            let physical = cam.physical.get_or_insert_with(PhysicalCamera::new);
+            if let Some(focal_length) = self.focal_length {physical.focal_length = focal_length}
+            if let Some(f_number) = self.f_number {physical.f_number = f_number}
+            if let Some(shutter_speed) = self.shutter_speed {physical.shutter_speed = shutter_speed}
+            if let Some(iso) = self.iso {physical.iso = iso}
+            if let Some(sensor_width) = self.sensor_width {physical.sensor_width = sensor_width}
+        }
+        if self.interocular.is_some() || self.convergence.is_some() || self.stereo_layout.is_some() {
+            let stereo = cam.stereo.get_or_insert_with(StereoRig::new);
+            if let Some(interocular) = self.interocular {stereo.interocular = interocular}
+            if let Some(convergence) = self.convergence {stereo.convergence = convergence}
+            if let Some(layout) = &self.stereo_layout {stereo.layout = stereo_layout_from_name(layout)?}
+        }
+        if let Some(photons) = self.photons {cam.photons = photons}
+        if let Some(photon_radius) = self.photon_radius {cam.photon_radius = photon_radius}
+        Ok(())
+    }
+}
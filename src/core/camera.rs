@@ -3,33 +3,97 @@
 // - using the results of these rays to construct the rendered image
 
 use std::io::Write;
-use std::fs::File;
-use std::io;
 use std::sync::Arc;
 use nalgebra::{Point3, Vector3};
 use rayon::prelude::*;
-use rayon::iter::IntoParallelIterator;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 
-use crate::core::colour::write_colour_string;
-use crate::util::interval::Interval;
-use crate::util::vector_math::{degrees_to_radians, random_f32, random_in_unit_disk};
+use crate::apertures::aperture::Aperture;
+use crate::apertures::circular::CircularAperture;
+use crate::core::checkpoint::{hash_bytes, Checkpoint};
+use crate::core::physical_camera::PhysicalCamera;
+use crate::core::projection::Projection;
+use crate::core::shutter::ShutterCurve;
+use crate::core::stereo::{Eye, StereoRig};
+use crate::core::film::{Film, FilterBuffer, Pixel, SplatBuffer, Tile};
+use crate::filters::box_filter::BoxFilter;
+use crate::filters::filter::Filter;
+use crate::util::rng::Rng;
+use crate::error::{Error, Result};
+use crate::util::vector_math::{degrees_to_radians, near_zero, random_f32};
+use crate::geometry::hittable_list::HittableList;
+use crate::integrators::integrator::{Integrator, RenderContext};
+use crate::integrators::nee_path_tracer::NEEPathTracer;
+use crate::samplers::sampler::{SampleId, Sampler};
+use crate::samplers::sobol::Sobol;
 use crate::{geometry::hittable::Hittable, core::ray::Ray, core::colour::Colour};
 
+// the width and height of the squares of pixels the image is split into to render in parallel
+const TILE_SIZE: usize = 16;
+
 pub struct Camera {
     pub aspect_ratio: f32,
     pub image_width: f32,
     pub samples_per_pixel: u32,
     pub max_depth: u32,
+    /// Paths that have bounced this many times go on with a chance that falls with how much light they can
+    /// still carry (Russian roulette). Set it to max_depth or more to always follow paths to max_depth.
+    pub russian_roulette_depth: u32,
     pub background: Colour,
+    /// The field of view across the image height, in degrees, for the perspective and fisheye projections.
     pub vfov: u32,
+    /// How the image is mapped to directions; see `Projection`.
+    pub projection: Projection,
+    /// The height of the orthographic projection's view, in scene units; 0 makes it as tall as the
+    /// perspective view is at the distance of `lookat`.
+    pub ortho_height: f32,
     pub lookfrom: Point3<f32>,
     pub lookat: Point3<f32>,
     pub vup: Vector3<f32>,
+    /// A moving camera: where it is looking from, at, and which way is up when the shutter closes. Each goes in
+    /// a straight line from its value above at shutter open; None keeps it where it is.
+    pub lookfrom_end: Option<Point3<f32>>,
+    pub lookat_end: Option<Point3<f32>>,
+    pub vup_end: Option<Vector3<f32>>,
+    /// When the shutter opens and closes, in the time the scene's moving objects move over (from 0 to 1).
+    pub shutter_open: f32,
+    pub shutter_close: f32,
+    /// How open the shutter is in between.
+    pub shutter_curve: ShutterCurve,
     pub defocus_angle: f32,
     pub focus_dist: f32,
+    /// The shape of the lens's opening, which out of focus highlights take; circular by default.
+    pub aperture: Box<dyn Aperture>,
+    /// Optical vignetting: how much the lens barrel cuts into the aperture away from the image center, giving
+    /// cat's eye shaped bokeh and darker corners. 0 (the default) leaves it whole; at 1 the corners see nothing.
+    pub cat_eye: f32,
+    /// A physical camera, whose lens, sensor and exposure settings take the place of `vfov` (for the perspective and
+    /// fisheye projections) and `defocus_angle`, and scale the image's brightness; see `PhysicalCamera`.
+    pub physical: Option<PhysicalCamera>,
+    /// A stereo rig, rendering a view for each eye into a film twice the size of the image; see `StereoRig`.
+    pub stereo: Option<StereoRig>,
+    /// Seed for the sample generator; the same seed gives the same image.
+    pub seed: u64,
+    /// How the light arriving along each camera ray is estimated.
+    pub integrator: Box<dyn Integrator>,
+    /// How each sample is shared out between the pixels around where it was taken.
+    pub filter: Box<dyn Filter>,
+    /// Where the random numbers for each camera sample come from (shared with every sample's `Rng`).
+    pub sampler: Arc<dyn Sampler>,
+    /// Adaptive sampling: a pixel stops taking samples once the standard error of its mean is below this fraction of the mean
+    /// (see `Pixel::relative_error`), so `samples_per_pixel` becomes the most a pixel takes. 0 turns it off.
+    pub adaptive_threshold: f32,
+    /// The fewest samples a pixel takes with adaptive sampling, so a pixel isn't judged by a few samples that happen to agree.
+    pub min_samples_per_pixel: u32,
+    /// Progressive rendering: take the samples in passes of this many samples per pixel over the whole image,
+    /// so the image so far can be looked at after each (see `render_progressive`). 0 takes them all in one pass.
+    pub samples_per_pass: u32,
+    /// Photons traced per pass by the photon mapping integrator; 0 traces one per pixel.
+    pub photons: u32,
+    /// The radius photons are gathered within on the first pass (it shrinks on later ones);
+    /// 0 uses five pixels' width at the distance of `lookat`.
+    pub photon_radius: f32,
     image_height: f32,
-    pixel_samples_scale: f32,
     center: Point3<f32>,
     pixel00_loc: Point3<f32>,
     pixel_delta_u: Vector3<f32>,
@@ -39,6 +103,7 @@ pub struct Camera {
     w: Vector3<f32>,
     defocus_disk_u: Vector3<f32>,
     defocus_disk_v: Vector3<f32>,
+    lens_radius: f32,
 }
 
 impl Camera {
@@ -48,15 +113,36 @@ impl Camera {
             image_width: 100.0,
             samples_per_pixel: 10,
             max_depth: 10,
+            russian_roulette_depth: 3,
             background: Colour::new(),
             vfov: 90,
+            projection: Projection::Perspective,
+            ortho_height: 0.0,
             lookfrom: Point3::origin(),
             lookat: Point3::new(0.0, 0.0, -1.0),
             vup: Vector3::new(0.0, 1.0, 0.0),
+            lookfrom_end: None,
+            lookat_end: None,
+            vup_end: None,
+            shutter_open: 0.0,
+            shutter_close: 1.0,
+            shutter_curve: ShutterCurve::Box,
             defocus_angle: 0.0,
             focus_dist: 10.0,
+            aperture: Box::new(CircularAperture),
+            cat_eye: 0.0,
+            physical: None,
+            stereo: None,
+            seed: 0,
+            integrator: Box::new(NEEPathTracer),
+            filter: Box::new(BoxFilter::new(0.5)),
+            sampler: Arc::new(Sobol),
+            samples_per_pass: 0,
+            adaptive_threshold: 0.0,
+            min_samples_per_pixel: 16,
+            photons: 0,
+            photon_radius: 0.0,
             image_height: 0.0,
-            pixel_samples_scale: 0.0,
             center: Point3::origin(),
             pixel00_loc: Point3::origin(),
             pixel_delta_u: Vector3::zeros(),
@@ -66,9 +152,12 @@ impl Camera {
             w: Vector3::zeros(),
             defocus_disk_u: Vector3::zeros(),
             defocus_disk_v: Vector3::zeros(),
+            lens_radius: 0.0,
         }
     }
-    pub fn initialise(&mut self) {
+    pub fn initialise(&mut self) -> Result<()> {
+        self.validate()?;
+
         // Ensure dimensions are correctly set
         self.image_height = self.image_width / self.aspect_ratio;
         if self.image_height < 1.0 {
@@ -76,14 +165,20 @@ impl Camera {
         }
         
         if self.samples_per_pixel == 0 {self.samples_per_pixel = 100}
-        self.pixel_samples_scale = 1.0/(self.samples_per_pixel as f32);
     
         self.center = self.lookfrom; 
 
         // Camera setup
-        let theta = degrees_to_radians(self.vfov as f32);
+        // the image plane lies on the plane of focus; the angular projections only use it to size things by pixels
+        let theta = self.fov();
         let h = (theta/2.0).tan();
-        let viewport_height = 2.0*h*self.focus_dist;
+        let viewport_height = match self.projection {
+            Projection::Orthographic if self.ortho_height > 0.0 => self.ortho_height,
+            // as tall as the perspective view is where it's looking
+            Projection::Orthographic => 2.0*h*(self.lookat - self.lookfrom).norm(),
+            Projection::Perspective => 2.0*h*self.focus_dist,
+            _ => 2.0*self.focus_dist,
+        };
         let viewport_width = viewport_height * (self.image_width / self.image_height);
     
         // basis vecs for camera coord frame
@@ -118,11 +213,83 @@ impl Camera {
     
         self.pixel00_loc = viewport_upper_left + 0.5 * (self.pixel_delta_u + self.pixel_delta_v);
     
-        let defocus_radius = self.focus_dist * (degrees_to_radians(self.defocus_angle/2.0)).tan();
-        self.defocus_disk_u = self.u*defocus_radius;
-        self.defocus_disk_v = self.v*defocus_radius;
+        self.lens_radius = match self.physical {
+            Some(physical) => physical.lens_radius(),
+            None => self.focus_dist * (degrees_to_radians(self.defocus_angle/2.0)).tan(),
+        };
+        self.defocus_disk_u = self.u*self.lens_radius;
+        self.defocus_disk_v = self.v*self.lens_radius;
         // Double-check deltas
         //println!("Pixel deltas: u = {:?}, v = {:?}", self.pixel_delta_u, self.pixel_delta_v);
+        Ok(())
+    }
+
+    /// Checks the settings make a usable camera, so a bad scene fails before the render starts.
+    fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(Error::InvalidScene(msg));
+
+        if self.image_width.is_nan() || self.image_width < 1.0 {return invalid(format!("image width must be at least 1 pixel, got {}", self.image_width))}
+        if !self.aspect_ratio.is_finite() || self.aspect_ratio <= 0.0 {return invalid(format!("aspect ratio must be positive, got {}", self.aspect_ratio))}
+        if let Some(physical) = &self.physical {
+            let PhysicalCamera {focal_length, f_number, shutter_speed, iso, sensor_width, metres_per_unit} = *physical;
+            for (name, value) in [("focal length", focal_length), ("f-number", f_number), ("shutter speed", shutter_speed),
+                                  ("ISO", iso), ("sensor width", sensor_width), ("metres per unit", metres_per_unit)] {
+                if !value.is_finite() || value <= 0.0 {return invalid(format!("{} must be positive, got {}", name, value))}
+            }
+            if physical.image_distance(self.focus_dist).is_none() {
+                return invalid(format!("a {}mm lens can't focus as close as {}", focal_length, self.focus_dist));
+            }
+            if self.projection == Projection::FisheyeEquisolid && physical.vfov(self.projection, self.aspect_ratio, self.focus_dist).is_none() {
+                return invalid(format!("a {}mm sensor is too tall for an equisolid fisheye with a {}mm lens", sensor_width / self.aspect_ratio, focal_length));
+            }
+        }
+        if let Some(stereo) = &self.stereo {
+            if !stereo.interocular.is_finite() || stereo.interocular < 0.0 {return invalid(format!("interocular distance can't be negative, got {}", stereo.interocular))}
+            if !stereo.convergence.is_finite() || stereo.convergence < 0.0 {return invalid(format!("convergence distance can't be negative, got {}", stereo.convergence))}
+            if self.projection == Projection::Orthographic {return invalid("a stereo rig can't see depth with the orthographic projection".to_string())}
+        }
+        // the physical camera's lens and sensor give the perspective and fisheye projections their field of view
+        let physical_fov = self.physical.is_some() && self.projection != Projection::Orthographic;
+        match self.projection {
+            _ if physical_fov => {}
+            Projection::Perspective => if self.vfov == 0 || self.vfov >= 180 {return invalid(format!("vfov must be between 0 and 180 degrees, got {}", self.vfov))},
+            Projection::Orthographic => {
+                if !self.ortho_height.is_finite() || self.ortho_height < 0.0 {return invalid(format!("orthographic height can't be negative, got {}", self.ortho_height))}
+                if self.ortho_height == 0.0 && (self.vfov == 0 || self.vfov >= 180) {return invalid(format!("vfov must be between 0 and 180 degrees to size the orthographic view, got {}", self.vfov))}
+            }
+            Projection::FisheyeEquidistant | Projection::FisheyeEquisolid => if self.vfov == 0 || self.vfov > 360 {return invalid(format!("a fisheye's vfov must be between 0 and 360 degrees, got {}", self.vfov))},
+            Projection::Equirectangular => {}
+        }
+        if self.focus_dist.is_nan() || self.focus_dist <= 0.0 {return invalid(format!("focus distance must be positive, got {}", self.focus_dist))}
+        if !self.adaptive_threshold.is_finite() || self.adaptive_threshold < 0.0 {return invalid(format!("adaptive threshold can't be negative, got {}", self.adaptive_threshold))}
+        if !self.photon_radius.is_finite() || self.photon_radius < 0.0 {return invalid(format!("photon radius can't be negative, got {}", self.photon_radius))}
+        if !(0.0..=1.0).contains(&self.cat_eye) {return invalid(format!("cat eye must be between 0 and 1, got {}", self.cat_eye))}
+        if self.defocus_angle < 0.0 || self.defocus_angle >= 180.0 {return invalid(format!("defocus angle must be between 0 and 180 degrees, got {}", self.defocus_angle))}
+
+        if !self.shutter_open.is_finite() || !self.shutter_close.is_finite() || self.shutter_close < self.shutter_open {
+            return invalid(format!("the shutter must close after it opens, got {} to {}", self.shutter_open, self.shutter_close));
+        }
+
+        let view_dir = self.lookfrom - self.lookat;
+        if near_zero(view_dir) {return invalid("lookfrom and lookat are the same point".to_string())}
+        if near_zero(self.vup.cross(&view_dir)) {return invalid("vup is parallel to the view direction".to_string())}
+        if self.is_moving() {
+            let view_dir_end = self.lookfrom_end.unwrap_or(self.lookfrom) - self.lookat_end.unwrap_or(self.lookat);
+            if near_zero(view_dir_end) {return invalid("lookfrom and lookat end at the same point".to_string())}
+            if near_zero(self.vup_end.unwrap_or(self.vup).cross(&view_dir_end)) {return invalid("vup ends parallel to the view direction".to_string())}
+        }
+        Ok(())
+    }
+
+    // the field of view across the image height, in radians: the physical camera's if it has one, otherwise vfov
+    fn fov(&self) -> f32 {
+        let physical = self.physical.and_then(|physical| physical.vfov(self.projection, self.aspect_ratio, self.focus_dist));
+        return physical.unwrap_or(degrees_to_radians(self.vfov as f32));
+    }
+
+    /// What the rendered radiance is multiplied by for the image: the physical camera's exposure, or 1 without one.
+    pub fn exposure(&self) -> f32 {
+        return self.physical.map_or(1.0, |physical| physical.exposure());
     }
 
     pub fn set_image_size(&mut self, width: f32) {
@@ -130,73 +297,269 @@ impl Camera {
         self.image_height = width / self.aspect_ratio;
     }
 
-    pub fn get_ray(&self, i: usize, j: usize) -> Ray {
-        let offset = sample_square();
-        let pixel_sample = self.pixel00_loc
-                            + ((i as f32 + offset.x) * self.pixel_delta_u)
-                            + ((j as f32 + offset.y) * self.pixel_delta_v);
-        
-        let ray_origin = if self.defocus_angle <= 0.0 {self.center} else {self.defocus_disk_sample()};
+    pub fn get_ray(&self, i: usize, j: usize, rng: &mut Rng) -> Option<Ray> {
+        let offset = sample_square(rng);
+        self.get_ray_at(i as f32 + offset.x, j as f32 + offset.y, rng)
+    }
+
+    /// A ray through the point `x` pixels across and `y` pixels down from the center of the top left pixel,
+    /// or None where the projection doesn't cover the image (outside a fisheye's image circle).
+    pub fn get_ray_at(&self, x: f32, y: f32, rng: &mut Rng) -> Option<Ray> {
+        // with a stereo rig, the film holds a view for each eye
+        let (eye, view) = self.view_at(x, y);
+        let (x, y) = (x - view.x as f32, y - view.y as f32);
+        if self.projection.is_angular() {return self.get_angular_ray(x, y, eye, rng)}
+
+        let mut pixel_sample = self.pixel00_loc + (x * self.pixel_delta_u) + (y * self.pixel_delta_v);
+
+        let (lens_x, lens_y) = self.sample_aperture(x, y, rng)?;
+        let mut lens_center = match self.projection {
+            // straight back from the plane of focus, to a lens the size of the image
+            Projection::Orthographic => pixel_sample + self.focus_dist*self.w,
+            _ => self.center,
+        };
+        if let (Some(eye), Some(stereo)) = (eye, &self.stereo) {
+            // off-axis: the eye moves sideways and its image plane moves with it, less the nearer it is than where
+            // the eyes converge, so the views line up at that distance
+            let convergence = if stereo.convergence > 0.0 {stereo.convergence} else {self.focus_dist};
+            let offset = eye.side() * stereo.interocular / 2.0 * self.u;
+            lens_center += offset;
+            pixel_sample += offset * (1.0 - self.focus_dist / convergence);
+        }
+        let ray_origin = lens_center + lens_x*self.defocus_disk_u + lens_y*self.defocus_disk_v;
         let ray_direction = pixel_sample - ray_origin;
-        let ray_time = random_f32();
+        let ray_time = self.sample_time(rng);
 
-        Ray::new_from(ray_origin, ray_direction, ray_time)
+        Some(self.ray_at_time(ray_origin, ray_direction, ray_time))
     }
-    pub fn render(&mut self, world: &Arc<dyn Hittable + Send + Sync>) -> io::Result<()> {
-        self.initialise();
 
-        // Open file and write a P6 (binary) PPM header
-        let mut file = File::create("rendered_image.ppm")?;
-        let header = format!("P6\n{} {}\n255\n", self.image_width, self.image_height);
-        file.write_all(header.as_bytes())?;
+    // the angular projections have no image plane: each pixel is a direction, and with defocus blur the lens is a disk
+    // facing that direction, aimed at the point focus_dist along it (so what's in focus is a sphere around the camera)
+    fn get_angular_ray(&self, x: f32, y: f32, eye: Option<Eye>, rng: &mut Rng) -> Option<Ray> {
+        let (image_x, image_y) = ((x + 0.5) / self.image_width - 0.5, 0.5 - (y + 0.5) / self.image_height);
+        let fov = self.fov();
+        let local = self.projection.direction(image_x, image_y, self.image_width / self.image_height, fov)?;
+        let direction = local.x*self.u + local.y*self.v + local.z*self.w;
 
-        // Shared counter for progress reporting
-        let progress = Arc::new(AtomicUsize::new(0));
+        let mut center = self.center;
+        if let (Some(eye), Some(stereo)) = (eye, &self.stereo) {
+            let across = match self.projection {
+                // omni-directional stereo: the eyes sit across the direction looked in, on a circle around the camera,
+                // moving in (with the cosine of the latitude) to meet straight up and down
+                Projection::Equirectangular => -local.z*self.u + local.x*self.w,
+                _ => self.u,
+            };
+            center += eye.side() * stereo.interocular / 2.0 * across;
+        }
 
-        // Parallel compute each scanline as a Vec<u8> of RGB bytes
-        let rendered_rows: Vec<Vec<u8>> = (0..self.image_height as usize)
-            .into_par_iter()
-            .map(|j| {
-                // Pre-allocate exactly width * 3 bytes for this row
-                let mut row_buf = Vec::with_capacity(self.image_width as usize * 3);
-                let my_world = Arc::clone(&world);
+        // the lens turned to face the direction, keeping the camera's up as near up as it can
+        let (lens_x, lens_y) = self.sample_aperture(x, y, rng)?;
+        let right = direction.cross(&self.v).try_normalize(1e-6).unwrap_or(self.u);
+        let up = right.cross(&direction);
+        let ray_origin = center + self.defocus_disk_u.norm() * (lens_x*right + lens_y*up);
+        let ray_direction = center + self.focus_dist*direction - ray_origin;
+        let ray_time = self.sample_time(rng);
 
-                for i in 0..self.image_width as usize {
-                    // Accumulate samples for this pixel
-                    let mut pixel_colour = Colour::new();
-                    for _ in 0..self.samples_per_pixel {
-                        let ray = self.get_ray(i, j);
-                        pixel_colour.0 += self.ray_colour(&ray, self.max_depth, &my_world).0;
-                    }
-                    pixel_colour.0 *= self.pixel_samples_scale;
+        Some(self.ray_at_time(ray_origin, ray_direction, ray_time))
+    }
+
+    /// The size of the film the camera renders into: the image, or with a stereo rig, both eyes' views of it.
+    pub fn film_size(&self) -> (usize, usize) {
+        let (width, height) = (self.image_width as usize, self.image_height as usize);
+        match &self.stereo {
+            Some(stereo) => stereo.layout.film_size(width, height),
+            None => (width, height),
+        }
+    }
+
+    // which eye (if there is a stereo rig) sees the point (x, y) of the film, and the part of the film it renders
+    fn view_at(&self, x: f32, y: f32) -> (Option<Eye>, Tile) {
+        let (width, height) = self.film_size();
+        let Some(stereo) = &self.stereo else {return (None, Tile {x: 0, y: 0, width, height})};
+        let [left, right] = stereo.layout.views(width, height);
+        // (x, y) is from the center of the top left pixel
+        if x + 0.5 >= right.x as f32 && y + 0.5 >= right.y as f32 {return (Some(Eye::Right), right)}
+        return (Some(Eye::Left), left);
+    }
+
+    /// A time for a sample, while the shutter is open and weighted by its curve.
+    pub fn sample_time(&self, rng: &mut Rng) -> f32 {
+        return self.shutter_open + self.shutter_curve.sample(random_f32(rng)) * (self.shutter_close - self.shutter_open);
+    }
+
+    /// Whether the camera moves while the shutter is open (see `lookfrom_end`).
+    pub fn is_moving(&self) -> bool {
+        self.lookfrom_end.is_some() || self.lookat_end.is_some() || self.vup_end.is_some()
+    }
+
+    // rays are worked out for where the camera is when the shutter opens; a moving camera then carries them
+    // along with it, to where it has got to by `time`
+    fn ray_at_time(&self, origin: Point3<f32>, direction: Vector3<f32>, time: f32) -> Ray {
+        if !self.is_moving() {return Ray::new_from(origin, direction, time)}
+
+        let shutter = self.shutter_close - self.shutter_open;
+        let t = if shutter > 0.0 {((time - self.shutter_open) / shutter).clamp(0.0, 1.0)} else {0.0};
+        let lookfrom = self.lookfrom.coords.lerp(&self.lookfrom_end.unwrap_or(self.lookfrom).coords, t);
+        let lookat = self.lookat.coords.lerp(&self.lookat_end.unwrap_or(self.lookat).coords, t);
+        let vup = self.vup.lerp(&self.vup_end.unwrap_or(self.vup), t);
+
+        let w = (lookfrom - lookat).normalize();
+        let u = vup.cross(&w).normalize();
+        let v = w.cross(&u);
+        let moved = |d: Vector3<f32>| d.dot(&self.u)*u + d.dot(&self.v)*v + d.dot(&self.w)*w;
+        return Ray::new_from(Point3::from(lookfrom) + moved(origin - self.center), moved(direction), time);
+    }
+
+    /// Renders the world with the camera's integrator and returns the film holding the linear radiance of every pixel.
+    /// `lights` are sampled directly by integrators that do so; it should hold every emissive object in the world,
+    /// since any left out are then only seen by the camera and specular bounces. Leave it empty to only find lights by bouncing.
+    pub fn render(&mut self, world: &Arc<dyn Hittable + Send + Sync>, lights: &HittableList) -> Result<Film> {
+        self.render_progressive(world, lights, |_, _| Ok(()))
+    }
+
+    /// Like `render`, but the samples are taken in passes over the whole image of `samples_per_pass` samples per pixel,
+    /// and `on_pass` is given the film so far and how many samples per pixel it has taken after each one (and after
+    /// the last), for example to write it to disk. An error from `on_pass` stops the render.
+    pub fn render_progressive(&mut self, world: &Arc<dyn Hittable + Send + Sync>, lights: &HittableList,
+                              on_pass: impl FnMut(&Film, u32) -> Result<()>) -> Result<Film> {
+        self.initialise()?;
+        let (width, height) = self.film_size();
+        let film = Film::new(width, height);
+        return self.render_passes(world, lights, film, 0, on_pass);
+    }
+
+    /// Carries on the render saved in `checkpoint`, taking the samples it hadn't got to yet up to `samples_per_pixel`
+    /// (which can be more than it was started with), as `render_progressive`.
+    /// The camera needs the settings the checkpoint was rendered with (see `Checkpoint::read`, which checks them).
+    pub fn resume(&mut self, world: &Arc<dyn Hittable + Send + Sync>, lights: &HittableList, checkpoint: Checkpoint,
+                  on_pass: impl FnMut(&Film, u32) -> Result<()>) -> Result<Film> {
+        self.initialise()?;
+        let size = self.film_size();
+        if (checkpoint.film.width(), checkpoint.film.height()) != size {
+            return Err(Error::InvalidSettings(format!("the checkpoint is {}x{} pixels, but the image is {}x{}",
+                checkpoint.film.width(), checkpoint.film.height(), size.0, size.1)));
+        }
+        if checkpoint.samples_done > self.samples_per_pixel {
+            return Err(Error::InvalidSettings(format!("the checkpoint already has {} samples per pixel, more than the {} asked for",
+                checkpoint.samples_done, self.samples_per_pixel)));
+        }
+        return self.render_passes(world, lights, checkpoint.film, checkpoint.samples_done, on_pass);
+    }
 
-                    // Apply gamma correction (gamma = 2.0) and convert to u8
-                    let r = (pixel_colour.r().sqrt() * 255.999) as u8;
-                    let g = (pixel_colour.g().sqrt() * 255.999) as u8;
-                    let b = (pixel_colour.b().sqrt() * 255.999) as u8;
+    /// A hash of every setting that changes the rendered image, so a checkpoint is only resumed by a camera that would
+    /// have carried on with the same samples. Not `samples_per_pass`, which only changes how often it is looked at,
+    /// nor `samples_per_pixel` (unless the sampler depends on it), so a resumed render can take more samples.
+    pub fn settings_hash(&self) -> u64 {
+        let samples_per_pixel = if self.sampler.depends_on_count() {Some(self.samples_per_pixel)} else {None};
+        let settings = format!("{:?}", (
+            (self.aspect_ratio, self.image_width, samples_per_pixel, self.max_depth, self.russian_roulette_depth, self.background.0),
+            (self.vfov, self.lookfrom, self.lookat, self.vup, self.lookfrom_end, self.lookat_end, self.vup_end, self.seed),
+            (self.projection.name(), self.ortho_height, self.defocus_angle, self.focus_dist, self.aperture.settings(), self.cat_eye, self.physical, self.stereo),
+            (self.shutter_open, self.shutter_close, self.shutter_curve.name()),
+            (self.integrator.name(), self.filter.name(), self.filter.radius(), self.sampler.name()),
+            (self.adaptive_threshold, self.min_samples_per_pixel, self.photons, self.photon_radius),
+        ));
+        return hash_bytes(settings.as_bytes());
+    }
+
+    // takes samples first_sample.. of samples_per_pixel for every pixel, adding them to `film`
+    fn render_passes(&self, world: &Arc<dyn Hittable + Send + Sync>, lights: &HittableList, mut film: Film,
+                     mut first_sample: u32, mut on_pass: impl FnMut(&Film, u32) -> Result<()>) -> Result<Film> {
+        film.set_exposure(self.exposure());
+        let tiles = film.tiles(TILE_SIZE);
+        let spp = self.samples_per_pixel;
+
+        // each pass takes its share of the sample indices, so a pixel's samples don't depend on how many passes there are.
+        // Passes end wherever the integrator starts a new pass of its own, and every samples_per_pass samples
+        let integrator_passes = self.integrator.passes(spp).clamp(1, spp);
+        let integrator_pass_starts: Vec<u32> = (0..integrator_passes).map(|pass| pass * spp / integrator_passes).collect();
+        let mut pass_ends: Vec<u32> = integrator_pass_starts.iter().skip(1).copied().chain([spp]).collect();
+        if self.samples_per_pass > 0 {pass_ends.extend((self.samples_per_pass..spp).step_by(self.samples_per_pass as usize))}
+        pass_ends.sort_unstable();
+        pass_ends.dedup();
+        // when resuming, the passes already in the film
+        pass_ends.retain(|&end| end > first_sample);
+        if pass_ends.is_empty() {on_pass(&film, first_sample)?}
 
-                    row_buf.push(r);
-                    row_buf.push(g);
-                    row_buf.push(b);
+        let adaptive = self.adaptive_threshold > 0.0;
+        let min_samples = self.min_samples_per_pixel.max(2);
+        let filtering = self.filters_samples();
+
+        for (pass, &end_sample) in pass_ends.iter().enumerate() {
+            let splats = SplatBuffer::new(film.width(), film.height());
+            let filtered = FilterBuffer::new(film.width(), film.height());
+            let ctx = RenderContext {
+                world: world.as_ref(),
+                lights,
+                camera: self,
+                splats: &splats,
+                background: self.background.clone(),
+                max_depth: self.max_depth,
+                russian_roulette_depth: self.russian_roulette_depth,
+            };
+            if let Some(integrator_pass) = integrator_pass_starts.iter().position(|&start| start == first_sample) {
+                self.integrator.start_pass(&ctx, integrator_pass as u32);
+            }
+
+            // Shared counter for progress reporting
+            let progress = AtomicUsize::new(0);
+            // samples the projection or the lens barrel block don't trace anything
+            let camera_rays = AtomicU64::new(0);
+
+            // Parallel compute each tile into a copy of its pixels, then put them back into the film
+            let rendered: Vec<Vec<Pixel>> = tiles.par_iter().map(|tile| {
+                let mut pixels = film.tile_pixels(tile);
+                for (index, pixel) in pixels.iter_mut().enumerate() {
+                    let (i, j) = (tile.x + index % tile.width, tile.y + index / tile.width);
+                    let pixel_index = (j*film.width() + i) as u64;
+                    for s in first_sample..end_sample {
+                        if adaptive && pixel.samples >= min_samples && pixel.relative_error() <= self.adaptive_threshold {break}
+                        let id = SampleId {seed: self.seed, pixel: pixel_index, index: s, count: spp};
+                        let mut rng = Rng::for_camera_sample(&self.sampler, id);
+                        let offset = sample_square(&mut rng);
+                        let (x, y) = (i as f32 + offset.x, j as f32 + offset.y);
+                        let colour = match self.get_ray_at(x, y, &mut rng) {
+                            Some(ray) => {
+                                camera_rays.fetch_add(1, Ordering::Relaxed);
+                                self.integrator.ray_colour(&ray, &ctx, &mut rng)
+                            }
+                            None => Colour::new(),
+                        };
+                        if filtering {self.add_filtered(&filtered, x, y, &colour)}
+                        pixel.add_sample(&colour);
+                    }
                 }
 
                 // Update and print progress
                 let done = progress.fetch_add(1, Ordering::Relaxed) + 1;
-                if done % 1 == 0 || done == self.image_height as usize {
-                    println!("Progress: {}/{}", done, self.image_height);
-                }
+                print!("\rPass {}/{}: {}/{} tiles", pass + 1, pass_ends.len(), done, tiles.len());
+                let _ = std::io::stdout().flush();
+                pixels
+            }).collect();
 
-                row_buf
-            })
-            .collect();
+            for (tile, pixels) in tiles.iter().zip(&rendered) {
+                film.set_tile(tile, pixels);
+            }
+            if filtering {film.add_filtered(&filtered)}
 
-        // Write each row’s raw RGB bytes sequentially
-        for row in rendered_rows {
-            file.write_all(&row)?;
+            // one light path was traced for each camera ray
+            if self.integrator.splats() {
+                film.add_splats(&splats, camera_rays.into_inner());
+            }
+
+            let last = end_sample == spp;
+            if last || (self.samples_per_pass > 0 && end_sample % self.samples_per_pass == 0) {on_pass(&film, end_sample)?}
+            first_sample = end_sample;
         }
 
-        println!("\rDone.               ");
-        Ok(())
+        println!();
+        if adaptive {
+            let average = film.total_samples() as f32 / (film.width() * film.height()) as f32;
+            println!("Adaptive sampling took {:.1} samples per pixel on average (at most {})", average, spp);
+        }
+
+        println!("Done.");
+        Ok(film)
     }
     /* 
     pub fn render(&mut self, world: &Arc<dyn Hittable + Send + Sync>) -> io::Result<()> {
@@ -246,54 +609,323 @@ impl Camera {
     }
     */
 
-    fn defocus_disk_sample(&self) -> Point3<f32> {
-        let p = random_in_unit_disk();
-        return self.center + (p[0]*self.defocus_disk_u) + (p[1]*self.defocus_disk_v);
+    /// A point on the lens, picked the way `get_ray` picks ray origins.
+    /// Optical vignetting isn't applied, as the point isn't for any pixel in particular; `importance` does that.
+    pub fn sample_lens(&self, rng: &mut Rng) -> Point3<f32> {
+        if self.lens_radius <= 0.0 {return self.center}
+        let (x, y) = self.aperture.sample(rng);
+        return self.center + x*self.defocus_disk_u + y*self.defocus_disk_v;
+    }
+
+    // a point on the aperture, with the lens's circle as the unit circle (the center for a pinhole),
+    // or None if the lens barrel hides it from the point (x, y) of the image
+    fn sample_aperture(&self, x: f32, y: f32, rng: &mut Rng) -> Option<(f32, f32)> {
+        if self.lens_radius <= 0.0 {return Some((0.0, 0.0))}
+        let p = self.aperture.sample(rng);
+        if self.vignetted(x, y, p) {return None}
+        return Some(p);
     }
 
+    // optical vignetting: away from the center of the image, the lens barrel cuts the aperture down to where it
+    // overlaps a unit circle shifted out towards that side, by 2 * cat_eye at the corners
+    fn vignetted(&self, x: f32, y: f32, (lens_x, lens_y): (f32, f32)) -> bool {
+        if self.cat_eye <= 0.0 || self.lens_radius <= 0.0 {return false}
+        let half_diagonal = (self.image_width.powi(2) + self.image_height.powi(2)).sqrt() / 2.0;
+        let shift_x = 2.0 * self.cat_eye * (x + 0.5 - self.image_width / 2.0) / half_diagonal;
+        let shift_y = 2.0 * self.cat_eye * (self.image_height / 2.0 - (y + 0.5)) / half_diagonal;
+        return (lens_x - shift_x).powi(2) + (lens_y - shift_y).powi(2) > 1.0;
+    }
 
-    fn ray_colour(&self, ray: &Ray, depth: u32, world: &Arc<dyn Hittable + Send + Sync>) -> Colour {
-        if depth <= 0 {return Colour::new()};
-        //println!("*");
+    /// Whether light paths can be joined straight to the camera, with `importance`. Only the perspective projection
+    /// has a lens they can be aimed at through the image plane, and only while the camera stays still with one eye.
+    /// Optical vignetting cuts some of the lens away from some pixels, which `ray_pdf` doesn't follow, so not with that.
+    pub fn can_connect_to_lens(&self) -> bool {
+        self.projection == Projection::Perspective && !self.is_moving() && self.stereo.is_none()
+            && !(self.cat_eye > 0.0 && self.lens_radius > 0.0)
+    }
 
-        let my_world = Arc::clone(&world);
+    /// The density, per unit solid angle, of camera rays (through any pixel) leaving the lens along `direction`.
+    /// Rays are spread evenly over the image on the plane of focus, so the density grows towards the edges.
+    /// 0 for projections other than the perspective one (see `can_connect_to_lens`).
+    pub fn ray_pdf(&self, direction: &Vector3<f32>) -> f32 {
+        if !self.can_connect_to_lens() {return 0.0}
+        let Some(direction) = direction.try_normalize(0.0) else {return 0.0};
+        let cos_theta = -direction.dot(&self.w);
+        if cos_theta <= 0.0 {return 0.0}
 
-        if let Some(hit_rec) = my_world.hit(ray, &Interval::new(0.001, f32::INFINITY)) {
-            // if we have a hit
-            let colour_from_emmision = hit_rec.mat.emitted(hit_rec.u, hit_rec.v, hit_rec.p);
-                
-            //set face normal
-            if let Some((attenuation, scattered)) = hit_rec.mat.scatter(&ray, &hit_rec) { 
-                // if we have a scatter
-                let r_col = self.ray_colour(&scattered, depth-1, &my_world);
-                let colour_from_scatter = Colour::new_from(attenuation.r()*r_col.r(), attenuation.g()*r_col.g(), attenuation.b()*r_col.b());
-                return Colour::new_from(colour_from_emmision.r() + colour_from_scatter.r(), colour_from_emmision.g() + colour_from_scatter.g(), colour_from_emmision.b() + colour_from_scatter.b());
-                //return Colour::new_from(attenuation.r()*r_col.r(), attenuation.g()*r_col.g(), attenuation.b()*r_col.b())
-            }
-            else {
-                return colour_from_emmision;
+        let film_area = (self.image_width.floor() * self.pixel_delta_u.norm()) * (self.image_height.floor() * self.pixel_delta_v.norm());
+        return self.focus_dist * self.focus_dist / (film_area * cos_theta.powi(3));
+    }
+
+    /// For light leaving `point` towards `lens_point`: the pixel it lands in and the camera's importance
+    /// (its response to light from that direction, matching `ray_pdf`), or None if it misses the image.
+    pub fn importance(&self, lens_point: &Point3<f32>, point: &Point3<f32>) -> Option<(usize, usize, f32)> {
+        if !self.can_connect_to_lens() {return None}
+        let direction = point - lens_point;
+        let depth = -direction.dot(&self.w);
+        if depth <= 0.0 {return None}
+
+        // where the ray from the lens crosses the plane of focus, in pixels from the top left corner of the image
+        let on_focus_plane = lens_point + direction * (self.focus_dist / depth);
+        let from_corner = on_focus_plane - (self.pixel00_loc - 0.5 * (self.pixel_delta_u + self.pixel_delta_v));
+        let x = from_corner.dot(&self.pixel_delta_u) / self.pixel_delta_u.norm_squared();
+        let y = from_corner.dot(&self.pixel_delta_v) / self.pixel_delta_v.norm_squared();
+        if x < 0.0 || y < 0.0 || x >= self.image_width.floor() || y >= self.image_height.floor() {return None}
+
+        return Some((x as usize, y as usize, self.ray_pdf(&direction)));
+    }
+
+    /// How many photons to trace per pass; see `photons`.
+    pub fn photons_per_pass(&self) -> u64 {
+        if self.photons > 0 {return self.photons as u64}
+        return self.image_width.floor() as u64 * self.image_height.floor() as u64;
+    }
+
+    /// The photon gather radius for the first pass; see `photon_radius`.
+    pub fn initial_photon_radius(&self) -> f32 {
+        if self.photon_radius > 0.0 {return self.photon_radius}
+        // pixel_delta_u is a pixel's width on the plane of focus
+        let distance = (self.lookat - self.lookfrom).norm();
+        let pixel_width = match self.projection {
+            Projection::Perspective => self.pixel_delta_u.norm() * distance / self.focus_dist,
+            Projection::Orthographic => self.pixel_delta_u.norm(),
+            // the angle a pixel covers at the center of the image
+            Projection::Equirectangular => std::f32::consts::PI / self.image_height * distance,
+            _ => self.fov() / self.image_height * distance,
+        };
+        return 5.0 * pixel_width;
+    }
+
+    // the default box filter counts each sample towards the pixel it was taken in and nothing else, which is
+    // just the pixel's mean, so it's resolved from that without the rounding of the filter buffer
+    fn filters_samples(&self) -> bool {
+        return !(self.filter.name() == "box" && self.filter.radius() == 0.5);
+    }
+
+    // adds a sample taken at (x, y), in pixels from the center of the top left pixel, to every pixel within the filter's reach
+    // (without reaching across into the other eye's view)
+    fn add_filtered(&self, filtered: &FilterBuffer, x: f32, y: f32, colour: &Colour) {
+        let radius = self.filter.radius();
+        let (_, view) = self.view_at(x, y);
+        let columns = (x - radius).ceil().max(view.x as f32) as usize..=((x + radius).floor() as usize).min(view.x + view.width - 1);
+        let rows = (y - radius).ceil().max(view.y as f32) as usize..=((y + radius).floor() as usize).min(view.y + view.height - 1);
+        for j in rows {
+            for i in columns.clone() {
+                let weight = self.filter.evaluate(i as f32 - x, j as f32 - y);
+                filtered.add(i, j, colour, weight);
             }
-            //else {println!("Hit no scatter")};
-            
-            //return Colour::new()
-        }
-        else {
-            //no hit so return background colour
-            return self.background.clone();
         }
-        //println!("*");
-        // else draw sky/background
+    }
+}
 
-        /*
-        let unit_direction = ray.direction().normalize();
-        let a = 0.5 * (unit_direction.y + 1.0);
-        let ans = (1.0-a)*Colour::new_from(1.0, 1.0, 1.0).0 + a*Colour::new_from(0.5, 0.7, 1.0).0;
-        Colour::new_from(ans[0], ans[1], ans[2])
-        */
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
-fn sample_square() -> Vector3<f32> {
-    Vector3::new(random_f32() - 0.5, random_f32() - 0.5, 0.0)
+fn sample_square(rng: &mut Rng) -> Vector3<f32> {
+    let (x, y) = rng.next_2d();
+    Vector3::new(x - 0.5, y - 0.5, 0.0)
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::stereo::StereoLayout;
+    use crate::filters::filter::filter_from_name;
+    use crate::integrators::integrator::integrator_from_name;
+    use crate::scenes::{self, Scene};
+
+    // the Cornell box at a size that renders in moments
+    fn small_cornell_box(integrator: &str) -> Scene {
+        let mut scene = scenes::cornell_box().unwrap();
+        scene.camera.image_width = 16.0;
+        scene.camera.samples_per_pixel = 4;
+        scene.camera.integrator = integrator_from_name(integrator).unwrap();
+        return scene;
+    }
+
+    fn pixels(film: &Film) -> Vec<Vector3<f32>> {
+        film.resolve().iter().map(|colour| colour.0).collect()
+    }
+
+    #[test]
+    fn renders_are_the_same_on_any_number_of_threads() {
+        for integrator in ["nee", "bdpt", "photon"] {
+            let films: Vec<_> = [1, 4].iter().map(|&threads| {
+                let pool = rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap();
+                let mut scene = small_cornell_box(integrator);
+                pixels(&pool.install(|| scene.render()).unwrap())
+            }).collect();
+            assert_eq!(films[0], films[1], "{} renders differently on 1 and 4 threads", integrator);
+        }
+    }
+
+    // a small camera with `filter`, and a film of its size with the same samples added through the filter
+    // and straight to the pixels: `samples` at each offset in `offsets` from every pixel's center
+    fn filter_samples(filter: Box<dyn Filter>, offsets: &[(f32, f32)], colour: impl Fn(usize, usize, usize) -> Colour) -> Film {
+        let mut camera = Camera::new();
+        camera.aspect_ratio = 1.0;
+        camera.image_width = 6.0;
+        camera.filter = filter;
+        camera.initialise().unwrap();
+
+        let mut film = Film::new(6, 6);
+        let filtered = FilterBuffer::new(6, 6);
+        for j in 0..6 {
+            for i in 0..6 {
+                for (s, (dx, dy)) in offsets.iter().enumerate() {
+                    let colour = colour(i, j, s);
+                    camera.add_filtered(&filtered, i as f32 + dx, j as f32 + dy, &colour);
+                    film.add_sample(i, j, &colour);
+                }
+            }
+        }
+        film.add_filtered(&filtered);
+        return film;
+    }
+
+    #[test]
+    fn default_box_filter_is_each_pixels_mean() {
+        assert!(!Camera::new().filters_samples());
+
+        // including samples right on the edges the pixel shares with its neighbours above and to the left
+        let offsets = [(-0.5, -0.5), (-0.5, 0.2), (0.3, -0.5), (0.0, 0.0), (0.4999, 0.4999), (-0.25, 0.45)];
+        let colour = |i: usize, j: usize, s: usize| Colour::new_from((i + 2*s) as f32, (j * s) as f32, 1.0 / (1 + s) as f32);
+        let film = filter_samples(Box::new(BoxFilter::new(0.5)), &offsets, colour);
+        for (j, row) in film.resolve().chunks(6).enumerate() {
+            for (i, resolved) in row.iter().enumerate() {
+                let mean = film.pixel(i, j).mean();
+                assert!((resolved.0 - mean.0).norm() < 1e-4, "pixel ({}, {}) is {:?} through the filter but has a mean of {:?}", i, j, resolved.0, mean.0);
+            }
+        }
+    }
+
+    #[test]
+    fn filter_weights_are_normalised() {
+        // a constant colour comes out unchanged however the samples around a pixel are weighted, negative lobes and all
+        let mut rng = Rng::new(3);
+        let offsets: Vec<(f32, f32)> = (0..16).map(|_| {
+            let offset = sample_square(&mut rng);
+            (offset.x, offset.y)
+        }).collect();
+        for name in ["box", "tent", "gaussian", "mitchell", "lanczos"] {
+            for radius in [None, Some(0.75)] {
+                let film = filter_samples(filter_from_name(name, radius).unwrap(), &offsets, |_, _, _| Colour::new_from(0.25, 0.5, 1.0));
+                for colour in film.resolve() {
+                    assert!((colour.0 - Vector3::new(0.25, 0.5, 1.0)).norm() < 1e-4, "{} filter with radius {:?} resolves to {:?}", name, radius, colour.0);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_sampling_stops_constant_pixels_at_the_minimum() {
+        // nothing to hit, so every sample is the background
+        let mut camera = Camera::new();
+        camera.aspect_ratio = 1.0;
+        camera.image_width = 8.0;
+        camera.samples_per_pixel = 64;
+        camera.adaptive_threshold = 0.01;
+        camera.min_samples_per_pixel = 4;
+        camera.background = Colour::new_from(0.3, 0.5, 0.7);
+        let world: Arc<dyn Hittable + Send + Sync> = Arc::new(HittableList::new());
+        let film = camera.render(&world, &HittableList::new()).unwrap();
+
+        for j in 0..8 {
+            for i in 0..8 {
+                assert_eq!(film.pixel(i, j).relative_error(), 0.0);
+                assert_eq!(film.pixel(i, j).samples, 4);
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_sampling_takes_every_sample_in_noisy_pixels() {
+        let mut scene = small_cornell_box("nee");
+        scene.camera.samples_per_pixel = 32;
+        scene.camera.adaptive_threshold = 0.01;
+        scene.camera.min_samples_per_pixel = 4;
+        let film = scene.render().unwrap();
+
+        // a pixel stops early only once it is under the threshold, and the diffusely lit walls never get there
+        let (width, height) = (film.width(), film.height());
+        for j in 0..height {
+            for i in 0..width {
+                let pixel = film.pixel(i, j);
+                assert!(pixel.samples == 32 || (pixel.samples >= 4 && pixel.relative_error() <= 0.01), "pixel ({}, {}) stopped at {} samples", i, j, pixel.samples);
+            }
+        }
+        assert_eq!(film.pixel(width / 2, height / 2).samples, 32);
+    }
+
+    // a camera framing the origin from `lookfrom`, with defocus blur so the lens sample is carried along too
+    fn framing_camera(lookfrom: Point3<f32>, lookat: Point3<f32>, vup: Vector3<f32>) -> Camera {
+        let mut camera = Camera::new();
+        camera.image_width = 20.0;
+        camera.aspect_ratio = 2.0;
+        camera.lookfrom = lookfrom;
+        camera.lookat = lookat;
+        camera.vup = vup;
+        camera.defocus_angle = 2.0;
+        camera.focus_dist = 4.0;
+        return camera;
+    }
+
+    fn assert_same_ray(got: &Ray, expected: &Ray) {
+        assert!((got.origin() - expected.origin()).norm() < 1e-4, "origin {:?}, expected {:?}", got.origin(), expected.origin());
+        assert!((got.direction() - expected.direction()).norm() < 1e-4, "direction {:?}, expected {:?}", got.direction(), expected.direction());
+    }
+
+    #[test]
+    fn moving_camera_matches_a_static_camera_at_each_end_of_the_shutter() {
+        let (from, at, up) = (Point3::new(0.0, 1.0, 5.0), Point3::new(0.0, 0.0, 0.0), Vector3::new(0.0, 1.0, 0.0));
+        let (from_end, at_end, up_end) = (Point3::new(3.0, 2.0, 3.0), Point3::new(1.0, 0.5, -1.0), Vector3::new(0.3, 1.0, 0.0));
+
+        let mut moving = framing_camera(from, at, up);
+        moving.lookfrom_end = Some(from_end);
+        moving.lookat_end = Some(at_end);
+        moving.vup_end = Some(up_end);
+        moving.shutter_open = 0.5;
+        moving.shutter_close = 1.5;
+        moving.initialise().unwrap();
+        let mut start = framing_camera(from, at, up);
+        start.initialise().unwrap();
+        let mut end = framing_camera(from_end, at_end, up_end);
+        end.initialise().unwrap();
+
+        for (x, y) in [(0.0, 0.0), (19.3, 9.1), (7.2, 3.4)] {
+            let seed = (x * 10.0 + y) as u64;
+            // the moving camera works its rays out where it starts, then carries them to where it is at `time`
+            let ray = start.get_ray_at(x, y, &mut Rng::new(seed)).unwrap();
+            assert_same_ray(&moving.ray_at_time(ray.origin(), ray.direction(), 0.5), &ray);
+            assert_same_ray(&moving.ray_at_time(ray.origin(), ray.direction(), 1.5), &end.get_ray_at(x, y, &mut Rng::new(seed)).unwrap());
+        }
+    }
+
+    #[test]
+    fn stereo_rays_meet_at_the_convergence_distance() {
+        for (layout, convergence) in [(StereoLayout::SideBySide, 6.0), (StereoLayout::TopBottom, 2.5), (StereoLayout::SideBySide, 0.0)] {
+            let mut camera = framing_camera(Point3::new(1.0, 2.0, 5.0), Point3::new(0.0, 0.5, 0.0), Vector3::new(0.0, 1.0, 0.0));
+            camera.defocus_angle = 0.0;
+            camera.stereo = Some(StereoRig {interocular: 0.3, convergence, layout});
+            camera.initialise().unwrap();
+            // 0 converges at the focus distance
+            let expected = if convergence > 0.0 {convergence} else {camera.focus_dist};
+
+            let [left, right] = layout.views(camera.film_size().0, camera.film_size().1);
+            for (x, y) in [(0.0, 0.0), (10.0, 5.0), (17.3, 2.6)] {
+                let mut rng = Rng::new(1);
+                let a = camera.get_ray_at(left.x as f32 + x, left.y as f32 + y, &mut rng).unwrap();
+                let b = camera.get_ray_at(right.x as f32 + x, right.y as f32 + y, &mut rng).unwrap();
+                assert!((a.origin() - b.origin()).norm() > 0.29, "the eyes aren't apart");
+
+                // where each ray gets to the convergence distance in front of the camera
+                let at_depth = |ray: &Ray| ray.at((expected - (camera.lookfrom - ray.origin()).dot(&camera.w)) / -ray.direction().dot(&camera.w));
+                let (p, q) = (at_depth(&a), at_depth(&b));
+                assert!((p - q).norm() < 1e-4, "{} rays through ({}, {}) are {} apart {} away", layout.name(), x, y, (p - q).norm(), expected);
+            }
+        }
+    }
+}
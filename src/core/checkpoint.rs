@@ -0,0 +1,215 @@
+// checkpoints: a render's film saved between passes, so a long render that is stopped can carry on where it left off
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::core::film::Film;
+use crate::error::{Error, Result};
+
+// the start of every checkpoint file, with the format version at the end
+const MAGIC: &[u8; 8] = b"RTCKPT02";
+
+/// A render saved after one of its passes: everything the film had accumulated (the pixels' sums and sample counts,
+/// the filtered sums and the splats) and how many samples per pixel it had taken. Samplers are stateless, each
+/// sample's numbers come from its seed, pixel and index, so that is all it takes to carry on with the same samples
+/// a render that was never stopped would have taken. `hash` is of the scene and camera settings it was rendered with.
+pub struct Checkpoint {
+    pub hash: u64,
+    pub samples_done: u32,
+    pub film: Film,
+}
+
+impl Checkpoint {
+    /// Saves `film`, with `samples_done` samples per pixel taken so far, to `path`. It is written next to it first
+    /// and then moved over it, so stopping the render mid-write leaves the last checkpoint whole.
+    pub fn write(path: &Path, hash: u64, samples_done: u32, film: &Film) -> Result<()> {
+        let temp_path = path.with_extension("tmp");
+        {
+            let mut out = BufWriter::new(File::create(&temp_path)?);
+            out.write_all(MAGIC)?;
+            write_u64(&mut out, hash)?;
+            write_u32(&mut out, samples_done)?;
+            film.write_state(&mut out)?;
+            out.flush()?;
+        }
+        std::fs::rename(&temp_path, path)?;
+        Ok(())
+    }
+
+    /// Loads the checkpoint at `path`, checking it was rendered with the scene and camera settings that hash to `hash`
+    /// (see `render_hash`).
+    pub fn read(path: &Path, hash: u64) -> Result<Checkpoint> {
+        let file = File::open(path)
+            .map_err(|e| Error::InvalidSettings(format!("could not open checkpoint '{}': {}", path.display(), e)))?;
+        let mut input = BufReader::new(file);
+        let invalid = |e: io::Error| Error::InvalidSettings(format!("'{}' is not a readable checkpoint: {}", path.display(), e));
+
+        let mut magic = [0; 8];
+        input.read_exact(&mut magic).map_err(invalid)?;
+        if &magic != MAGIC {return Err(Error::InvalidSettings(format!("'{}' is not a checkpoint", path.display())))}
+
+        let saved_hash = read_u64(&mut input).map_err(invalid)?;
+        if saved_hash != hash {
+            return Err(Error::InvalidSettings(format!(
+                "checkpoint '{}' was rendered with a different scene or camera settings; render with the same ones to resume it",
+                path.display())));
+        }
+        let samples_done = read_u32(&mut input).map_err(invalid)?;
+        let film = Film::read_state(&mut input).map_err(invalid)?;
+        Ok(Checkpoint {hash, samples_done, film})
+    }
+}
+
+/// The hash a checkpoint is saved with: of the scene's source (a scene file's text, or a built-in scene's name)
+/// together with `Camera::settings_hash`. The scene's own random placement follows from the seed, in the camera's.
+/// Only the scene file's own text is hashed, not the OBJ models, MTL libraries or images it loads, so a change
+/// to one of those between stopping a render and resuming it isn't noticed.
+pub fn render_hash(scene_source: &[u8], camera_hash: u64) -> u64 {
+    return hash_bytes(&[scene_source, &camera_hash.to_le_bytes()].concat());
+}
+
+/// The 64 bit FNV-1a hash of `bytes`, which (unlike the standard library's hashers) is the same from build to build.
+pub fn hash_bytes(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return hash;
+}
+
+// numbers are stored little endian, whatever machine wrote them
+
+pub(crate) fn write_u32(out: &mut impl Write, value: u32) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_u64(out: &mut impl Write, value: u64) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_f32(out: &mut impl Write, value: f32) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn write_f64(out: &mut impl Write, value: f64) -> io::Result<()> {
+    out.write_all(&value.to_le_bytes())
+}
+
+pub(crate) fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_f32(input: &mut impl Read) -> io::Result<f32> {
+    let mut bytes = [0; 4];
+    input.read_exact(&mut bytes)?;
+    Ok(f32::from_le_bytes(bytes))
+}
+
+pub(crate) fn read_f64(input: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    input.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::film::tests::sample_film;
+    use crate::filters::filter::filter_from_name;
+    use crate::integrators::integrator::integrator_from_name;
+    use crate::scenes;
+
+    // a file in the temp directory, removed when the test is done with it
+    struct TempFile(std::path::PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            TempFile(std::env::temp_dir().join(format!("rusty_raytracer_{}_{}.ckpt", name, std::process::id())))
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.0);
+        }
+    }
+
+    #[test]
+    fn checkpoint_round_trips() {
+        let file = TempFile::new("round_trip");
+        let film = sample_film();
+        Checkpoint::write(&file.0, 42, 12, &film).unwrap();
+        let checkpoint = Checkpoint::read(&file.0, 42).unwrap();
+
+        assert_eq!((checkpoint.hash, checkpoint.samples_done), (42, 12));
+        // every field, down to the bits of each float
+        assert_eq!(format!("{:?}", checkpoint.film), format!("{:?}", film));
+    }
+
+    #[test]
+    fn wrong_hash_is_rejected() {
+        let file = TempFile::new("wrong_hash");
+        Checkpoint::write(&file.0, 42, 12, &sample_film()).unwrap();
+        assert!(Checkpoint::read(&file.0, 43).is_err());
+    }
+
+    #[test]
+    fn truncated_file_is_rejected() {
+        let file = TempFile::new("truncated");
+        Checkpoint::write(&file.0, 42, 12, &sample_film()).unwrap();
+        let bytes = std::fs::read(&file.0).unwrap();
+        for length in [4, MAGIC.len() + 4, bytes.len() / 2, bytes.len() - 1] {
+            std::fs::write(&file.0, &bytes[..length]).unwrap();
+            assert!(Checkpoint::read(&file.0, 42).is_err(), "read {} of {} bytes", length, bytes.len());
+        }
+    }
+
+    #[test]
+    fn other_files_are_rejected() {
+        let file = TempFile::new("not_a_checkpoint");
+        std::fs::write(&file.0, b"P6\n1 1\n255\n\0\0\0").unwrap();
+        assert!(Checkpoint::read(&file.0, 42).is_err());
+    }
+
+    #[test]
+    fn hash_is_fnv_1a() {
+        // the published FNV-1a test vectors, so the hash can't change from build to build unnoticed
+        assert_eq!(hash_bytes(b""), 0xcbf29ce484222325);
+        assert_eq!(hash_bytes(b"a"), 0xaf63dc4c8601ec8c);
+    }
+
+    #[test]
+    fn resuming_with_more_samples_matches_a_straight_render() {
+        // splats from bdpt, and the filtered sums of a tent filter
+        for (integrator, filter) in [("path", "tent"), ("bdpt", "box")] {
+            let small_cornell_box = |samples_per_pixel| {
+                let mut scene = scenes::cornell_box().unwrap();
+                scene.camera.image_width = 12.0;
+                scene.camera.samples_per_pixel = samples_per_pixel;
+                scene.camera.integrator = integrator_from_name(integrator).unwrap();
+                scene.camera.filter = filter_from_name(filter, None).unwrap();
+                scene
+            };
+            let straight = small_cornell_box(8).render().unwrap();
+
+            let file = TempFile::new(&format!("resume_{}", integrator));
+            let half = small_cornell_box(4).render().unwrap();
+            Checkpoint::write(&file.0, 1, 4, &half).unwrap();
+            let resumed = small_cornell_box(8).resume(Checkpoint::read(&file.0, 1).unwrap(), |_, _| Ok(())).unwrap();
+
+            assert_eq!(resumed.total_samples(), straight.total_samples());
+            let pixels = |film: &Film| film.resolve().iter().map(|colour| colour.0).collect::<Vec<_>>();
+            assert_eq!(pixels(&resumed), pixels(&straight), "{} render resumed from 4 to 8 samples per pixel differs from 8 straight", integrator);
+        }
+    }
+}
@@ -32,6 +32,17 @@ impl Colour {
     pub fn b(&self) -> f32 {
         self.0.z
     }
+
+    /// How bright the colour looks (Rec. 709 weights for linear RGB).
+    pub fn luminance(&self) -> f32 {
+        0.2126*self.0.x + 0.7152*self.0.y + 0.0722*self.0.z
+    }
+}
+
+impl Default for Colour {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 pub fn write_colour(mut file: &File, pixel_colour: Colour) -> Result<(), Box<dyn std::error::Error>> {
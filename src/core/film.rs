@@ -0,0 +1,474 @@
+// the film (framebuffer) a render accumulates into
+// it stores the linear radiance of every sample, so the image can be tonemapped, encoded or compared afterwards
+
+use std::io::{self, Read, Write};
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+
+use crate::core::checkpoint::{read_f32, read_f64, read_u32, read_u64, write_f32, write_f64, write_u32, write_u64};
+use crate::core::colour::Colour;
+
+/// The running sum of the samples taken for one pixel, and of their luminance and its square for the variance.
+#[derive(Clone, Debug)]
+pub struct Pixel {
+    pub sum: Colour,
+    pub samples: u32,
+    luminance_sum: f64,
+    luminance_sum_squares: f64,
+}
+
+impl Pixel {
+    pub fn new() -> Self {
+        Self {
+            sum: Colour::new(),
+            samples: 0,
+            luminance_sum: 0.0,
+            luminance_sum_squares: 0.0,
+        }
+    }
+
+    pub fn add_sample(&mut self, colour: &Colour) {
+        self.sum.0 += colour.0;
+        self.samples += 1;
+        let luminance = colour.luminance() as f64;
+        self.luminance_sum += luminance;
+        self.luminance_sum_squares += luminance * luminance;
+    }
+
+    /// The standard error of the pixel's mean luminance over the mean itself: roughly how far off the pixel still is,
+    /// as a fraction of its brightness. Infinite until there are two samples to estimate the variance from,
+    /// and 0 for a pixel whose samples have all been the same (like one that only sees the background).
+    pub fn relative_error(&self) -> f32 {
+        if self.samples < 2 {return f32::INFINITY}
+        let n = self.samples as f64;
+        let mean = self.luminance_sum / n;
+        let variance = ((self.luminance_sum_squares / n - mean * mean) * n / (n - 1.0)).max(0.0);
+        let standard_error = (variance / n).sqrt();
+        if standard_error == 0.0 {return 0.0}
+        return (standard_error / mean.abs().max(f64::MIN_POSITIVE)) as f32;
+    }
+
+    /// The average of the samples so far (black if there are none).
+    pub fn mean(&self) -> Colour {
+        if self.samples == 0 {return Colour::new()}
+        Colour(self.sum.0 / self.samples as f32)
+    }
+}
+
+impl Default for Pixel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A rectangle of pixels, rendered as one piece of work: `width` by `height` pixels from (`x`, `y`).
+#[derive(Clone, Debug)]
+pub struct Tile {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct Film {
+    width: usize,
+    height: usize,
+    pixels: Vec<Pixel>,
+    // light tracing contributions, which land on whichever pixel a light path is seen from,
+    // and how many light paths they came from. Kept in the fixed point of `SplatBuffer`, so the sums don't
+    // depend on how the render was split into passes
+    splats: Vec<[u64; 3]>,
+    light_paths: u64,
+    // the sums of the filter weighted samples around each pixel and of their weights, in the fixed point of `FilterBuffer`,
+    // when the samples went through a reconstruction filter; otherwise each pixel is the plain mean of its own samples
+    filtered: Option<Vec<[i64; 4]>>,
+    // what the resolved pixels are multiplied by (the camera's exposure); set by the camera, so not saved in checkpoints
+    exposure: f32,
+}
+
+impl Film {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![Pixel::new(); width*height],
+            splats: vec![[0; 3]; width*height],
+            light_paths: 0,
+            filtered: None,
+            exposure: 1.0,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Pixel (i, j), counting from the top left.
+    pub fn pixel(&self, i: usize, j: usize) -> &Pixel {
+        &self.pixels[j*self.width + i]
+    }
+
+    pub fn add_sample(&mut self, i: usize, j: usize, colour: &Colour) {
+        self.pixels[j*self.width + i].add_sample(colour);
+    }
+
+    /// The number of samples taken over the whole film.
+    pub fn total_samples(&self) -> u64 {
+        self.pixels.iter().map(|pixel| pixel.samples as u64).sum()
+    }
+
+    /// A picture of how many samples each pixel took, from blue for the fewest to red for the most,
+    /// as a film with one sample per pixel so it can be written like any render.
+    pub fn sample_heatmap(&self) -> Film {
+        let mut heatmap = Film::new(self.width, self.height);
+        let (fewest, most) = self.pixels.iter()
+            .fold((u32::MAX, 0), |(fewest, most), pixel| (fewest.min(pixel.samples), most.max(pixel.samples)));
+
+        for (heat, pixel) in heatmap.pixels.iter_mut().zip(&self.pixels) {
+            let t = if most > fewest {(pixel.samples - fewest) as f32 / (most - fewest) as f32} else {0.0};
+            heat.add_sample(&Colour::new_from(t, 1.0 - (2.0*t - 1.0).abs(), 1.0 - t));
+        }
+        return heatmap;
+    }
+
+    /// Writes everything the film has accumulated, for a `Checkpoint`.
+    pub(crate) fn write_state(&self, out: &mut impl Write) -> io::Result<()> {
+        write_u64(out, self.width as u64)?;
+        write_u64(out, self.height as u64)?;
+        for pixel in &self.pixels {
+            write_colour(out, &pixel.sum)?;
+            write_u32(out, pixel.samples)?;
+            write_f64(out, pixel.luminance_sum)?;
+            write_f64(out, pixel.luminance_sum_squares)?;
+        }
+        write_u64(out, self.light_paths)?;
+        for splat in self.splats.iter().flatten() {
+            write_u64(out, *splat)?;
+        }
+        write_u32(out, self.filtered.is_some() as u32)?;
+        for sum in self.filtered.iter().flatten().flatten() {
+            write_u64(out, *sum as u64)?;
+        }
+        Ok(())
+    }
+
+    /// Reads back a film written by `write_state`.
+    pub(crate) fn read_state(input: &mut impl Read) -> io::Result<Film> {
+        let width = read_u64(input)? as usize;
+        let height = read_u64(input)? as usize;
+        // the vectors grow as they're read, so a damaged size runs out of file rather than memory
+        let count = width.checked_mul(height).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "film size overflows"))?;
+
+        let mut film = Film {width, height, pixels: Vec::new(), splats: Vec::new(), light_paths: 0, filtered: None, exposure: 1.0};
+        for _ in 0..count {
+            film.pixels.push(Pixel {
+                sum: read_colour(input)?,
+                samples: read_u32(input)?,
+                luminance_sum: read_f64(input)?,
+                luminance_sum_squares: read_f64(input)?,
+            });
+        }
+        film.light_paths = read_u64(input)?;
+        for _ in 0..count {
+            film.splats.push([read_u64(input)?, read_u64(input)?, read_u64(input)?]);
+        }
+        if read_u32(input)? != 0 {
+            let mut filtered = Vec::new();
+            for _ in 0..count {
+                filtered.push([read_u64(input)? as i64, read_u64(input)? as i64, read_u64(input)? as i64, read_u64(input)? as i64]);
+            }
+            film.filtered = Some(filtered);
+        }
+        Ok(film)
+    }
+
+    /// The film split into tiles of (up to) `size` by `size` pixels, row by row from the top left.
+    pub fn tiles(&self, size: usize) -> Vec<Tile> {
+        let mut tiles = Vec::new();
+        for y in (0..self.height).step_by(size) {
+            for x in (0..self.width).step_by(size) {
+                tiles.push(Tile {x, y, width: size.min(self.width - x), height: size.min(self.height - y)});
+            }
+        }
+        return tiles;
+    }
+
+    /// A copy of the pixels in `tile`, row by row, for rendering into away from the film.
+    pub fn tile_pixels(&self, tile: &Tile) -> Vec<Pixel> {
+        (tile.y..tile.y + tile.height)
+            .flat_map(|j| self.pixels[j*self.width + tile.x..j*self.width + tile.x + tile.width].iter().cloned())
+            .collect()
+    }
+
+    /// Puts the pixels of `tile` (as from `tile_pixels`) back into the film.
+    pub fn set_tile(&mut self, tile: &Tile, pixels: &[Pixel]) {
+        for (row, j) in pixels.chunks_exact(tile.width).zip(tile.y..tile.y + tile.height) {
+            self.pixels[j*self.width + tile.x..j*self.width + tile.x + tile.width].clone_from_slice(row);
+        }
+    }
+
+    /// Adds the splats from `light_paths` light paths. Each light path covers the whole image, so the splats
+    /// are scaled by the number of pixels over the number of paths when the film is resolved.
+    pub fn add_splats(&mut self, splats: &SplatBuffer, light_paths: u64) {
+        for (sum, splat) in self.splats.iter_mut().flatten().zip(&splats.sums) {
+            *sum += splat.load(Ordering::Relaxed);
+        }
+        self.light_paths += light_paths;
+    }
+
+    /// Sets what the pixels are multiplied by when the film is resolved.
+    pub fn set_exposure(&mut self, exposure: f32) {
+        self.exposure = exposure;
+    }
+
+    /// Adds the filtered samples from `filtered`, which the pixels are then resolved from.
+    pub fn add_filtered(&mut self, filtered: &FilterBuffer) {
+        let sums = self.filtered.get_or_insert_with(|| vec![[0; 4]; self.width*self.height]);
+        for (sum, value) in sums.iter_mut().flatten().zip(&filtered.sums) {
+            *sum += value.load(Ordering::Relaxed);
+        }
+    }
+
+    /// The average linear colour of every pixel, row by row from the top left: the filter weighted average of the
+    /// samples around it if they were filtered, otherwise the mean of its own samples, scaled by the exposure.
+    pub fn resolve(&self) -> Vec<Colour> {
+        let mut colours: Vec<Colour> = match &self.filtered {
+            Some(filtered) => self.pixels.iter().zip(filtered).map(|(pixel, sum)| {
+                let (colour, weight) = filter_sum_value(sum);
+                // negative lobes can cancel out the weights of a pixel with few samples nearby
+                if weight > 0.0 {Colour(colour.0 / weight)} else {pixel.mean()}
+            }).collect(),
+            None => self.pixels.iter().map(|pixel| pixel.mean()).collect(),
+        };
+
+        if self.light_paths > 0 {
+            let splat_scale = (self.width*self.height) as f32 / self.light_paths as f32;
+            for (colour, splat) in colours.iter_mut().zip(&self.splats) {
+                colour.0 += splat_value(splat).0*splat_scale;
+            }
+        }
+        if self.exposure != 1.0 {
+            for colour in colours.iter_mut() {
+                colour.0 *= self.exposure;
+            }
+        }
+        return colours;
+    }
+}
+
+fn write_colour(out: &mut impl Write, colour: &Colour) -> io::Result<()> {
+    colour.0.iter().try_for_each(|c| write_f32(out, *c))
+}
+
+fn read_colour(input: &mut impl Read) -> io::Result<Colour> {
+    Ok(Colour::new_from(read_f32(input)?, read_f32(input)?, read_f32(input)?))
+}
+
+// fixed point scale for splats: about 6e-8 resolution, and room for sums up to about 1e12
+const SPLAT_SCALE: f64 = (1u64 << 24) as f64;
+
+/// Splats that can be added to any pixel from any thread while the film's rows are being filled in.
+/// Sums are kept in fixed point, so they come out the same whatever order the splats arrive in,
+/// which keeps renders reproducible however the work is split across threads.
+pub struct SplatBuffer {
+    width: usize,
+    height: usize,
+    sums: Vec<AtomicU64>,
+}
+
+impl SplatBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            sums: (0..3*width*height).map(|_| AtomicU64::new(0)).collect(),
+        }
+    }
+
+    /// Adds `colour` to pixel (i, j). Negative and non-finite channels are dropped.
+    pub fn add(&self, i: usize, j: usize, colour: &Colour) {
+        if i >= self.width || j >= self.height {return}
+        let index = 3*(j*self.width + i);
+        for c in 0..3 {
+            let value = colour.0[c];
+            if !value.is_finite() || value <= 0.0 {continue}
+            // capped so a single wild value can't overflow the sum
+            let fixed = (value.min(1e9) as f64 * SPLAT_SCALE).round() as u64;
+            self.sums[index + c].fetch_add(fixed, Ordering::Relaxed);
+        }
+    }
+
+    /// The splatted sum for every pixel, row by row from the top left.
+    pub fn colours(&self) -> Vec<Colour> {
+        self.sums.chunks_exact(3).map(|sum| splat_value(&[0, 1, 2].map(|c| sum[c].load(Ordering::Relaxed)))).collect()
+    }
+}
+
+fn splat_value(sum: &[u64; 3]) -> Colour {
+    let channel = |c: usize| (sum[c] as f64 / SPLAT_SCALE) as f32;
+    Colour::new_from(channel(0), channel(1), channel(2))
+}
+
+// fixed point scale for filtered samples, which can be negative: about 4e-9 resolution, and room for sums up to about 3e10
+const FILTER_SCALE: f64 = (1u64 << 28) as f64;
+
+/// Filter weighted samples that can be added to any pixel from any thread while the film's rows are being filled in,
+/// kept in fixed point like `SplatBuffer` so the sums come out the same whatever order the samples arrive in.
+pub struct FilterBuffer {
+    width: usize,
+    height: usize,
+    // the weighted red, green and blue and the weight, for each pixel
+    sums: Vec<AtomicI64>,
+}
+
+impl FilterBuffer {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            sums: (0..4*width*height).map(|_| AtomicI64::new(0)).collect(),
+        }
+    }
+
+    /// Adds `colour` with filter weight `weight` to pixel (i, j). Samples with non-finite channels are dropped.
+    pub fn add(&self, i: usize, j: usize, colour: &Colour, weight: f32) {
+        if i >= self.width || j >= self.height || weight == 0.0 {return}
+        if !colour.0.iter().all(|c| c.is_finite()) {return}
+
+        let index = 4*(j*self.width + i);
+        let values = [colour.0.x * weight, colour.0.y * weight, colour.0.z * weight, weight];
+        for (c, value) in values.iter().enumerate() {
+            // capped so a single wild value can't overflow the sum
+            let fixed = (value.clamp(-1e7, 1e7) as f64 * FILTER_SCALE).round() as i64;
+            self.sums[index + c].fetch_add(fixed, Ordering::Relaxed);
+        }
+    }
+
+    /// The weighted colour and weight summed for every pixel, row by row from the top left.
+    pub fn sums(&self) -> Vec<(Colour, f32)> {
+        self.sums.chunks_exact(4).map(|sum| filter_sum_value(&[0, 1, 2, 3].map(|c| sum[c].load(Ordering::Relaxed)))).collect()
+    }
+}
+
+fn filter_sum_value(sum: &[i64; 4]) -> (Colour, f32) {
+    let channel = |c: usize| (sum[c] as f64 / FILTER_SCALE) as f32;
+    (Colour::new_from(channel(0), channel(1), channel(2)), channel(3))
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use nalgebra::Vector3;
+
+    // a 3x2 film with samples, splats and filtered sums in it
+    pub(crate) fn sample_film() -> Film {
+        let mut film = Film::new(3, 2);
+        let splats = SplatBuffer::new(3, 2);
+        let filtered = FilterBuffer::new(3, 2);
+        for j in 0..2 {
+            for i in 0..3 {
+                for s in 0..=(i + j) {
+                    let colour = Colour::new_from(0.1 * (i + 1) as f32, 0.25 * s as f32, 1.5 + j as f32);
+                    film.add_sample(i, j, &colour);
+                    filtered.add(i, j, &colour, 0.5 + 0.125 * s as f32);
+                }
+                splats.add(i, j, &Colour::new_from(0.5, i as f32, j as f32));
+            }
+        }
+        film.add_splats(&splats, 7);
+        film.add_filtered(&filtered);
+        return film;
+    }
+
+    #[test]
+    fn state_round_trips() {
+        let film = sample_film();
+        let mut bytes = Vec::new();
+        film.write_state(&mut bytes).unwrap();
+        let read = Film::read_state(&mut bytes.as_slice()).unwrap();
+
+        assert_eq!((read.width, read.height, read.light_paths), (film.width, film.height, film.light_paths));
+        for (read, pixel) in read.pixels.iter().zip(&film.pixels) {
+            assert_eq!(read.sum.0, pixel.sum.0);
+            assert_eq!(read.samples, pixel.samples);
+            assert_eq!(read.luminance_sum, pixel.luminance_sum);
+            assert_eq!(read.luminance_sum_squares, pixel.luminance_sum_squares);
+        }
+        assert_eq!(read.pixels.len(), film.pixels.len());
+        assert_eq!(read.splats, film.splats);
+        assert_eq!(read.filtered, film.filtered);
+    }
+
+    #[test]
+    fn state_without_filtering_round_trips() {
+        let mut film = Film::new(2, 1);
+        film.add_sample(1, 0, &Colour::new_from(1.0, 2.0, 3.0));
+        let mut bytes = Vec::new();
+        film.write_state(&mut bytes).unwrap();
+        let read = Film::read_state(&mut bytes.as_slice()).unwrap();
+
+        assert!(read.filtered.is_none());
+        assert_eq!(read.pixel(1, 0).sum.0, film.pixel(1, 0).sum.0);
+        assert_eq!(read.pixel(1, 0).samples, 1);
+    }
+
+    #[test]
+    fn truncated_state_is_an_error() {
+        let mut bytes = Vec::new();
+        sample_film().write_state(&mut bytes).unwrap();
+        for length in [0, 8, bytes.len() / 2, bytes.len() - 1] {
+            assert!(Film::read_state(&mut &bytes[..length]).is_err(), "read {} of {} bytes", length, bytes.len());
+        }
+    }
+
+    fn pixel_of(greys: &[f32]) -> Pixel {
+        let mut pixel = Pixel::new();
+        for grey in greys {
+            pixel.add_sample(&Colour::new_from(*grey, *grey, *grey));
+        }
+        return pixel;
+    }
+
+    #[test]
+    fn relative_error_is_the_standard_error_over_the_mean() {
+        assert_eq!(pixel_of(&[]).relative_error(), f32::INFINITY);
+        assert_eq!(pixel_of(&[0.5]).relative_error(), f32::INFINITY);
+        assert_eq!(pixel_of(&[0.5; 8]).relative_error(), 0.0);
+        assert_eq!(pixel_of(&[0.0; 8]).relative_error(), 0.0);
+
+        // a mean of 2 and a sample variance of 2, so a standard error of 1
+        assert!((pixel_of(&[1.0, 3.0]).relative_error() - 0.5).abs() < 1e-6);
+        // the error of the mean falls as the square root of the number of samples
+        assert!((pixel_of(&[1.0, 3.0, 1.0, 3.0, 1.0, 3.0, 1.0, 3.0]).relative_error() - (4.0f32 / 7.0).sqrt() / 4.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn heatmap_runs_from_blue_to_red() {
+        let mut film = Film::new(3, 1);
+        for (i, samples) in [2, 4, 6].iter().enumerate() {
+            for _ in 0..*samples {film.add_sample(i, 0, &Colour::new_from(1.0, 1.0, 1.0))}
+        }
+        let heat: Vec<_> = film.sample_heatmap().resolve().iter().map(|colour| colour.0).collect();
+        assert_eq!(heat, [Vector3::new(0.0, 0.0, 1.0), Vector3::new(0.5, 1.0, 0.5), Vector3::new(1.0, 0.0, 0.0)]);
+    }
+
+    #[test]
+    fn heatmap_of_evenly_sampled_film_is_all_blue() {
+        // the fewest and the most samples are the same, which mustn't divide by zero
+        let mut film = Film::new(3, 2);
+        for j in 0..2 {
+            for i in 0..3 {
+                for _ in 0..5 {film.add_sample(i, j, &Colour::new_from(0.5, 0.5, 0.5))}
+            }
+        }
+        for film in [film, Film::new(2, 2)] {
+            for colour in film.sample_heatmap().resolve() {
+                assert_eq!(colour.0, Vector3::new(0.0, 0.0, 1.0));
+            }
+        }
+    }
+}
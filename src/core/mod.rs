@@ -1,3 +1,9 @@
 pub mod ray;
 pub mod camera;
-pub mod colour;
\ No newline at end of file
+pub mod checkpoint;
+pub mod colour;
+pub mod film;
+pub mod physical_camera;
+pub mod projection;
+pub mod shutter;
+pub mod stereo;
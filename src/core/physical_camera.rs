@@ -0,0 +1,163 @@
+// physical cameras: a real camera's lens, sensor and exposure settings, which the field of view, depth of field
+// and brightness of the image are worked out from
+
+use crate::core::projection::Projection;
+
+// the light a lens lets through to the sensor relative to an ideal one (lens transmittance and vignetting, ISO 12232's q)
+const LENS_TRANSMISSION: f32 = 0.65;
+// the exposure, in lux seconds, that saturates a sensor at ISO 1 (ISO 12232's saturation based speed)
+const SATURATION_EXPOSURE: f32 = 78.0;
+
+/// A camera described by its lens, sensor and exposure settings rather than by angles, so a scene can be lit in
+/// physical units: with emission and the background in candelas per square metre, a pixel value of 1 is the
+/// brightness that just saturates the sensor. The same lights then work from shot to shot, with the exposure
+/// settings doing the work of brightening or darkening the image as they would with a real camera.
+///
+/// The field of view follows from the focal length and the sensor (the lens is focused at `Camera::focus_dist`,
+/// so it narrows a little as the focus comes closer), the depth of field from the aperture the f-number gives,
+/// and the exposure from the f-number, shutter speed and ISO. The shutter speed only sets the exposure; motion blur
+/// still happens over `Camera::shutter_open` to `Camera::shutter_close`, in scene time.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PhysicalCamera {
+    /// The lens's focal length, in millimetres.
+    pub focal_length: f32,
+    /// The focal length over the diameter of the aperture; larger numbers let less light in and keep more in focus.
+    pub f_number: f32,
+    /// How long the shutter is open, in seconds.
+    pub shutter_speed: f32,
+    /// The sensor's sensitivity; doubling it brightens the image as much as doubling the shutter speed.
+    pub iso: f32,
+    /// The sensor's width, in millimetres (36 for full frame). Its height is the width over the image's aspect ratio.
+    pub sensor_width: f32,
+    /// How many metres a unit of the scene is, to size the aperture in the scene.
+    pub metres_per_unit: f32,
+}
+
+impl PhysicalCamera {
+    /// A full frame camera with a 50mm lens at f/8, 1/125s and ISO 100, in a scene measured in metres.
+    pub fn new() -> Self {
+        Self {
+            focal_length: 50.0,
+            f_number: 8.0,
+            shutter_speed: 1.0 / 125.0,
+            iso: 100.0,
+            sensor_width: 36.0,
+            metres_per_unit: 1.0,
+        }
+    }
+
+    /// How far behind the lens the sensor is, in millimetres, to focus at `focus_dist` scene units (the thin lens equation).
+    /// None if that is closer than the focal length, which the lens can't focus on.
+    pub fn image_distance(&self, focus_dist: f32) -> Option<f32> {
+        let focus_mm = focus_dist * self.metres_per_unit * 1000.0;
+        if focus_mm <= self.focal_length {return None}
+        return Some(self.focal_length * focus_mm / (focus_mm - self.focal_length));
+    }
+
+    /// The field of view across the image height, in radians, for `projection`, or None if the lens can't focus
+    /// at `focus_dist` or (for the equisolid fisheye) the sensor is too tall for the lens's image circle. Fisheye
+    /// lenses map angles to distances on the sensor with their focal length (r = f θ, or r = 2 f sin(θ / 2));
+    /// the orthographic and equirectangular projections don't have a field of view and give None.
+    pub fn vfov(&self, projection: Projection, aspect_ratio: f32, focus_dist: f32) -> Option<f32> {
+        let sensor_height = self.sensor_width / aspect_ratio;
+        let image_distance = self.image_distance(focus_dist)?;
+        match projection {
+            Projection::Perspective => Some(2.0 * (sensor_height / (2.0 * image_distance)).atan()),
+            Projection::FisheyeEquidistant => Some(sensor_height / image_distance),
+            Projection::FisheyeEquisolid => {
+                let sin_quarter = sensor_height / (4.0 * image_distance);
+                if sin_quarter > 1.0 {return None}
+                Some(4.0 * sin_quarter.asin())
+            }
+            Projection::Orthographic | Projection::Equirectangular => None,
+        }
+    }
+
+    /// The radius of the aperture, in scene units.
+    pub fn lens_radius(&self) -> f32 {
+        return self.focal_length / (2.0 * self.f_number) / 1000.0 / self.metres_per_unit;
+    }
+
+    /// What the rendered radiance is multiplied by: the exposure a pixel gets relative to the one that saturates
+    /// the sensor at this ISO, t S q / (78 N^2).
+    pub fn exposure(&self) -> f32 {
+        return self.shutter_speed * self.iso * LENS_TRANSMISSION / (SATURATION_EXPOSURE * self.f_number * self.f_number);
+    }
+}
+
+impl Default for PhysicalCamera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // far enough that the lens is focused at infinity, for all practical purposes
+    const FAR: f32 = 1e7;
+
+    #[test]
+    fn image_distance_follows_the_thin_lens_equation() {
+        let camera = PhysicalCamera::new();
+        // 1/50 = 1/1000 + 1/d
+        assert!((camera.image_distance(1.0).unwrap() - 50.0 * 1000.0 / 950.0).abs() < 1e-3);
+        assert!((camera.image_distance(FAR).unwrap() - 50.0).abs() < 1e-3);
+
+        // a scene measured in centimetres
+        let centimetres = PhysicalCamera {metres_per_unit: 0.01, ..camera};
+        assert!((centimetres.image_distance(100.0).unwrap() - camera.image_distance(1.0).unwrap()).abs() < 1e-3);
+    }
+
+    #[test]
+    fn no_image_distance_closer_than_the_focal_length() {
+        let camera = PhysicalCamera::new();
+        assert_eq!(camera.image_distance(0.05), None);
+        assert_eq!(camera.image_distance(0.02), None);
+        assert_eq!(camera.vfov(Projection::Perspective, 1.5, 0.02), None);
+    }
+
+    #[test]
+    fn vfov_of_each_projection() {
+        // a 50mm lens on a 36x24mm sensor
+        let camera = PhysicalCamera::new();
+        let vfov = |projection| camera.vfov(projection, 1.5, FAR);
+        assert!((vfov(Projection::Perspective).unwrap().to_degrees() - 26.99).abs() < 0.01);
+        assert!((vfov(Projection::FisheyeEquidistant).unwrap() - 24.0 / 50.0).abs() < 1e-5);
+        assert!((vfov(Projection::FisheyeEquisolid).unwrap() - 4.0 * (24.0f32 / 200.0).asin()).abs() < 1e-5);
+        assert_eq!(vfov(Projection::Orthographic), None);
+        assert_eq!(vfov(Projection::Equirectangular), None);
+
+        // focusing closer moves the sensor back, which narrows the view
+        assert!(camera.vfov(Projection::Perspective, 1.5, 0.5).unwrap() < vfov(Projection::Perspective).unwrap());
+
+        // an equisolid lens this short can't cover a sensor this tall
+        let short = PhysicalCamera {focal_length: 5.0, ..camera};
+        assert_eq!(short.vfov(Projection::FisheyeEquisolid, 1.5, FAR), None);
+    }
+
+    #[test]
+    fn exposure_follows_the_exposure_settings() {
+        let camera = PhysicalCamera::new();
+        let expected = (1.0 / 125.0) * 100.0 * 0.65 / (78.0 * 8.0 * 8.0);
+        assert!((camera.exposure() - expected).abs() < 1e-9);
+
+        // doubling the time or the ISO, or opening up a stop, each doubles the exposure
+        let doubled = [
+            PhysicalCamera {shutter_speed: 2.0 / 125.0, ..camera},
+            PhysicalCamera {iso: 200.0, ..camera},
+            PhysicalCamera {f_number: 8.0 / 2.0f32.sqrt(), ..camera},
+        ];
+        for other in doubled {
+            assert!((other.exposure() / camera.exposure() - 2.0).abs() < 1e-5, "{:?}", other);
+        }
+    }
+
+    #[test]
+    fn lens_radius_is_half_the_focal_length_over_the_f_number() {
+        let camera = PhysicalCamera::new();
+        assert!((camera.lens_radius() - 0.003125).abs() < 1e-7);
+        assert!((PhysicalCamera {metres_per_unit: 0.01, ..camera}.lens_radius() - 0.3125).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,171 @@
+// projections: how the camera maps points on the image to the directions it looks in
+
+use std::f32::consts::PI;
+
+use nalgebra::Vector3;
+
+use crate::error::{Error, Result};
+
+/// The camera's projection. The perspective and orthographic projections look through a flat image plane;
+/// the fisheyes and the equirectangular panorama map the image onto angles instead, and can see behind the camera.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Projection {
+    /// A pinhole (or thin lens) camera, with a field of view of `Camera::vfov` across the image height.
+    Perspective,
+    /// Parallel rays, from a rectangle `Camera::ortho_height` tall facing `lookat`, so sizes don't shrink with distance.
+    Orthographic,
+    /// A fisheye where the distance from the image center is proportional to the angle off the view direction,
+    /// with `Camera::vfov` (up to 360 degrees) across the image height.
+    FisheyeEquidistant,
+    /// A fisheye that keeps areas: equal solid angles cover equal areas of the image. `Camera::vfov` is as for the
+    /// equidistant fisheye.
+    FisheyeEquisolid,
+    /// Every direction: 360 degrees of longitude across the image width and 180 of latitude down its height,
+    /// with `lookat` in the center. Use an aspect ratio of 2 for square pixels.
+    Equirectangular,
+}
+
+impl Projection {
+    /// The name used on the command line and in scene files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Projection::Perspective => "perspective",
+            Projection::Orthographic => "orthographic",
+            Projection::FisheyeEquidistant => "equidistant",
+            Projection::FisheyeEquisolid => "equisolid",
+            Projection::Equirectangular => "equirectangular",
+        }
+    }
+
+    /// Whether the projection maps the image onto angles around the camera rather than through an image plane.
+    pub fn is_angular(&self) -> bool {
+        !matches!(self, Projection::Perspective | Projection::Orthographic)
+    }
+
+    /// For the angular projections: the direction the camera looks in through the point `x` of the image width right
+    /// of the image center and `y` of its height up from it (so both run from -0.5 to 0.5), in camera space
+    /// (x right, y up, looking down -z), or None if the point is outside a fisheye's image circle. `aspect_ratio` is the
+    /// image's width over its height, and `fov` the fisheyes' field of view across the height, in radians.
+    /// The flat projections give None; the camera works those out from its image plane.
+    pub fn direction(&self, x: f32, y: f32, aspect_ratio: f32, fov: f32) -> Option<Vector3<f32>> {
+        // for the fisheyes, in image heights, so the image circle is round whatever the aspect ratio
+        let (x, y) = match self {
+            Projection::Perspective | Projection::Orthographic => return None,
+            Projection::Equirectangular => {
+                let (longitude, latitude) = (x * 2.0 * PI, y * PI);
+                return Some(Vector3::new(latitude.cos() * longitude.sin(), latitude.sin(), -latitude.cos() * longitude.cos()));
+            }
+            _ => (x * aspect_ratio, y),
+        };
+        let r = (x * x + y * y).sqrt();
+
+        // the center to the top of the image (half a height) is half of the field of view
+        let theta = match self {
+            Projection::FisheyeEquisolid => {
+                let sin_half_theta = 2.0 * r * (fov / 4.0).sin();
+                if sin_half_theta > 1.0 {return None}
+                2.0 * sin_half_theta.asin()
+            }
+            _ => r * fov,
+        };
+        if theta > PI {return None}
+
+        if r == 0.0 {return Some(Vector3::new(0.0, 0.0, -1.0))}
+        return Some(Vector3::new(theta.sin() * x / r, theta.sin() * y / r, -theta.cos()));
+    }
+}
+
+/// Looks a projection up by the name used on the command line and in scene files.
+pub fn projection_from_name(name: &str) -> Result<Projection> {
+    match name {
+        "perspective" => Ok(Projection::Perspective),
+        "orthographic" => Ok(Projection::Orthographic),
+        "equidistant" => Ok(Projection::FisheyeEquidistant),
+        "equisolid" => Ok(Projection::FisheyeEquisolid),
+        "equirectangular" => Ok(Projection::Equirectangular),
+        _ => Err(Error::InvalidSettings(format!(
+            "unknown projection '{}' (expected perspective, orthographic, equidistant, equisolid or equirectangular)", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ANGULAR: [Projection; 3] = [Projection::FisheyeEquidistant, Projection::FisheyeEquisolid, Projection::Equirectangular];
+
+    fn assert_direction(direction: Option<Vector3<f32>>, expected: Vector3<f32>) {
+        let direction = direction.expect("no direction");
+        assert!((direction - expected).norm() < 1e-5, "{:?}, expected {:?}", direction, expected);
+    }
+
+    #[test]
+    fn image_center_looks_down_minus_z() {
+        for projection in ANGULAR {
+            for (aspect_ratio, fov) in [(1.0, PI / 2.0), (2.0, PI), (16.0 / 9.0, 1.5 * PI)] {
+                assert_direction(projection.direction(0.0, 0.0, aspect_ratio, fov), Vector3::new(0.0, 0.0, -1.0));
+            }
+        }
+    }
+
+    #[test]
+    fn flat_projections_have_no_direction() {
+        for projection in [Projection::Perspective, Projection::Orthographic] {
+            assert!(!projection.is_angular());
+            assert_eq!(projection.direction(0.0, 0.0, 1.0, PI / 2.0), None);
+        }
+    }
+
+    #[test]
+    fn equirectangular_edges_look_straight_back() {
+        for x in [-0.5, 0.5] {
+            assert_direction(Projection::Equirectangular.direction(x, 0.0, 2.0, PI), Vector3::new(0.0, 0.0, 1.0));
+        }
+        // a quarter of the way across is 90 degrees round, and the top and bottom are the poles
+        assert_direction(Projection::Equirectangular.direction(0.25, 0.0, 2.0, PI), Vector3::new(1.0, 0.0, 0.0));
+        assert_direction(Projection::Equirectangular.direction(0.1, 0.5, 2.0, PI), Vector3::new(0.0, 1.0, 0.0));
+        assert_direction(Projection::Equirectangular.direction(-0.3, -0.5, 2.0, PI), Vector3::new(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn fisheye_top_of_image_is_half_the_field_of_view() {
+        let fov = 2.0 * PI / 3.0;
+        for projection in [Projection::FisheyeEquidistant, Projection::FisheyeEquisolid] {
+            let half = fov / 2.0;
+            assert_direction(projection.direction(0.0, 0.5, 1.5, fov), Vector3::new(0.0, half.sin(), -half.cos()));
+            // and the same distance across, whatever the aspect ratio
+            assert_direction(projection.direction(0.5 / 1.5, 0.0, 1.5, fov), Vector3::new(half.sin(), 0.0, -half.cos()));
+        }
+    }
+
+    #[test]
+    fn fisheye_has_no_direction_outside_its_image_circle() {
+        // a 360 degree equidistant fisheye sees straight back at half an image height from the center, and nothing past it
+        let full = 2.0 * PI;
+        assert_direction(Projection::FisheyeEquidistant.direction(0.0, 0.5, 1.0, full), Vector3::new(0.0, 0.0, 1.0));
+        assert_eq!(Projection::FisheyeEquidistant.direction(0.0, 0.51, 1.0, full), None);
+        assert_eq!(Projection::FisheyeEquidistant.direction(0.4, 0.4, 1.0, full), None);
+        // a 180 degree one runs out of angles (theta > PI) a whole image height out, in the corners of a wide image
+        assert!(Projection::FisheyeEquidistant.direction(0.45, 0.0, 2.0, PI).is_some());
+        assert_eq!(Projection::FisheyeEquidistant.direction(0.5, 0.5, 2.0, PI), None);
+
+        // the equisolid fisheye's image circle is where sin(theta / 2) reaches 1, about 0.71 image heights out at 180 degrees
+        assert!(Projection::FisheyeEquisolid.direction(0.0, 0.7, 1.0, PI).is_some());
+        assert_eq!(Projection::FisheyeEquisolid.direction(0.0, 0.72, 1.0, PI), None);
+        assert_eq!(Projection::FisheyeEquisolid.direction(0.5, 0.6, 1.0, PI), None);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for projection in [Projection::Perspective, Projection::Orthographic, Projection::FisheyeEquidistant, Projection::FisheyeEquisolid, Projection::Equirectangular] {
+            assert_eq!(projection_from_name(projection.name()).unwrap(), projection);
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_invalid_settings() {
+        for name in ["", "fisheye", "Perspective"] {
+            assert!(matches!(projection_from_name(name), Err(Error::InvalidSettings(_))), "'{}' was accepted", name);
+        }
+    }
+}
@@ -44,3 +44,9 @@ impl Ray {
 
 }
 
+impl Default for Ray {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
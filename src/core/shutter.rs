@@ -0,0 +1,83 @@
+// shutters: when, over the time the shutter is open, the camera's samples are taken
+
+use crate::error::{Error, Result};
+
+/// How open the shutter is over its interval, which weights the times the samples are taken at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ShutterCurve {
+    /// Fully open from the moment it opens until it closes, so motion blurs evenly (the books' shutter).
+    Box,
+    /// Opening steadily to fully open halfway and closing the same way, so motion blur fades out at its ends.
+    Triangle,
+}
+
+impl ShutterCurve {
+    /// The name used on the command line and in scene files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            ShutterCurve::Box => "box",
+            ShutterCurve::Triangle => "triangle",
+        }
+    }
+
+    /// How far through the shutter interval (from 0 to 1) a sample is taken, from a uniform number `u` in [0, 1).
+    pub fn sample(&self, u: f32) -> f32 {
+        match self {
+            ShutterCurve::Box => u,
+            // inverting the tent's cumulative distribution, one half at a time
+            ShutterCurve::Triangle => if u < 0.5 {(2.0 * u).sqrt() / 2.0} else {1.0 - (2.0 * (1.0 - u)).sqrt() / 2.0},
+        }
+    }
+}
+
+/// Looks a shutter curve up by the name used on the command line and in scene files.
+pub fn shutter_curve_from_name(name: &str) -> Result<ShutterCurve> {
+    match name {
+        "box" => Ok(ShutterCurve::Box),
+        "triangle" => Ok(ShutterCurve::Triangle),
+        _ => Err(Error::InvalidSettings(format!("unknown shutter curve '{}' (expected box or triangle)", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STEPS: u32 = 10000;
+
+    fn us() -> impl Iterator<Item = f32> {
+        (0..STEPS).map(|step| step as f32 / STEPS as f32)
+    }
+
+    #[test]
+    fn triangle_stays_in_the_shutter_interval() {
+        let mut last = 0.0;
+        for u in us() {
+            let t = ShutterCurve::Triangle.sample(u);
+            assert!((0.0..=1.0).contains(&t), "sample({}) = {}", u, t);
+            assert!(t >= last, "sample({}) = {} is before {}", u, t, last);
+            last = t;
+        }
+        assert_eq!(ShutterCurve::Triangle.sample(0.0), 0.0);
+        assert!(ShutterCurve::Triangle.sample(1.0 - f32::EPSILON) <= 1.0);
+    }
+
+    #[test]
+    fn triangle_is_symmetric() {
+        for u in us().skip(1) {
+            let (t, mirrored) = (ShutterCurve::Triangle.sample(u), ShutterCurve::Triangle.sample(1.0 - u));
+            assert!((t + mirrored - 1.0).abs() < 1e-3, "sample({}) = {} but sample({}) = {}", u, t, 1.0 - u, mirrored);
+        }
+        assert!((ShutterCurve::Triangle.sample(0.5) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn triangle_weights_the_middle_of_the_interval() {
+        // the middle half of a tent holds three quarters of it
+        let middle = us().filter(|&u| (0.25..0.75).contains(&ShutterCurve::Triangle.sample(u))).count();
+        assert!((middle as f32 / STEPS as f32 - 0.75).abs() < 1e-3);
+
+        let middle = us().filter(|&u| (0.25..0.75).contains(&ShutterCurve::Box.sample(u))).count();
+        assert!((middle as f32 / STEPS as f32 - 0.5).abs() < 1e-3);
+    }
+}
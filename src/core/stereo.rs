@@ -0,0 +1,138 @@
+// stereo rigs: two eyes a little apart, rendered together into one film, for images to be seen in 3D
+
+use crate::core::film::Tile;
+use crate::error::{Error, Result};
+
+/// A pair of eyes `interocular` apart, either side of the camera, each rendering its own view of the scene.
+/// Both views are rendered in the same passes into one film twice the size of the image, so they share the world
+/// and its BVH; `layout` is how they are arranged in it, and how they are written out.
+///
+/// How the eyes see depends on the projection. The perspective projection gives each eye an off-axis view (its image
+/// plane is shifted rather than the eye turned in), so the two views line up `convergence` away, and anything nearer
+/// stands out in front of the screen. The fisheyes look straight ahead from each eye. The equirectangular projection
+/// renders omni-directional stereo: the eyes are turned with every direction looked in, so the panorama is in 3D
+/// whichever way it is looked at, with the eyes coming together towards straight up and down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StereoRig {
+    /// The distance between the eyes, in scene units.
+    pub interocular: f32,
+    /// For the perspective projection, how far away the two views line up (where there is no parallax between them);
+    /// 0 lines them up at `Camera::focus_dist`.
+    pub convergence: f32,
+    pub layout: StereoLayout,
+}
+
+impl StereoRig {
+    /// Eyes 0.065 apart (a person's, for a scene in metres), converging at the focus distance, side by side.
+    pub fn new() -> Self {
+        Self {
+            interocular: 0.065,
+            convergence: 0.0,
+            layout: StereoLayout::SideBySide,
+        }
+    }
+}
+
+impl Default for StereoRig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How a stereo rig's two views are arranged in the film and written out.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum StereoLayout {
+    /// The left eye's view on the left and the right eye's on the right, in one image.
+    SideBySide,
+    /// The left eye's view above the right eye's, in one image (the usual layout for stereo panoramas).
+    TopBottom,
+    /// Rendered side by side, but written to two images, with `_left` and `_right` added to the output's name.
+    Separate,
+}
+
+impl StereoLayout {
+    /// The name used on the command line and in scene files.
+    pub fn name(&self) -> &'static str {
+        match self {
+            StereoLayout::SideBySide => "side_by_side",
+            StereoLayout::TopBottom => "top_bottom",
+            StereoLayout::Separate => "separate",
+        }
+    }
+
+    /// The size of a film holding both views of an image `width` by `height` pixels.
+    pub fn film_size(&self, width: usize, height: usize) -> (usize, usize) {
+        match self {
+            StereoLayout::TopBottom => (width, 2*height),
+            StereoLayout::SideBySide | StereoLayout::Separate => (2*width, height),
+        }
+    }
+
+    /// Where the left and the right eye's views are in a film `width` by `height` pixels holding both.
+    pub fn views(&self, width: usize, height: usize) -> [Tile; 2] {
+        match self {
+            StereoLayout::TopBottom => [
+                Tile {x: 0, y: 0, width, height: height/2},
+                Tile {x: 0, y: height/2, width, height: height/2},
+            ],
+            StereoLayout::SideBySide | StereoLayout::Separate => [
+                Tile {x: 0, y: 0, width: width/2, height},
+                Tile {x: width/2, y: 0, width: width/2, height},
+            ],
+        }
+    }
+}
+
+/// One of a stereo rig's eyes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Eye {
+    Left,
+    Right,
+}
+
+impl Eye {
+    /// Which way the eye is from the camera, along its right: -1 for the left eye, 1 for the right.
+    pub fn side(&self) -> f32 {
+        match self {
+            Eye::Left => -1.0,
+            Eye::Right => 1.0,
+        }
+    }
+}
+
+/// Looks a stereo layout up by the name used on the command line and in scene files.
+pub fn stereo_layout_from_name(name: &str) -> Result<StereoLayout> {
+    match name {
+        "side_by_side" => Ok(StereoLayout::SideBySide),
+        "top_bottom" => Ok(StereoLayout::TopBottom),
+        "separate" => Ok(StereoLayout::Separate),
+        _ => Err(Error::InvalidSettings(format!("unknown stereo layout '{}' (expected side_by_side, top_bottom or separate)", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn views_tile_the_film() {
+        for layout in [StereoLayout::SideBySide, StereoLayout::TopBottom, StereoLayout::Separate] {
+            for (width, height) in [(1, 1), (16, 9), (7, 13)] {
+                let (film_width, film_height) = layout.film_size(width, height);
+                let views = layout.views(film_width, film_height);
+
+                // each view is the size of the image, and together they cover every pixel exactly once
+                let mut covered = vec![0; film_width*film_height];
+                for view in &views {
+                    assert_eq!((view.width, view.height), (width, height), "{} view of a {}x{} image", layout.name(), width, height);
+                    for j in view.y..view.y + view.height {
+                        for i in view.x..view.x + view.width {
+                            covered[j*film_width + i] += 1;
+                        }
+                    }
+                }
+                assert!(covered.iter().all(|&count| count == 1), "{} views of a {}x{} image overlap or leave gaps", layout.name(), width, height);
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+// the crate-wide error type, so failures are reported rather than panicking mid-render
+
+use std::fmt;
+use std::io;
+
+use crate::scene_file::SceneFileError;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing a file failed.
+    Io(io::Error),
+    /// The image crate could not encode the output image.
+    Image(image::ImageError),
+    /// A texture image could not be found or decoded.
+    ImageLoad(String),
+    /// An OBJ model or its MTL material library could not be loaded.
+    ObjLoad(String),
+    /// A scene or camera parameter is out of range.
+    InvalidScene(String),
+    /// A render setting (output format, tonemapper, scene name, ...) is not recognised.
+    InvalidSettings(String),
+    /// A scene file could not be parsed or built.
+    SceneFile(SceneFileError),
+    /// There is nothing to render (or to build a BVH from).
+    EmptyWorld,
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(e) => write!(f, "I/O error: {}", e),
+            Error::Image(e) => write!(f, "could not encode image: {}", e),
+            Error::ImageLoad(filename) => write!(f, "could not load image file '{}'", filename),
+            Error::ObjLoad(msg) => write!(f, "{}", msg),
+            Error::InvalidScene(msg) => write!(f, "invalid scene: {}", msg),
+            Error::InvalidSettings(msg) => write!(f, "{}", msg),
+            Error::SceneFile(e) => write!(f, "{}", e),
+            Error::EmptyWorld => write!(f, "the world has no objects"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Image(e) => Some(e),
+            Error::SceneFile(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Error::Io(e)
+    }
+}
+
+impl From<image::ImageError> for Error {
+    fn from(e: image::ImageError) -> Self {
+        Error::Image(e)
+    }
+}
+
+impl From<SceneFileError> for Error {
+    fn from(e: SceneFileError) -> Self {
+        Error::SceneFile(e)
+    }
+}
@@ -0,0 +1,32 @@
+// the box filter: every sample within the radius counts the same
+
+use crate::filters::filter::Filter;
+
+/// Equal weights out to `radius`. With the default radius of half a pixel each sample only counts towards
+/// the pixel it was taken in, the plain average the renderer has always used.
+pub struct BoxFilter {
+    pub radius: f32,
+}
+
+impl BoxFilter {
+    pub fn new(radius: f32) -> Self {
+        Self {radius}
+    }
+}
+
+impl Filter for BoxFilter {
+    fn name(&self) -> &'static str {
+        "box"
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    // half-open, so a sample on the edge between two pixels only counts towards one of them; samples are taken
+    // in [-0.5, 0.5) around a pixel's center, which puts them (-0.5, 0.5] away from it
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        let inside = |d: f32| d > -self.radius && d <= self.radius;
+        if inside(x) && inside(y) {1.0} else {0.0}
+    }
+}
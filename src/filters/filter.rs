@@ -0,0 +1,63 @@
+// reconstruction filters: how much a sample counts towards each pixel near where it was taken,
+// by its offset from the pixel's center (in pixels)
+
+use crate::error::{Error, Result};
+use crate::filters::box_filter::BoxFilter;
+use crate::filters::gaussian::GaussianFilter;
+use crate::filters::lanczos::LanczosFilter;
+use crate::filters::mitchell::MitchellFilter;
+use crate::filters::tent::TentFilter;
+
+pub trait Filter: Send + Sync {
+    /// The name used on the command line and in scene files.
+    fn name(&self) -> &'static str;
+
+    /// How far (in pixels, on each axis) a sample reaches from where it was taken.
+    fn radius(&self) -> f32;
+
+    /// The weight of a sample `x` and `y` pixels away from a pixel's center. Some filters have
+    /// negative lobes, which sharpen the image; a pixel's weights are normalised by their sum.
+    fn evaluate(&self, x: f32, y: f32) -> f32;
+}
+
+/// Looks a filter up by the name used on the command line and in scene files, with the filter's own
+/// default radius unless `radius` is given.
+pub fn filter_from_name(name: &str, radius: Option<f32>) -> Result<Box<dyn Filter>> {
+    if let Some(radius) = radius {
+        if !radius.is_finite() || radius <= 0.0 {return Err(Error::InvalidSettings(format!("filter radius must be positive, got {}", radius)))}
+    }
+    match name {
+        "box" => Ok(Box::new(BoxFilter::new(radius.unwrap_or(0.5)))),
+        "tent" => Ok(Box::new(TentFilter::new(radius.unwrap_or(1.0)))),
+        "gaussian" => Ok(Box::new(GaussianFilter::new(radius.unwrap_or(1.5), 0.5))),
+        "mitchell" => Ok(Box::new(MitchellFilter::new(radius.unwrap_or(2.0), 1.0 / 3.0, 1.0 / 3.0))),
+        "lanczos" => Ok(Box::new(LanczosFilter::new(radius.unwrap_or(3.0), 3.0))),
+        _ => Err(Error::InvalidSettings(format!("unknown filter '{}' (expected box, tent, gaussian, mitchell or lanczos)", name))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NAMES: [&str; 5] = ["box", "tent", "gaussian", "mitchell", "lanczos"];
+
+    #[test]
+    fn names_round_trip() {
+        for name in NAMES {
+            assert_eq!(filter_from_name(name, None).unwrap().name(), name);
+            let filter = filter_from_name(name, Some(1.25)).unwrap();
+            assert_eq!((filter.name(), filter.radius()), (name, 1.25));
+        }
+    }
+
+    #[test]
+    fn unknown_names_and_bad_radii_are_invalid_settings() {
+        for name in ["", "Box", "sinc"] {
+            assert!(matches!(filter_from_name(name, None), Err(Error::InvalidSettings(_))), "'{}' was accepted", name);
+        }
+        for radius in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(filter_from_name("tent", Some(radius)), Err(Error::InvalidSettings(_))), "radius {} was accepted", radius);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+// the Gaussian filter: a smooth bell, slightly blurring
+
+use crate::filters::filter::Filter;
+
+/// A Gaussian with standard deviation `sigma` (in pixels) on each axis, shifted down so it reaches zero at the radius.
+pub struct GaussianFilter {
+    pub radius: f32,
+    pub sigma: f32,
+}
+
+impl GaussianFilter {
+    pub fn new(radius: f32, sigma: f32) -> Self {
+        Self {radius, sigma}
+    }
+
+    fn gaussian(&self, x: f32) -> f32 {
+        (-x * x / (2.0 * self.sigma * self.sigma)).exp()
+    }
+}
+
+impl Filter for GaussianFilter {
+    fn name(&self) -> &'static str {
+        "gaussian"
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        if x.abs() > self.radius || y.abs() > self.radius {return 0.0}
+        let edge = self.gaussian(self.radius);
+        return (self.gaussian(x) - edge).max(0.0) * (self.gaussian(y) - edge).max(0.0);
+    }
+}
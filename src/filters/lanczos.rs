@@ -0,0 +1,43 @@
+// the Lanczos filter: a windowed sinc, sharp but prone to ringing around bright edges
+
+use std::f32::consts::PI;
+
+use crate::filters::filter::Filter;
+
+/// sinc(x) windowed by sinc(x / tau) on each axis, cut off at the radius.
+pub struct LanczosFilter {
+    pub radius: f32,
+    pub tau: f32,
+}
+
+impl LanczosFilter {
+    pub fn new(radius: f32, tau: f32) -> Self {
+        Self {radius, tau}
+    }
+
+    fn windowed_sinc(&self, x: f32) -> f32 {
+        if x.abs() > self.radius {return 0.0}
+        return sinc(x) * sinc(x / self.tau);
+    }
+}
+
+impl Filter for LanczosFilter {
+    fn name(&self) -> &'static str {
+        "lanczos"
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        self.windowed_sinc(x) * self.windowed_sinc(y)
+    }
+}
+
+fn sinc(x: f32) -> f32 {
+    let x = PI * x;
+    // 1 - x^2 / 6 is sin(x) / x to within rounding this close to 0
+    if x.abs() < 1e-5 {return 1.0 - x * x / 6.0}
+    return x.sin() / x;
+}
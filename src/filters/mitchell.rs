@@ -0,0 +1,44 @@
+// the Mitchell-Netravali filter: a cubic with small negative lobes, a compromise between blurring and ringing
+
+use crate::filters::filter::Filter;
+
+/// Mitchell and Netravali's cubic on each axis, stretched over the radius. `b` = `c` = 1/3 is their recommendation.
+pub struct MitchellFilter {
+    pub radius: f32,
+    pub b: f32,
+    pub c: f32,
+}
+
+impl MitchellFilter {
+    pub fn new(radius: f32, b: f32, c: f32) -> Self {
+        Self {radius, b, c}
+    }
+
+    // the cubic, defined over [-2, 2]
+    fn mitchell(&self, x: f32) -> f32 {
+        let (b, c) = (self.b, self.c);
+        let x = x.abs();
+        let weight = if x <= 1.0 {
+            (12.0 - 9.0*b - 6.0*c) * x*x*x + (-18.0 + 12.0*b + 6.0*c) * x*x + (6.0 - 2.0*b)
+        } else if x <= 2.0 {
+            (-b - 6.0*c) * x*x*x + (6.0*b + 30.0*c) * x*x + (-12.0*b - 48.0*c) * x + (8.0*b + 24.0*c)
+        } else {
+            0.0
+        };
+        return weight / 6.0;
+    }
+}
+
+impl Filter for MitchellFilter {
+    fn name(&self) -> &'static str {
+        "mitchell"
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        self.mitchell(2.0 * x / self.radius) * self.mitchell(2.0 * y / self.radius)
+    }
+}
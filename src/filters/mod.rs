@@ -0,0 +1,6 @@
+pub mod filter;
+pub mod box_filter;
+pub mod gaussian;
+pub mod lanczos;
+pub mod mitchell;
+pub mod tent;
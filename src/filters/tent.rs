@@ -0,0 +1,28 @@
+// the tent (triangle) filter: weights fall off linearly to zero at the radius
+
+use crate::filters::filter::Filter;
+
+/// A tent on each axis, multiplied together.
+pub struct TentFilter {
+    pub radius: f32,
+}
+
+impl TentFilter {
+    pub fn new(radius: f32) -> Self {
+        Self {radius}
+    }
+}
+
+impl Filter for TentFilter {
+    fn name(&self) -> &'static str {
+        "tent"
+    }
+
+    fn radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn evaluate(&self, x: f32, y: f32) -> f32 {
+        (self.radius - x.abs()).max(0.0) * (self.radius - y.abs()).max(0.0)
+    }
+}
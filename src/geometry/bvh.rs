@@ -1,5 +1,7 @@
+use crate::util::rng::Rng;
 use std::cmp::Ordering;
 
+use crate::error::{Error, Result};
 use crate::{geometry::aabb::AABB, geometry::hittable::{HitRecord, Hittable}, geometry::hittable_list::HittableList, util::interval::Interval, core::ray::Ray};
 
 #[derive(Clone)]
@@ -10,9 +12,10 @@ pub struct BVHNode {
 }
 
 impl BVHNode {
-    pub fn from_hittable_list(mut list: HittableList) -> Self {
+    pub fn from_hittable_list(mut list: HittableList) -> Result<Self> {
         let len = list.objects.len();
-        Self::build(&mut list.objects, 0, len)
+        if len == 0 {return Err(Error::EmptyWorld)}
+        Ok(Self::build(&mut list.objects, 0, len))
     }
 
     pub fn build(objects: &mut [Box<dyn Hittable>], start: usize, end: usize) -> Self {
@@ -69,7 +72,7 @@ impl BVHNode {
 }
 
 impl Hittable for BVHNode {
-    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, ray_t: &Interval, rng: &mut Rng) -> Option<HitRecord> {
         //None
 
         //println!("hit for BVHNode");
@@ -83,10 +86,10 @@ impl Hittable for BVHNode {
             return None;
         }
 
-        let hit_left = self.left.hit(ray, ray_t);
+        let hit_left = self.left.hit(ray, ray_t, rng);
         
-        let max = if hit_left.is_some() {hit_left.as_ref().unwrap().t.clone()} else {ray_t.max};
-        let hit_right = self.right.hit(ray, &Interval::new(ray_t.min, max));
+        let max = hit_left.as_ref().map_or(ray_t.max, |rec| rec.t);
+        let hit_right = self.right.hit(ray, &Interval::new(ray_t.min, max), rng);
     
 
         // whiich one to return if both are hits?
@@ -1,3 +1,4 @@
+use crate::util::rng::Rng;
 use crate::materials::lambertian::Lambertian;
 use crate::geometry::aabb::AABB;
 use crate::{materials::material::Material, core::ray::Ray};
@@ -8,11 +9,34 @@ use nalgebra::{Point3, Vector3};
 pub trait Hittable: Send + Sync {
     // a trait will be used as a sort of "parent class" for hittable objects
     
-    fn hit(&self, _ray: &Ray, _ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, _ray: &Ray, _ray_t: &Interval, _rng: &mut Rng) -> Option<HitRecord> {
         None
     }
     fn bounding_box(&self) -> AABB;
 
+    // used to sample lights directly (next-event estimation): pdf_value is the probability density,
+    // per unit solid angle seen from origin, of random picking direction; random picks a direction
+    // from origin towards the object. Objects that can't be sampled leave these as they are.
+    fn pdf_value(&self, _origin: &Point3<f32>, _direction: &Vector3<f32>, _rng: &mut Rng) -> f32 {
+        0.0
+    }
+
+    fn random(&self, _origin: &Point3<f32>, _rng: &mut Rng) -> Vector3<f32> {
+        Vector3::new(1.0, 0.0, 0.0)
+    }
+
+    // used to start paths on lights (bidirectional path tracing): sample_surface picks a uniformly
+    // distributed point on the object, as a hit record with its outward normal, along with the
+    // probability density per unit area of picking it; surface_pdf is that density at a point on
+    // the object, or 0 for a point that isn't on it. Moving objects are sampled where they start.
+    fn sample_surface(&self, _rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        None
+    }
+
+    fn surface_pdf(&self, _point: &Point3<f32>) -> f32 {
+        0.0
+    }
+
     fn clone_box(&self) -> Box<dyn Hittable>;
 }
 
@@ -22,6 +46,11 @@ impl Clone for Box<dyn Hittable> {
     }
 }
 
+/// How far a point can be from a surface and still count as on it, allowing for rounding in the intersection tests.
+pub(crate) fn on_surface_tolerance(point: &Point3<f32>) -> f32 {
+    1e-4 * point.coords.amax().max(1.0)
+}
+
 //#[derive(Clone)]
 pub struct HitRecord {
     pub p: Point3<f32>,
@@ -67,11 +96,17 @@ impl HitRecord {
         // set normal vector
 
 
-        self.front_face = ray.direction().dot(&outward_normal) < 0.0;
+        self.front_face = ray.direction().dot(outward_normal) < 0.0;
         if self.front_face {
             self.normal = outward_normal.clone()
         } 
         else {self.normal = -outward_normal};
 
     }
+}
+
+impl Default for HitRecord {
+    fn default() -> Self {
+        Self::new()
+    }
 }
\ No newline at end of file
@@ -1,7 +1,10 @@
+use crate::util::rng::Rng;
 use crate::geometry::aabb::AABB;
 use crate::geometry::hittable::{Hittable, HitRecord};
 use crate::util::interval::Interval;
 use crate::core::ray::Ray;
+use crate::util::vector_math::random_f32;
+use nalgebra::{Point3, Vector3};
 
 #[derive(Clone)]
 pub struct HittableList {
@@ -31,18 +34,31 @@ impl HittableList {
         //println!("hittable list bbox: {:?}", &self.bbox);
     }
 
+    pub fn add_list(&mut self, list: HittableList) {
+        // adds every object of another list (e.g. the triangles of a mesh) individually, so a BVH can split them up
+        for object in list.objects {
+            self.add(object);
+        }
+    }
+
     
 }
 
+impl Default for HittableList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Hittable for HittableList {
-    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, ray_t: &Interval, rng: &mut Rng) -> Option<HitRecord> {
         // returns a HitRecord if ray intersects an object between t_min and t_max
         //println!("hit for hittable list");
         let mut closest_so_far  = ray_t.max;
         let mut final_hit = None;
 
         for object in self.objects.iter() {
-            if let Some(hit_record) = object.hit(ray, &Interval::new(ray_t.min, closest_so_far)) {
+            if let Some(hit_record) = object.hit(ray, &Interval::new(ray_t.min, closest_so_far), rng) {
                 closest_so_far = hit_record.t;
                 final_hit = Some(hit_record);
             }
@@ -52,7 +68,36 @@ impl Hittable for HittableList {
 
     fn bounding_box(&self) -> AABB {
         //println!("in hittable list bbox");
-        return self.bbox.clone().expect("Hittable list has no objects");
+        // an empty list has nothing to bound; AABB::universe() is built from the inverted
+        // Interval::default() (min = +inf, max = -inf), so no ray can hit it
+        return self.bbox.clone().unwrap_or(AABB::universe());
+    }
+
+    fn pdf_value(&self, origin: &Point3<f32>, direction: &Vector3<f32>, rng: &mut Rng) -> f32 {
+        // random() picks each object with equal probability, so the density is the average of theirs
+        if self.objects.is_empty() {return 0.0}
+        let weight = 1.0 / self.objects.len() as f32;
+        return self.objects.iter().map(|object| weight * object.pdf_value(origin, direction, rng)).sum();
+    }
+
+    fn random(&self, origin: &Point3<f32>, rng: &mut Rng) -> Vector3<f32> {
+        if self.objects.is_empty() {return Vector3::new(1.0, 0.0, 0.0)}
+        let index = ((random_f32(rng) * self.objects.len() as f32) as usize).min(self.objects.len() - 1);
+        return self.objects[index].random(origin, rng);
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        // like random(), pick an object with equal probability, so its density is shared between them
+        if self.objects.is_empty() {return None}
+        let index = ((random_f32(rng) * self.objects.len() as f32) as usize).min(self.objects.len() - 1);
+        let (rec, pdf) = self.objects[index].sample_surface(rng)?;
+        return Some((rec, pdf / self.objects.len() as f32));
+    }
+
+    fn surface_pdf(&self, point: &Point3<f32>) -> f32 {
+        if self.objects.is_empty() {return 0.0}
+        let weight = 1.0 / self.objects.len() as f32;
+        return self.objects.iter().map(|object| weight * object.surface_pdf(point)).sum();
     }
 
     fn clone_box(&self) -> Box<dyn Hittable> {
@@ -3,4 +3,6 @@ pub mod bvh;
 pub mod hittable_list;
 pub mod hittable;
 pub mod quad;
-pub mod sphere;
\ No newline at end of file
+pub mod sphere;
+pub mod triangle;
+pub mod triangle_mesh;
@@ -1,8 +1,9 @@
 // struct for parallelograms (the tutorial RTiOW calls them quadrilaterals)
 
 
+use crate::util::rng::Rng;
 use crate::geometry::aabb::AABB;
-use crate::geometry::hittable::{Hittable, HitRecord};
+use crate::geometry::hittable::{on_surface_tolerance, Hittable, HitRecord};
 use crate::geometry::hittable_list::HittableList;
 use crate::util::interval::Interval;
 use crate::core::ray::Ray;
@@ -20,6 +21,7 @@ pub struct Quad {
     bbox: AABB,
     normal: Vector3<f32>,
     d: f32,
+    area: f32,
 }
 
 impl Quad {
@@ -37,6 +39,7 @@ impl Quad {
             normal,
             d: q.coords.dot(&normal),
             w: n/(n.dot(&n)),
+            area: n.norm(),
         }
     }
 
@@ -46,7 +49,7 @@ impl Quad {
 
 impl Hittable for Quad {
 
-    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, ray_t: &Interval, _rng: &mut Rng) -> Option<HitRecord> {
        
         let denom = self.normal.dot(&ray.direction());
 
@@ -65,9 +68,7 @@ impl Hittable for Quad {
         let alpha = self.w.dot(&planar_hitpt_vector.cross(&self.v));
         let beta = self.w.dot(&self.u.cross(&planar_hitpt_vector));
 
-        if is_interior(alpha, beta).is_none() {
-            return None;
-        }
+        is_interior(alpha, beta)?;
         // IM NOT 100% SURE I'VE DONE THIS NORMAL CORRECTLY -- IF IT DOESN'T WORK CHECK HERE FIRST
 
         let mut rec = HitRecord::new_from(intersection, self.normal, self.mat.clone(), t);
@@ -84,6 +85,43 @@ impl Hittable for Quad {
         return self.bbox.clone();
     }
 
+    fn pdf_value(&self, origin: &Point3<f32>, direction: &Vector3<f32>, rng: &mut Rng) -> f32 {
+        let Some(rec) = self.hit(&Ray::new_from(*origin, *direction, 0.0), &Interval::new(0.001, f32::INFINITY), rng) else {
+            return 0.0;
+        };
+
+        // convert the uniform density over the area into a density over solid angle
+        let distance_squared = rec.t * rec.t * direction.norm_squared();
+        let cosine = (direction.dot(&rec.normal) / direction.norm()).abs();
+        if cosine < 1e-8 {return 0.0}
+
+        return distance_squared / (cosine * self.area);
+    }
+
+    fn random(&self, origin: &Point3<f32>, rng: &mut Rng) -> Vector3<f32> {
+        let (alpha, beta) = rng.next_2d();
+        let p = self.q + (alpha * self.u) + (beta * self.v);
+        return p - origin;
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        let (alpha, beta) = rng.next_2d();
+        let mut rec = HitRecord::new_from(self.q + (alpha * self.u) + (beta * self.v), self.normal, self.mat.clone(), 0.0);
+        rec.u = alpha;
+        rec.v = beta;
+        rec.front_face = true;
+        Some((rec, 1.0 / self.area))
+    }
+
+    fn surface_pdf(&self, point: &Point3<f32>) -> f32 {
+        if (self.normal.dot(&point.coords) - self.d).abs() > on_surface_tolerance(point) {return 0.0}
+        let planar_hitpt_vector = point - self.q;
+        let alpha = self.w.dot(&planar_hitpt_vector.cross(&self.v));
+        let beta = self.w.dot(&self.u.cross(&planar_hitpt_vector));
+        if is_interior(alpha, beta).is_none() {return 0.0}
+        return 1.0 / self.area;
+    }
+
     fn clone_box(&self) -> Box<dyn Hittable> {
         Box::new((*self).clone())
     }
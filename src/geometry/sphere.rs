@@ -1,7 +1,10 @@
 
+use crate::util::rng::Rng;
 use crate::geometry::aabb::AABB;
-use crate::geometry::hittable::{Hittable, HitRecord};
+use crate::geometry::hittable::{on_surface_tolerance, Hittable, HitRecord};
 use crate::util::interval::Interval;
+use crate::util::onb::ONB;
+use crate::util::vector_math::random_unit_vector;
 use crate::core::ray::Ray;
 use crate::materials::material::Material;
 use nalgebra::{Point3, Vector3};
@@ -47,7 +50,7 @@ impl Sphere {
         //     <1 0 0> yields <0.50 0.50>       <-1  0  0> yields <0.00 0.50>
         //     <0 1 0> yields <0.50 1.00>       < 0 -1  0> yields <0.50 0.00>
         //     <0 0 1> yields <0.25 0.50>       < 0  0 -1> yields <0.75 0.50>
-        let y_clamped = p.y.max(-1.0).min(1.0);
+        let y_clamped = p.y.clamp(-1.0, 1.0);
         let theta = y_clamped.acos();
         let phi = -p.z.atan2(p.x) + std::f32::consts::PI;
 
@@ -56,7 +59,7 @@ impl Sphere {
 }
 
 impl Hittable for Sphere {
-    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, ray_t: &Interval, _rng: &mut Rng) -> Option<HitRecord> {
         // Compute the ray-sphere intersection here.
         //println!("In sphere hit");
 
@@ -108,6 +111,43 @@ impl Hittable for Sphere {
         Some(rec)
     }
 
+    fn pdf_value(&self, origin: &Point3<f32>, direction: &Vector3<f32>, rng: &mut Rng) -> f32 {
+        // only works for stationary spheres (a moving light is sampled where it starts)
+        if self.hit(&Ray::new_from(*origin, *direction, 0.0), &Interval::new(0.001, f32::INFINITY), rng).is_none() {
+            return 0.0;
+        }
+
+        let distance_squared = (self.center.at(0.0) - origin).norm_squared();
+        if distance_squared <= self.radius * self.radius {return 0.0}
+        let cos_theta_max = (1.0 - self.radius * self.radius / distance_squared).sqrt();
+        let solid_angle = 2.0 * std::f32::consts::PI * (1.0 - cos_theta_max);
+
+        return 1.0 / solid_angle;
+    }
+
+    fn random(&self, origin: &Point3<f32>, rng: &mut Rng) -> Vector3<f32> {
+        let direction = self.center.at(0.0) - origin;
+        let distance_squared = direction.norm_squared();
+        if distance_squared <= self.radius * self.radius {return direction}
+
+        // a direction in the cone the sphere covers, built around the z axis then turned towards the sphere
+        let uvw = ONB::new(&direction);
+        return uvw.transform(&random_to_sphere(self.radius, distance_squared, rng));
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        let normal = random_unit_vector(rng);
+        let mut rec = HitRecord::new_from(self.center.at(0.0) + self.radius * normal, normal, self.mat.clone(), 0.0);
+        (rec.u, rec.v) = Sphere::get_sphere_uv(&Point3::from(normal));
+        rec.front_face = true;
+        Some((rec, 1.0 / (4.0 * std::f32::consts::PI * self.radius * self.radius)))
+    }
+
+    fn surface_pdf(&self, point: &Point3<f32>) -> f32 {
+        if ((point - self.center.at(0.0)).norm() - self.radius).abs() > on_surface_tolerance(point) {return 0.0}
+        return 1.0 / (4.0 * std::f32::consts::PI * self.radius * self.radius);
+    }
+
     fn bounding_box(&self) -> AABB {
         //println!("in sphere bbox");
         return self.bbox.clone();
@@ -116,4 +156,15 @@ impl Hittable for Sphere {
     fn clone_box(&self) -> Box<dyn Hittable> {
         Box::new((*self).clone())
     }
-}
\ No newline at end of file
+}
+
+fn random_to_sphere(radius: f32, distance_squared: f32, rng: &mut Rng) -> Vector3<f32> {
+    let (r1, r2) = rng.next_2d();
+    let z = 1.0 + r2 * ((1.0 - radius * radius / distance_squared).sqrt() - 1.0);
+
+    let phi = 2.0 * std::f32::consts::PI * r1;
+    let x = phi.cos() * (1.0 - z * z).sqrt();
+    let y = phi.sin() * (1.0 - z * z).sqrt();
+
+    return Vector3::new(x, y, z);
+}
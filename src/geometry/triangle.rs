@@ -0,0 +1,229 @@
+// struct for single triangles, plus the ray-triangle test shared with triangle meshes
+
+use crate::util::rng::Rng;
+use crate::error::{Error, Result};
+use crate::geometry::aabb::AABB;
+use crate::geometry::hittable::{on_surface_tolerance, Hittable, HitRecord};
+use crate::util::interval::Interval;
+use crate::core::ray::Ray;
+use crate::materials::material::Material;
+use nalgebra::{Point3, Vector3};
+
+#[derive(Clone)]
+pub struct Triangle {
+    a: Point3<f32>,
+    b: Point3<f32>,
+    c: Point3<f32>,
+    mat: Box<dyn Material + Send + Sync>,
+    bbox: AABB,
+    normal: Vector3<f32>,
+}
+
+impl Triangle {
+
+    /// Fails if the corners are collinear, since a triangle with no area has no normal.
+    pub fn new(a: Point3<f32>, b: Point3<f32>, c: Point3<f32>, mat: Box<dyn Material>) -> Result<Self> {
+        let Some(normal) = (b - a).cross(&(c - a)).try_normalize(0.0) else {
+            return Err(Error::InvalidScene("triangle has no area (its corners are in a line)".to_string()));
+        };
+        Ok(Self {
+            a,
+            b,
+            c,
+            mat,
+            bbox: triangle_bounding_box(&a, &b, &c),
+            normal,
+        })
+    }
+}
+
+impl Hittable for Triangle {
+
+    fn hit(&self, ray: &Ray, ray_t: &Interval, _rng: &mut Rng) -> Option<HitRecord> {
+        let (t, beta, gamma) = intersect_triangle(&self.a, &self.b, &self.c, ray, ray_t)?;
+
+        let mut rec = HitRecord::new_from(ray.at(t), self.normal, self.mat.clone(), t);
+
+        // with no texture coordinates of its own, a triangle is textured by its barycentric coordinates
+        rec.u = beta;
+        rec.v = gamma;
+        rec.set_face_normal(ray, &self.normal);
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        return self.bbox.clone();
+    }
+
+    fn pdf_value(&self, origin: &Point3<f32>, direction: &Vector3<f32>, _rng: &mut Rng) -> f32 {
+        return triangle_pdf_value(&self.a, &self.b, &self.c, origin, direction);
+    }
+
+    fn random(&self, origin: &Point3<f32>, rng: &mut Rng) -> Vector3<f32> {
+        return random_on_triangle(&self.a, &self.b, &self.c, rng) - origin;
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        let (beta, gamma) = random_barycentric(rng);
+        let p = self.a + (beta * (self.b - self.a)) + (gamma * (self.c - self.a));
+        let mut rec = HitRecord::new_from(p, self.normal, self.mat.clone(), 0.0);
+        rec.u = beta;
+        rec.v = gamma;
+        rec.front_face = true;
+        Some((rec, 1.0 / triangle_area(&self.a, &self.b, &self.c)))
+    }
+
+    fn surface_pdf(&self, point: &Point3<f32>) -> f32 {
+        return triangle_surface_pdf(&self.a, &self.b, &self.c, point);
+    }
+
+    fn clone_box(&self) -> Box<dyn Hittable> {
+        Box::new((*self).clone())
+    }
+}
+
+pub(crate) fn triangle_bounding_box(a: &Point3<f32>, b: &Point3<f32>, c: &Point3<f32>) -> AABB {
+    let min = Point3::new(a.x.min(b.x).min(c.x), a.y.min(b.y).min(c.y), a.z.min(b.z).min(c.z));
+    let max = Point3::new(a.x.max(b.x).max(c.x), a.y.max(b.y).max(c.y), a.z.max(b.z).max(c.z));
+    return AABB::new_from_extrema(min, max);
+}
+
+/// Möller-Trumbore ray-triangle intersection.
+/// Returns the ray parameter and the barycentric weights of `b` and `c` at the hit point
+/// (the weight of `a` is whatever is left over).
+pub(crate) fn intersect_triangle(a: &Point3<f32>, b: &Point3<f32>, c: &Point3<f32>, ray: &Ray, ray_t: &Interval) -> Option<(f32, f32, f32)> {
+    let edge1 = b - a;
+    let edge2 = c - a;
+
+    let p = ray.direction().cross(&edge2);
+    let det = edge1.dot(&p);
+
+    // no hit if the ray is parallel to the triangle (or the triangle is degenerate)
+    if det.abs() < 1e-8 {
+        return None;
+    }
+    let inv_det = 1.0 / det;
+
+    let s = ray.origin() - a;
+    let beta = s.dot(&p) * inv_det;
+    if !(0.0..=1.0).contains(&beta) {
+        return None;
+    }
+
+    let q = s.cross(&edge1);
+    let gamma = ray.direction().dot(&q) * inv_det;
+    if gamma < 0.0 || beta + gamma > 1.0 {
+        return None;
+    }
+
+    let t = edge2.dot(&q) * inv_det;
+    if !ray_t.contains(t) {
+        return None;
+    }
+
+    return Some((t, beta, gamma));
+}
+
+/// The density, over solid angle seen from `origin`, of sampling `direction` by picking a uniform point on the triangle.
+pub(crate) fn triangle_pdf_value(a: &Point3<f32>, b: &Point3<f32>, c: &Point3<f32>, origin: &Point3<f32>, direction: &Vector3<f32>) -> f32 {
+    let ray = Ray::new_from(*origin, *direction, 0.0);
+    let Some((t, _, _)) = intersect_triangle(a, b, c, &ray, &Interval::new(0.001, f32::INFINITY)) else {
+        return 0.0;
+    };
+
+    let n = (b - a).cross(&(c - a));
+    let area = 0.5 * n.norm();
+    let distance_squared = t * t * direction.norm_squared();
+    let cosine = (direction.dot(&n) / (direction.norm() * n.norm())).abs();
+    if cosine < 1e-8 {return 0.0}
+
+    return distance_squared / (cosine * area);
+}
+
+/// A uniformly distributed point on the triangle.
+pub(crate) fn random_on_triangle(a: &Point3<f32>, b: &Point3<f32>, c: &Point3<f32>, rng: &mut Rng) -> Point3<f32> {
+    let (r1, r2) = random_barycentric(rng);
+    return a + (r1 * (b - a)) + (r2 * (c - a));
+}
+
+/// The barycentric weights of `b` and `c` for a uniformly distributed point on a triangle.
+pub(crate) fn random_barycentric(rng: &mut Rng) -> (f32, f32) {
+    let (mut r1, mut r2) = rng.next_2d();
+    // fold the half of the unit square outside the triangle back into it
+    if r1 + r2 > 1.0 {
+        r1 = 1.0 - r1;
+        r2 = 1.0 - r2;
+    }
+    return (r1, r2);
+}
+
+pub(crate) fn triangle_area(a: &Point3<f32>, b: &Point3<f32>, c: &Point3<f32>) -> f32 {
+    return 0.5 * (b - a).cross(&(c - a)).norm();
+}
+
+/// The density, per unit area, of picking `point` uniformly on the triangle (0 if it isn't on it).
+pub(crate) fn triangle_surface_pdf(a: &Point3<f32>, b: &Point3<f32>, c: &Point3<f32>, point: &Point3<f32>) -> f32 {
+    let n = (b - a).cross(&(c - a));
+    let Some(normal) = n.try_normalize(0.0) else {return 0.0};
+    let s = point - a;
+    if s.dot(&normal).abs() > on_surface_tolerance(point) {return 0.0}
+
+    // barycentric weights of b and c, from the sub-triangles opposite them
+    let n2 = n.norm_squared();
+    let beta = s.cross(&(c - a)).dot(&n) / n2;
+    let gamma = (b - a).cross(&s).dot(&n) / n2;
+    if beta < 0.0 || gamma < 0.0 || beta + gamma > 1.0 {return 0.0}
+
+    return 2.0 / n.norm();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::colour::Colour;
+    use crate::materials::lambertian::Lambertian;
+
+    fn unit_triangle() -> Triangle {
+        let mat = Box::new(Lambertian::new_from(Colour::new_from(0.5, 0.5, 0.5)));
+        return Triangle::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0), Point3::new(0.0, 1.0, 0.0), mat).unwrap();
+    }
+
+    fn down_from(x: f32, y: f32) -> Ray {
+        return Ray::new_from(Point3::new(x, y, 1.0), Vector3::new(0.0, 0.0, -1.0), 0.0);
+    }
+
+    #[test]
+    fn hits_inside_and_misses_outside() {
+        let triangle = unit_triangle();
+        let ray_t = Interval::new(0.001, f32::INFINITY);
+        let mut rng = Rng::new(1);
+
+        let rec = triangle.hit(&down_from(0.25, 0.25), &ray_t, &mut rng).expect("ray through the middle should hit");
+        assert!((rec.t - 1.0).abs() < 1e-6);
+        assert!((rec.p - Point3::new(0.25, 0.25, 0.0)).norm() < 1e-6);
+        assert!((rec.normal - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-6);
+        assert!(rec.front_face);
+
+        // past the hypotenuse, behind the ray, and parallel to the triangle
+        assert!(triangle.hit(&down_from(0.6, 0.6), &ray_t, &mut rng).is_none());
+        assert!(triangle.hit(&down_from(0.25, 0.25), &Interval::new(0.001, 0.5), &mut rng).is_none());
+        let parallel = Ray::new_from(Point3::new(-1.0, 0.25, 0.0), Vector3::new(1.0, 0.0, 0.0), 0.0);
+        assert!(triangle.hit(&parallel, &ray_t, &mut rng).is_none());
+    }
+
+    #[test]
+    fn uv_is_the_barycentric_weights_of_b_and_c() {
+        let triangle = unit_triangle();
+        let rec = triangle.hit(&down_from(0.2, 0.7), &Interval::new(0.001, f32::INFINITY), &mut Rng::new(1)).unwrap();
+        assert!((rec.u - 0.2).abs() < 1e-6);
+        assert!((rec.v - 0.7).abs() < 1e-6);
+    }
+
+    #[test]
+    fn degenerate_triangles_are_rejected() {
+        let mat = Box::new(Lambertian::new_from(Colour::new_from(0.5, 0.5, 0.5)));
+        let collinear = Triangle::new(Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0), Point3::new(2.0, 2.0, 2.0), mat);
+        assert!(matches!(collinear, Err(Error::InvalidScene(_))));
+    }
+}
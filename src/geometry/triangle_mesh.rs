@@ -0,0 +1,294 @@
+// an indexed triangle mesh
+// the vertex, normal and texture coordinate buffers are stored once and shared by every triangle,
+// and each triangle is its own hittable so the BVH can split the mesh up like any other objects
+
+use std::sync::Arc;
+
+use crate::util::rng::Rng;
+use crate::error::{Error, Result};
+use crate::geometry::aabb::AABB;
+use crate::geometry::hittable::{Hittable, HitRecord};
+use crate::geometry::hittable_list::HittableList;
+use crate::geometry::triangle::{intersect_triangle, random_barycentric, random_on_triangle, triangle_area, triangle_bounding_box, triangle_pdf_value, triangle_surface_pdf};
+use crate::util::interval::Interval;
+use crate::core::ray::Ray;
+use crate::materials::material::Material;
+use nalgebra::{Point3, Vector3};
+
+pub struct TriangleMesh {
+    pub positions: Vec<Point3<f32>>,
+    /// Either empty or one normal per position.
+    pub normals: Vec<Vector3<f32>>,
+    /// Either empty or one (u, v) per position. Without them triangles use their barycentric coordinates.
+    pub uvs: Vec<(f32, f32)>,
+    /// Three indices into the vertex buffers per triangle, counter-clockwise seen from the front.
+    pub indices: Vec<[usize; 3]>,
+    /// Shade with the interpolated vertex normals rather than the flat face normal.
+    pub smooth: bool,
+    mat: Box<dyn Material>,
+}
+
+impl TriangleMesh {
+
+    pub fn new(positions: Vec<Point3<f32>>, indices: Vec<[usize; 3]>, mat: Box<dyn Material>) -> Self {
+        Self {
+            positions,
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            indices,
+            smooth: false,
+            mat,
+        }
+    }
+
+    pub fn material(&self) -> &dyn Material {
+        self.mat.as_ref()
+    }
+
+    pub fn set_material(&mut self, mat: Box<dyn Material>) {
+        self.mat = mat;
+    }
+
+    pub fn num_triangles(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// Fills in vertex normals by averaging the normals of the faces around each vertex
+    /// (weighted by face area), and turns on smooth shading.
+    /// Fails if a triangle refers to a vertex that doesn't exist.
+    pub fn compute_normals(&mut self) -> Result<()> {
+        self.validate_indices()?;
+
+        let mut normals = vec![Vector3::zeros(); self.positions.len()];
+        for [i0, i1, i2] in &self.indices {
+            // the unnormalised cross product is twice the face area
+            let face_normal = (self.positions[*i1] - self.positions[*i0]).cross(&(self.positions[*i2] - self.positions[*i0]));
+            for i in [i0, i1, i2] {
+                normals[*i] += face_normal;
+            }
+        }
+        self.normals = normals.into_iter().map(|n| n.try_normalize(0.0).unwrap_or(Vector3::zeros())).collect();
+        self.smooth = true;
+        Ok(())
+    }
+
+    fn validate_indices(&self) -> Result<()> {
+        let vertices = self.positions.len();
+        if let Some((n, tri)) = self.indices.iter().enumerate().find(|(_, tri)| tri.iter().any(|i| *i >= vertices)) {
+            return Err(Error::InvalidScene(format!("triangle {} of the mesh refers to vertex {:?} but there are only {} vertices", n, tri, vertices)));
+        }
+        Ok(())
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |msg: String| Err(Error::InvalidScene(msg));
+        let vertices = self.positions.len();
+
+        if self.indices.is_empty() {return invalid("triangle mesh has no triangles".to_string())}
+        if !self.normals.is_empty() && self.normals.len() != vertices {
+            return invalid(format!("triangle mesh has {} normals for {} vertices", self.normals.len(), vertices));
+        }
+        if !self.uvs.is_empty() && self.uvs.len() != vertices {
+            return invalid(format!("triangle mesh has {} texture coordinates for {} vertices", self.uvs.len(), vertices));
+        }
+        if self.smooth && self.normals.is_empty() {
+            return invalid("smooth shading needs vertex normals (see TriangleMesh::compute_normals)".to_string());
+        }
+        self.validate_indices()
+    }
+
+    /// One hittable per triangle, all sharing this mesh's buffers, ready to add to a world or BVH.
+    pub fn into_hittable_list(self) -> Result<HittableList> {
+        self.validate()?;
+
+        let mat = self.mat;
+        let data = Arc::new(MeshData {
+            positions: self.positions,
+            normals: if self.smooth {self.normals} else {Vec::new()},
+            uvs: self.uvs,
+        });
+
+        let mut triangles = HittableList::new();
+        for indices in self.indices {
+            let [a, b, c] = indices.map(|i| data.positions[i]);
+            // skip triangles with no area, they can't be hit and have no normal
+            let Some(normal) = (b - a).cross(&(c - a)).try_normalize(0.0) else {continue};
+            triangles.add(Box::new(MeshTriangle {
+                mesh: Arc::clone(&data),
+                indices,
+                mat: mat.clone(),
+                bbox: triangle_bounding_box(&a, &b, &c),
+                normal,
+            }));
+        }
+        if triangles.objects.is_empty() {return Err(Error::InvalidScene("every triangle in the mesh is degenerate".to_string()))}
+
+        Ok(triangles)
+    }
+}
+
+// the buffers every triangle of a mesh points into
+struct MeshData {
+    positions: Vec<Point3<f32>>,
+    normals: Vec<Vector3<f32>>,
+    uvs: Vec<(f32, f32)>,
+}
+
+#[derive(Clone)]
+struct MeshTriangle {
+    mesh: Arc<MeshData>,
+    indices: [usize; 3],
+    mat: Box<dyn Material>,
+    bbox: AABB,
+    normal: Vector3<f32>,
+}
+
+impl Hittable for MeshTriangle {
+
+    fn hit(&self, ray: &Ray, ray_t: &Interval, _rng: &mut Rng) -> Option<HitRecord> {
+        let [i0, i1, i2] = self.indices;
+        let mesh = &self.mesh;
+        let (t, beta, gamma) = intersect_triangle(&mesh.positions[i0], &mesh.positions[i1], &mesh.positions[i2], ray, ray_t)?;
+        let alpha = 1.0 - beta - gamma;
+
+        let mut rec = HitRecord::new_from(ray.at(t), self.normal, self.mat.clone(), t);
+        rec.set_face_normal(ray, &self.normal);
+
+        if mesh.uvs.is_empty() {
+            rec.u = beta;
+            rec.v = gamma;
+        } else {
+            let (uv0, uv1, uv2) = (mesh.uvs[i0], mesh.uvs[i1], mesh.uvs[i2]);
+            rec.u = alpha*uv0.0 + beta*uv1.0 + gamma*uv2.0;
+            rec.v = alpha*uv0.1 + beta*uv1.1 + gamma*uv2.1;
+        }
+
+        // smooth shading: interpolate the vertex normals, keeping them on the side the ray came from
+        if !mesh.normals.is_empty() {
+            let shading_normal = alpha*mesh.normals[i0] + beta*mesh.normals[i1] + gamma*mesh.normals[i2];
+            if let Some(shading_normal) = shading_normal.try_normalize(0.0) {
+                rec.normal = if shading_normal.dot(&rec.normal) < 0.0 {-shading_normal} else {shading_normal};
+            }
+        }
+
+        Some(rec)
+    }
+
+    fn bounding_box(&self) -> AABB {
+        return self.bbox.clone();
+    }
+
+    fn pdf_value(&self, origin: &Point3<f32>, direction: &Vector3<f32>, _rng: &mut Rng) -> f32 {
+        let [i0, i1, i2] = self.indices;
+        return triangle_pdf_value(&self.mesh.positions[i0], &self.mesh.positions[i1], &self.mesh.positions[i2], origin, direction);
+    }
+
+    fn random(&self, origin: &Point3<f32>, rng: &mut Rng) -> Vector3<f32> {
+        let [i0, i1, i2] = self.indices;
+        return random_on_triangle(&self.mesh.positions[i0], &self.mesh.positions[i1], &self.mesh.positions[i2], rng) - origin;
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        let [i0, i1, i2] = self.indices;
+        let mesh = &self.mesh;
+        let (a, b, c) = (mesh.positions[i0], mesh.positions[i1], mesh.positions[i2]);
+        let (beta, gamma) = random_barycentric(rng);
+        let alpha = 1.0 - beta - gamma;
+
+        let mut rec = HitRecord::new_from(a + (beta * (b - a)) + (gamma * (c - a)), self.normal, self.mat.clone(), 0.0);
+        rec.front_face = true;
+        if mesh.uvs.is_empty() {
+            rec.u = beta;
+            rec.v = gamma;
+        } else {
+            let (uv0, uv1, uv2) = (mesh.uvs[i0], mesh.uvs[i1], mesh.uvs[i2]);
+            rec.u = alpha*uv0.0 + beta*uv1.0 + gamma*uv2.0;
+            rec.v = alpha*uv0.1 + beta*uv1.1 + gamma*uv2.1;
+        }
+        Some((rec, 1.0 / triangle_area(&a, &b, &c)))
+    }
+
+    fn surface_pdf(&self, point: &Point3<f32>) -> f32 {
+        let [i0, i1, i2] = self.indices;
+        return triangle_surface_pdf(&self.mesh.positions[i0], &self.mesh.positions[i1], &self.mesh.positions[i2], point);
+    }
+
+    fn clone_box(&self) -> Box<dyn Hittable> {
+        Box::new((*self).clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::colour::Colour;
+    use crate::materials::lambertian::Lambertian;
+
+    // the unit square in the z = 0 plane, split along its diagonal
+    fn square() -> TriangleMesh {
+        let positions = vec![Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 0.0, 0.0), Point3::new(1.0, 1.0, 0.0), Point3::new(0.0, 1.0, 0.0)];
+        let mat = Box::new(Lambertian::new_from(Colour::new_from(0.5, 0.5, 0.5)));
+        return TriangleMesh::new(positions, vec![[0, 1, 2], [0, 2, 3]], mat);
+    }
+
+    fn hit_from_above(mesh: TriangleMesh, x: f32, y: f32) -> Option<HitRecord> {
+        let ray = Ray::new_from(Point3::new(x, y, 1.0), Vector3::new(0.0, 0.0, -1.0), 0.0);
+        return mesh.into_hittable_list().unwrap().hit(&ray, &Interval::new(0.001, f32::INFINITY), &mut Rng::new(1));
+    }
+
+    #[test]
+    fn hits_either_triangle_and_misses_outside() {
+        for (x, y) in [(0.7, 0.2), (0.2, 0.7)] {
+            let rec = hit_from_above(square(), x, y).expect("ray onto the square should hit");
+            assert!((rec.p - Point3::new(x, y, 0.0)).norm() < 1e-6);
+            assert!((rec.normal - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-6);
+        }
+        assert!(hit_from_above(square(), 1.2, 0.5).is_none());
+        assert!(hit_from_above(square(), -0.1, 0.5).is_none());
+    }
+
+    #[test]
+    fn texture_coordinates_are_interpolated() {
+        // texture coordinates running the other way to the positions, so they can't be mistaken for barycentrics
+        let mut mesh = square();
+        mesh.uvs = vec![(1.0, 1.0), (0.0, 1.0), (0.0, 0.0), (1.0, 0.0)];
+        let rec = hit_from_above(mesh, 0.7, 0.2).unwrap();
+        assert!((rec.u - 0.3).abs() < 1e-6);
+        assert!((rec.v - 0.8).abs() < 1e-6);
+
+        // without them, the barycentric weights of the second and third vertex
+        let rec = hit_from_above(square(), 0.7, 0.2).unwrap();
+        assert!((rec.u - 0.5).abs() < 1e-6);
+        assert!((rec.v - 0.2).abs() < 1e-6);
+    }
+
+    #[test]
+    fn smooth_normals_are_interpolated() {
+        let mut mesh = square();
+        mesh.normals = vec![Vector3::new(-1.0, 0.0, 1.0), Vector3::new(1.0, 0.0, 1.0), Vector3::new(1.0, 0.0, 1.0), Vector3::new(-1.0, 0.0, 1.0)];
+        mesh.smooth = true;
+
+        // a quarter of the way across, the normal is a quarter of the way from tilting left to tilting right
+        let rec = hit_from_above(mesh, 0.25, 0.1).unwrap();
+        let expected = Vector3::new(-0.5, 0.0, 1.0).normalize();
+        assert!((rec.normal - expected).norm() < 1e-5, "normal {:?}", rec.normal);
+    }
+
+    #[test]
+    fn computed_normals_of_a_flat_mesh_are_the_face_normal() {
+        let mut mesh = square();
+        mesh.compute_normals().unwrap();
+        assert!(mesh.smooth);
+        for normal in &mesh.normals {
+            assert!((normal - Vector3::new(0.0, 0.0, 1.0)).norm() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn out_of_range_indices_are_an_error() {
+        let mut mesh = square();
+        mesh.indices.push([0, 2, 7]);
+        assert!(matches!(mesh.compute_normals(), Err(Error::InvalidScene(_))));
+        assert!(matches!(mesh.into_hittable_list(), Err(Error::InvalidScene(_))));
+    }
+}
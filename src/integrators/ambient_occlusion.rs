@@ -0,0 +1,63 @@
+// ambient occlusion: how much of the hemisphere above each visible point is open, ignoring materials and lights
+
+use crate::core::colour::Colour;
+use crate::core::ray::Ray;
+use crate::integrators::integrator::{Integrator, RenderContext};
+use crate::util::interval::Interval;
+use crate::util::rng::Rng;
+use crate::util::vector_math::{near_zero, random_unit_vector};
+
+/// Shades the first hit white where a cosine-distributed ray leaves without hitting anything within `distance`,
+/// and black where it is blocked, so averaging the samples gives the (cosine weighted) unoccluded fraction.
+/// Rays from the camera that miss everything show the background.
+pub struct AmbientOcclusion {
+    /// How far away geometry still occludes. None uses a tenth of the diagonal of the world's bounding box.
+    pub distance: Option<f32>,
+}
+
+impl AmbientOcclusion {
+    pub fn new() -> Self {
+        Self {
+            distance: None,
+        }
+    }
+
+    pub fn new_from(distance: f32) -> Self {
+        Self {
+            distance: Some(distance),
+        }
+    }
+}
+
+impl Default for AmbientOcclusion {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator for AmbientOcclusion {
+    fn name(&self) -> &'static str {
+        "ao"
+    }
+
+    fn ray_colour(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+        let Some(hit_rec) = ctx.world.hit(ray, &Interval::new(0.001, f32::INFINITY), rng) else {
+            return ctx.background.clone();
+        };
+
+        let distance = self.distance.unwrap_or_else(|| {
+            let bbox = ctx.world.bounding_box();
+            0.1 * (bbox.x.size().powi(2) + bbox.y.size().powi(2) + bbox.z.size().powi(2)).sqrt()
+        });
+
+        // the normal faces the incoming ray, so this picks a direction on the side it was seen from
+        let mut direction = hit_rec.normal + random_unit_vector(rng);
+        if near_zero(direction) {direction = hit_rec.normal}
+
+        let occlusion_ray = Ray::new_from(hit_rec.p, direction.normalize(), ray.time());
+        match ctx.world.hit(&occlusion_ray, &Interval::new(0.001, distance), rng) {
+            Some(_) => Colour::new(),
+            None => Colour::new_from(1.0, 1.0, 1.0),
+        }
+    }
+}
@@ -0,0 +1,521 @@
+// bidirectional path tracing: for every camera ray a second path is traced from a point on a light,
+// and every way of joining a prefix of one to a prefix of the other is weighted against the rest
+// with multiple importance sampling (following Veach's thesis and pbrt's implementation)
+
+use nalgebra::{Point3, Vector3};
+
+use crate::core::colour::Colour;
+use crate::core::ray::Ray;
+use crate::geometry::hittable::{HitRecord, Hittable};
+use crate::integrators::integrator::{russian_roulette, Integrator, RenderContext};
+use crate::util::interval::Interval;
+use crate::util::rng::Rng;
+use crate::util::vector_math::{near_zero, random_f32, random_unit_vector};
+
+/// Bidirectional path tracing. Paths are built from a camera subpath and a light subpath (started on a
+/// random point of one of the lights), joined in every possible way: the camera path hitting a light, a shadow
+/// ray to a new point on a light, a shadow ray between the two subpaths, or the light subpath seen by the camera,
+/// which lands on an arbitrary pixel and is splatted there. The power heuristic weighs these against each other.
+///
+/// Light paths reach where camera paths struggle to: caustics seen through glass, and light scattered inside
+/// volumes. Every emissive object needs to be in the scene's lights, as with next-event estimation.
+pub struct BDPT;
+
+#[derive(Clone, Copy, PartialEq)]
+enum VertexKind {
+    Camera,
+    Light,
+    Surface,
+}
+
+// a vertex of a camera or light subpath
+struct Vertex {
+    kind: VertexKind,
+    p: Point3<f32>,
+    // the normal of a surface, facing the side the path arrived from (outwards for the start of a light path);
+    // zero for the camera and for points inside volumes, where no cosine applies
+    n: Vector3<f32>,
+    // the hit, for lights and surfaces
+    rec: Option<HitRecord>,
+    // the ray that arrived at a surface
+    ray_in: Ray,
+    // the subpath's contribution up to this vertex over the density it was sampled with
+    beta: Colour,
+    // set when the path scattered off this vertex with a delta lobe (a mirror or glass), so it can't be joined to
+    delta: bool,
+    // the density, per unit area, of sampling this vertex from the one before it on its subpath,
+    // and from the one after it if the path were traced the other way
+    pdf_fwd: f32,
+    pdf_rev: f32,
+}
+
+impl Vertex {
+    fn camera(p: Point3<f32>) -> Self {
+        Self {
+            kind: VertexKind::Camera,
+            p,
+            n: Vector3::zeros(),
+            rec: None,
+            ray_in: Ray::new(),
+            beta: Colour::new_from(1.0, 1.0, 1.0),
+            delta: false,
+            pdf_fwd: 1.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    // a point picked on a light with density pdf_pos
+    fn light(rec: HitRecord, pdf_pos: f32) -> Self {
+        Self {
+            kind: VertexKind::Light,
+            p: rec.p,
+            n: rec.normal,
+            rec: Some(rec),
+            ray_in: Ray::new(),
+            beta: Colour::new_from(1.0 / pdf_pos, 1.0 / pdf_pos, 1.0 / pdf_pos),
+            delta: false,
+            pdf_fwd: pdf_pos,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn surface(rec: HitRecord, ray_in: Ray, beta: Colour) -> Self {
+        Self {
+            kind: VertexKind::Surface,
+            p: rec.p,
+            n: if rec.mat.is_volumetric() {Vector3::zeros()} else {rec.normal},
+            rec: Some(rec),
+            ray_in,
+            beta,
+            delta: false,
+            pdf_fwd: 0.0,
+            pdf_rev: 0.0,
+        }
+    }
+
+    fn on_surface(&self) -> bool {
+        self.n != Vector3::zeros()
+    }
+
+    fn is_light(&self) -> bool {
+        match (&self.rec, self.kind) {
+            (_, VertexKind::Light) => true,
+            (Some(rec), VertexKind::Surface) => rec.mat.is_emissive(),
+            _ => false,
+        }
+    }
+
+    // the light the vertex's surface emits, which is the same in every direction and from both sides
+    fn le(&self) -> Colour {
+        match &self.rec {
+            Some(rec) => rec.mat.emitted(rec.u, rec.v, rec.p),
+            None => Colour::new(),
+        }
+    }
+
+    // what the vertex scatters towards `next` (or emits, for the start of a light path), including the cosine at this end
+    fn f(&self, next: &Vertex) -> Colour {
+        let Some(rec) = &self.rec else {return Colour::new()};
+        let Some(direction) = (next.p - self.p).try_normalize(0.0) else {return Colour::new()};
+        match self.kind {
+            VertexKind::Light => Colour(self.le().0 * self.n.dot(&direction).abs()),
+            _ => rec.mat.eval(&self.ray_in, rec, &direction),
+        }
+    }
+
+    // turns a density per unit solid angle, for the direction from this vertex to `next`, into a density per unit area at `next`
+    fn convert_density(&self, pdf: f32, next: &Vertex) -> f32 {
+        let w = next.p - self.p;
+        let distance_squared = w.norm_squared();
+        if distance_squared <= 0.0 {return 0.0}
+        let mut pdf = pdf / distance_squared;
+        if next.on_surface() {
+            pdf *= next.n.dot(&w).abs() / distance_squared.sqrt();
+        }
+        return pdf;
+    }
+
+    // the density, per unit area, of sampling `next` from this vertex having arrived from `prev`
+    fn pdf(&self, prev: Option<&Vertex>, next: &Vertex, ctx: &RenderContext) -> f32 {
+        let direction = next.p - self.p;
+        let pdf = match (&self.rec, self.kind) {
+            (_, VertexKind::Camera) => ctx.camera.ray_pdf(&direction),
+            (_, VertexKind::Light) => return self.pdf_light(next),
+            (Some(rec), VertexKind::Surface) => {
+                let ray_in = match prev {
+                    Some(prev) => Ray::new_from(prev.p, self.p - prev.p, self.ray_in.time()),
+                    None => self.ray_in.clone(),
+                };
+                rec.mat.pdf(&ray_in, rec, &direction)
+            }
+            (None, VertexKind::Surface) => 0.0,
+        };
+        return self.convert_density(pdf, next);
+    }
+
+    // the density, per unit area, of a light path leaving this (emissive) vertex towards `next`
+    fn pdf_light(&self, next: &Vertex) -> f32 {
+        let Some(direction) = (next.p - self.p).try_normalize(0.0) else {return 0.0};
+        let pdf = self.n.dot(&direction).abs() / (2.0 * std::f32::consts::PI);
+        return self.convert_density(pdf, next);
+    }
+
+    // the density, per unit area, of a light path starting at this vertex
+    fn pdf_light_origin(&self, ctx: &RenderContext) -> f32 {
+        return ctx.lights.surface_pdf(&self.p);
+    }
+}
+
+// whether nothing lies between two vertices
+fn unoccluded(a: &Point3<f32>, b: &Point3<f32>, time: f32, ctx: &RenderContext, rng: &mut Rng) -> bool {
+    let w = b - a;
+    let distance = w.norm();
+    if distance <= 0.0 {return false}
+    let ray = Ray::new_from(*a, w / distance, time);
+    return ctx.world.hit(&ray, &Interval::new(0.001, distance - 0.001), rng).is_none();
+}
+
+impl BDPT {
+    // traces the camera subpath for `ray`, and returns the light from the background if it escapes the scene
+    fn camera_subpath(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng, path: &mut Vec<Vertex>) -> Colour {
+        path.push(Vertex::camera(ray.origin()));
+        let pdf = ctx.camera.ray_pdf(&ray.direction());
+        return self.random_walk(ray.clone(), Colour::new_from(1.0, 1.0, 1.0), pdf, ctx.max_depth, ctx, rng, path);
+    }
+
+    fn light_subpath(&self, time: f32, ctx: &RenderContext, rng: &mut Rng, path: &mut Vec<Vertex>) {
+        if ctx.max_depth == 0 {return}
+        let Some((rec, pdf_pos)) = ctx.lights.sample_surface(rng) else {return};
+        if pdf_pos.is_nan() || pdf_pos <= 0.0 {return}
+
+        // lights emit from both sides: a cosine distributed direction about the normal, on either side
+        let mut direction = rec.normal + random_unit_vector(rng);
+        if near_zero(direction) {direction = rec.normal}
+        direction = direction.normalize();
+        if random_f32(rng) < 0.5 {direction = -direction}
+        let cos_theta = rec.normal.dot(&direction).abs();
+        let pdf_dir = cos_theta / (2.0 * std::f32::consts::PI);
+
+        let le = rec.mat.emitted(rec.u, rec.v, rec.p);
+        let origin = rec.p;
+        path.push(Vertex::light(rec, pdf_pos));
+        if pdf_dir <= 0.0 || le.0 == Vector3::zeros() {return}
+
+        let beta = Colour(le.0 * (cos_theta / (pdf_pos * pdf_dir)));
+        // light paths escaping the scene carry nothing back to the camera
+        self.random_walk(Ray::new_from(origin, direction, time), beta, pdf_dir, ctx.max_depth - 1, ctx, rng, path);
+    }
+
+    // extends `path` by up to `max_vertices` vertices along `ray`, which left the last vertex in a direction sampled with
+    // density `pdf` (per unit solid angle). Returns the background light times the throughput if the path escapes.
+    #[allow(clippy::too_many_arguments)]
+    fn random_walk(&self, mut ray: Ray, mut beta: Colour, mut pdf: f32, max_vertices: u32, ctx: &RenderContext, rng: &mut Rng, path: &mut Vec<Vertex>) -> Colour {
+        for bounce in 0..max_vertices {
+            let Some(rec) = ctx.world.hit(&ray, &Interval::new(0.001, f32::INFINITY), rng) else {
+                return Colour(beta.0.component_mul(&ctx.background.0));
+            };
+
+            let last = bounce + 1 == max_vertices;
+            let sample = if last {None} else {rec.mat.sample(&ray, &rec, rng)};
+
+            let prev = path.len() - 1;
+            let mut vertex = Vertex::surface(rec, ray.clone(), beta.clone());
+            vertex.pdf_fwd = path[prev].convert_density(pdf, &vertex);
+
+            let Some(sample) = sample else {
+                path.push(vertex);
+                break;
+            };
+
+            // delta lobes have no density to speak of either way; they are flagged instead
+            vertex.delta = sample.is_delta;
+            let pdf_rev = match (&vertex.rec, sample.is_delta) {
+                (Some(rec), false) => {
+                    let reversed = Ray::new_from(sample.ray.at(1.0), -sample.ray.direction(), ray.time());
+                    rec.mat.pdf(&reversed, rec, &-ray.direction())
+                }
+                _ => 0.0,
+            };
+            path[prev].pdf_rev = vertex.convert_density(pdf_rev, &path[prev]);
+            path.push(vertex);
+
+            pdf = if sample.is_delta {0.0} else {sample.pdf};
+            beta.0.component_mul_assign(&sample.attenuation.0);
+            ray = sample.ray;
+
+            if bounce + 1 >= ctx.russian_roulette_depth && !russian_roulette(&mut beta, rng) {break}
+        }
+        return Colour::new();
+    }
+
+    // the MIS weighted contribution of the path made of the first s vertices of the light subpath and the first t of
+    // the camera subpath, and the pixel it should be splatted to if it was joined straight to the camera (t = 1)
+    #[allow(clippy::too_many_arguments)]
+    fn connect(&self, light: &[Vertex], camera: &[Vertex], s: usize, t: usize, time: f32, ctx: &RenderContext, rng: &mut Rng) -> (Colour, Option<(usize, usize)>) {
+        let none = (Colour::new(), None);
+
+        if s == 0 {
+            // the camera subpath found a light by itself
+            let pt = &camera[t-1];
+            if !pt.is_light() {return none}
+            let contribution = Colour(pt.beta.0.component_mul(&pt.le().0));
+            if contribution.0 == Vector3::zeros() {return none}
+            let weight = self.mis_weight(light, camera, None, pt, s, t, ctx);
+            return (Colour(contribution.0 * weight), None);
+        }
+
+        if t == 1 {
+            // the light subpath seen from a new point on the lens
+            let qs = &light[s-1];
+            if qs.delta {return none}
+            let lens_point = ctx.camera.sample_lens(rng);
+            let Some((i, j, importance)) = ctx.camera.importance(&lens_point, &qs.p) else {return none};
+            let pt = Vertex::camera(lens_point);
+
+            let distance_squared = (qs.p - pt.p).norm_squared();
+            let contribution = Colour(qs.beta.0.component_mul(&qs.f(&pt).0) * (importance / distance_squared));
+            if contribution.0 == Vector3::zeros() || !unoccluded(&qs.p, &pt.p, time, ctx, rng) {return none}
+            let weight = self.mis_weight(light, camera, Some(qs), &pt, s, t, ctx);
+            return (Colour(contribution.0 * weight), Some((i, j)));
+        }
+
+        let pt = &camera[t-1];
+        if pt.delta {return none}
+
+        if s == 1 {
+            // a shadow ray to a new point on a light
+            let Some((rec, pdf_pos)) = ctx.lights.sample_surface(rng) else {return none};
+            if pdf_pos.is_nan() || pdf_pos <= 0.0 {return none}
+            let qs = Vertex::light(rec, pdf_pos);
+            return self.join(light, camera, &qs, pt, s, t, time, ctx, rng);
+        }
+
+        let qs = &light[s-1];
+        if qs.delta {return none}
+        return self.join(light, camera, qs, pt, s, t, time, ctx, rng);
+    }
+
+    // the MIS weighted contribution of a shadow ray joining light vertex qs to camera vertex pt
+    #[allow(clippy::too_many_arguments)]
+    fn join(&self, light: &[Vertex], camera: &[Vertex], qs: &Vertex, pt: &Vertex, s: usize, t: usize, time: f32, ctx: &RenderContext, rng: &mut Rng) -> (Colour, Option<(usize, usize)>) {
+        let distance_squared = (qs.p - pt.p).norm_squared();
+        if distance_squared <= 0.0 {return (Colour::new(), None)}
+
+        let throughput = qs.beta.0.component_mul(&pt.beta.0);
+        let scattering = qs.f(pt).0.component_mul(&pt.f(qs).0);
+        let contribution = throughput.component_mul(&scattering) / distance_squared;
+        if contribution == Vector3::zeros() || !unoccluded(&pt.p, &qs.p, time, ctx, rng) {return (Colour::new(), None)}
+
+        let weight = self.mis_weight(light, camera, Some(qs), pt, s, t, ctx);
+        return (Colour(contribution * weight), None);
+    }
+
+    // the power heuristic weight of the (s, t) strategy against every other way the same path could have been sampled,
+    // where qs and pt are the vertices being joined (which may have been sampled just for this, rather than taken from the subpaths)
+    #[allow(clippy::too_many_arguments)]
+    fn mis_weight(&self, light: &[Vertex], camera: &[Vertex], qs: Option<&Vertex>, pt: &Vertex, s: usize, t: usize, ctx: &RenderContext) -> f32 {
+        // a light missing from the scene's lights can only be found by the camera subpath
+        if s == 0 && pt.pdf_light_origin(ctx) <= 0.0 {return 1.0}
+
+        // (pdf_fwd, pdf_rev, delta) for the vertices of the path, updated for the join
+        let mut light_pdfs = light[..s].iter().map(|v| (v.pdf_fwd, v.pdf_rev, v.delta)).collect::<Vec<_>>();
+        let mut camera_pdfs = camera[..t].iter().map(|v| (v.pdf_fwd, v.pdf_rev, v.delta)).collect::<Vec<_>>();
+        camera_pdfs[t-1] = (pt.pdf_fwd, pt.pdf_rev, false);
+        if let Some(qs) = qs {
+            light_pdfs[s-1] = (qs.pdf_fwd, qs.pdf_rev, false);
+        }
+
+        let pt_minus = if t > 1 {Some(&camera[t-2])} else {None};
+        let qs_minus = if s > 1 {Some(&light[s-2])} else {None};
+
+        // the densities of sampling the vertices around the join from the other side
+        camera_pdfs[t-1].1 = match qs {
+            Some(qs) => qs.pdf(qs_minus, pt, ctx),
+            None => pt.pdf_light_origin(ctx),
+        };
+        if let Some(pt_minus) = pt_minus {
+            camera_pdfs[t-2].1 = match qs {
+                Some(qs) => pt.pdf(Some(qs), pt_minus, ctx),
+                None => pt.pdf_light(pt_minus),
+            };
+        }
+        if let Some(qs) = qs {
+            light_pdfs[s-1].1 = pt.pdf(pt_minus, qs, ctx);
+            if let Some(qs_minus) = qs_minus {
+                light_pdfs[s-2].1 = qs.pdf(Some(pt), qs_minus, ctx);
+            }
+        }
+
+        // delta vertices have no density either way, and the ratio of the two is taken as 1
+        let remap = |pdf: f32| if pdf != 0.0 {pdf} else {1.0};
+
+        // move the join towards the camera, one vertex at a time
+        let mut sum_ri = 0.0;
+        let mut ri = 1.0;
+        for i in (1..t).rev() {
+            ri *= remap(camera_pdfs[i].1) / remap(camera_pdfs[i].0);
+            // joining at i = 1 is a light path seen by the camera, which not every projection can do
+            let joinable = i > 1 || ctx.camera.can_connect_to_lens();
+            if joinable && !camera_pdfs[i].2 && !camera_pdfs[i-1].2 {sum_ri += ri * ri}
+        }
+
+        // and towards the light
+        ri = 1.0;
+        for i in (0..s).rev() {
+            ri *= remap(light_pdfs[i].1) / remap(light_pdfs[i].0);
+            let delta_before = i > 0 && light_pdfs[i-1].2;
+            if !light_pdfs[i].2 && !delta_before {sum_ri += ri * ri}
+        }
+
+        return 1.0 / (1.0 + sum_ri);
+    }
+}
+
+impl Integrator for BDPT {
+    fn name(&self) -> &'static str {
+        "bdpt"
+    }
+
+    fn ray_colour(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+        let mut camera_path = Vec::with_capacity(ctx.max_depth as usize + 1);
+        let mut light_path = Vec::with_capacity(ctx.max_depth as usize);
+
+        let mut colour = self.camera_subpath(ray, ctx, rng, &mut camera_path).0;
+        self.light_subpath(ray.time(), ctx, rng, &mut light_path);
+
+        for t in 1..=camera_path.len() {
+            for s in 0..=light_path.len() {
+                // s + t - 1 is the number of vertices after the camera, which max_depth limits as for the other integrators
+                if (s == 0 && t == 1) || s + t - 1 > ctx.max_depth as usize {continue}
+
+                let (contribution, pixel) = self.connect(&light_path, &camera_path, s, t, ray.time(), ctx, rng);
+                match pixel {
+                    Some((i, j)) => ctx.splats.add(i, j, &contribution),
+                    None => colour += contribution.0,
+                }
+            }
+        }
+
+        return Colour(colour);
+    }
+
+    fn splats(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::camera::Camera;
+    use crate::core::film::SplatBuffer;
+    use crate::geometry::hittable_list::HittableList;
+    use crate::geometry::quad::Quad;
+    use crate::integrators::integrator::integrator_from_name;
+    use crate::materials::diffuse_light::DiffuseLight;
+    use crate::materials::lambertian::Lambertian;
+    use crate::scenes;
+
+    // the mean of every pixel of a small render of the Cornell box, splats included
+    fn cornell_box_mean(integrator: &str) -> f64 {
+        let mut scene = scenes::cornell_box().unwrap();
+        scene.camera.image_width = 12.0;
+        scene.camera.samples_per_pixel = 128;
+        scene.camera.max_depth = 4;
+        scene.camera.integrator = integrator_from_name(integrator).unwrap();
+        let pixels = scene.render().unwrap().resolve();
+        return pixels.iter().map(|colour| colour.0.sum() as f64).sum::<f64>() / pixels.len() as f64;
+    }
+
+    #[test]
+    fn agrees_with_light_sampling() {
+        let (bdpt, nee) = (cornell_box_mean("bdpt"), cornell_box_mean("nee"));
+        assert!((bdpt - nee).abs() < 0.03 * nee, "{} with BDPT, {} with NEE", bdpt, nee);
+    }
+
+    // what a camera ray from `from` towards `to` hits, which should be `to`
+    fn hit_at(world: &HittableList, from: &Point3<f32>, to: &Point3<f32>) -> (HitRecord, Ray) {
+        let ray = Ray::new_from(*from, to - from, 0.0);
+        let rec = world.hit(&ray, &Interval::new(0.001, f32::INFINITY), &mut Rng::new(1)).unwrap();
+        assert!((rec.p - to).norm() < 1e-4, "{:?} instead of {:?}", rec.p, to);
+        return (rec, ray);
+    }
+
+    // a path from the camera, off a floor and a wall, to a light in the ceiling, written as every (s, t) split into a
+    // light and a camera subpath: the weights of the strategies that can make it must share it out completely
+    #[test]
+    fn mis_weights_sum_to_one() {
+        let light_quad = Quad::new(Point3::new(-0.5, 2.0, -0.5), Vector3::new(1.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 1.0),
+                                   Box::new(DiffuseLight::new_from(Colour::new_from(4.0, 4.0, 4.0))));
+        let white = || Box::new(Lambertian::new_from(Colour::new_from(0.7, 0.7, 0.7)));
+        let mut world = HittableList::new();
+        world.add(Box::new(light_quad.clone()));
+        world.add(Box::new(Quad::new(Point3::new(-2.0, 0.0, -2.0), Vector3::new(4.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 4.0), white())));
+        world.add(Box::new(Quad::new(Point3::new(1.0, 0.0, -2.0), Vector3::new(0.0, 2.0, 0.0), Vector3::new(0.0, 0.0, 4.0), white())));
+        let mut lights = HittableList::new();
+        lights.add(Box::new(light_quad));
+
+        let mut camera = Camera::new();
+        camera.image_width = 16.0;
+        camera.lookfrom = Point3::new(-1.5, 1.0, 0.0);
+        camera.lookat = Point3::new(0.0, 0.0, 0.0);
+        camera.initialise().unwrap();
+        let splats = SplatBuffer::new(1, 1);
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: Colour::new(),
+            max_depth: 8,
+            russian_roulette_depth: 8,
+            camera: &camera,
+            splats: &splats,
+        };
+
+        let c = camera.sample_lens(&mut Rng::new(1));
+        let (a, b, l) = (Point3::new(0.0, 0.0, 0.0), Point3::new(1.0, 1.0, 0.2), Point3::new(0.0, 2.0, 0.1));
+        let beta = || Colour::new_from(1.0, 1.0, 1.0);
+
+        // the camera subpath c, a, b, l, with the densities of sampling each vertex from either side
+        let camera_vertex = Vertex::camera(c);
+        let (rec, ray) = hit_at(&world, &c, &a);
+        let mut camera_a = Vertex::surface(rec, ray, beta());
+        let (rec, ray) = hit_at(&world, &a, &b);
+        let mut camera_b = Vertex::surface(rec, ray, beta());
+        let (rec, ray) = hit_at(&world, &b, &l);
+        let mut camera_l = Vertex::surface(rec, ray, beta());
+
+        // and the light subpath l, b, a
+        let (rec, _) = hit_at(&world, &b, &l);
+        let mut light_l = Vertex::light(rec, lights.surface_pdf(&l));
+        let (rec, ray) = hit_at(&world, &l, &b);
+        let mut light_b = Vertex::surface(rec, ray, beta());
+        let (rec, ray) = hit_at(&world, &b, &a);
+        let mut light_a = Vertex::surface(rec, ray, beta());
+
+        camera_a.pdf_fwd = camera_vertex.convert_density(camera.ray_pdf(&(a - c)), &camera_a);
+        camera_b.pdf_fwd = camera_a.pdf(Some(&camera_vertex), &camera_b, &ctx);
+        camera_l.pdf_fwd = camera_b.pdf(Some(&camera_a), &camera_l, &ctx);
+        light_b.pdf_fwd = light_l.pdf_light(&light_b);
+        light_a.pdf_fwd = light_b.pdf(Some(&light_l), &light_a, &ctx);
+        camera_a.pdf_rev = light_a.pdf_fwd;
+        camera_b.pdf_rev = light_b.pdf_fwd;
+        camera_l.pdf_rev = light_l.pdf_fwd;
+        light_l.pdf_rev = camera_l.pdf_fwd;
+        light_b.pdf_rev = camera_b.pdf_fwd;
+        light_a.pdf_rev = camera_a.pdf_fwd;
+
+        let camera_path = [camera_vertex, camera_a, camera_b, camera_l];
+        let light_path = [light_l, light_b, light_a];
+        let lens = Vertex::camera(c);
+        let weights = [
+            BDPT.mis_weight(&light_path, &camera_path, None, &camera_path[3], 0, 4, &ctx),
+            BDPT.mis_weight(&light_path, &camera_path, Some(&light_path[0]), &camera_path[2], 1, 3, &ctx),
+            BDPT.mis_weight(&light_path, &camera_path, Some(&light_path[1]), &camera_path[1], 2, 2, &ctx),
+            BDPT.mis_weight(&light_path, &camera_path, Some(&light_path[2]), &lens, 3, 1, &ctx),
+        ];
+        for weight in weights {
+            assert!(weight > 0.0 && weight < 1.0, "{:?}", weights);
+        }
+        assert!((weights.iter().sum::<f32>() - 1.0).abs() < 1e-4, "{:?}", weights);
+    }
+}
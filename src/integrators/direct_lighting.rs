@@ -0,0 +1,54 @@
+// direct lighting only: light that reaches a surface straight from a light (or the background), with no indirect bounces
+
+use nalgebra::Vector3;
+
+use crate::core::colour::Colour;
+use crate::core::ray::Ray;
+use crate::integrators::integrator::{sample_lights, weighted_emission, Integrator, RenderContext};
+use crate::util::interval::Interval;
+use crate::util::rng::Rng;
+
+/// Direct lighting at the first non-delta hit, from both a light sample and a BSDF sample weighted with
+/// multiple importance sampling. Mirrors and glass are followed (up to the maximum depth) so lights can still be
+/// seen in them, but light bouncing off another diffuse surface is left out.
+pub struct DirectLighting;
+
+impl Integrator for DirectLighting {
+    fn name(&self) -> &'static str {
+        "direct"
+    }
+
+    fn ray_colour(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+        let mut colour = Vector3::zeros();
+        let mut throughput = Colour::new_from(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+        // as for weighted_emission; once it is set the ray was a BSDF sample from a diffuse hit,
+        // and only what it finds directly counts
+        let mut bsdf_pdf = None;
+
+        for bounce in 0..ctx.max_depth {
+            let Some(hit_rec) = ctx.world.hit(&ray, &Interval::new(0.001, f32::INFINITY), rng) else {
+                colour += throughput.0.component_mul(&ctx.background.0);
+                break;
+            };
+
+            let emitted = weighted_emission(&ray, &hit_rec, bsdf_pdf, ctx, rng);
+            colour += throughput.0.component_mul(&emitted.0);
+            if bsdf_pdf.is_some() {break}
+
+            let Some(sample) = hit_rec.mat.sample(&ray, &hit_rec, rng) else {break};
+
+            let last_bounce = bounce + 1 == ctx.max_depth;
+            if !last_bounce && !sample.is_delta && !ctx.lights.objects.is_empty() {
+                let direct = sample_lights(&ray, &hit_rec, ctx, rng);
+                colour += throughput.0.component_mul(&direct.0);
+            }
+
+            throughput.0.component_mul_assign(&sample.attenuation.0);
+            bsdf_pdf = if sample.is_delta {None} else {Some(sample.pdf)};
+            ray = sample.ray;
+        }
+
+        return Colour(colour);
+    }
+}
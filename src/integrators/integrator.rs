@@ -0,0 +1,143 @@
+// integrators turn a camera ray into the light arriving along it; the camera picks the rays,
+// the integrator decides which paths to follow and how to weight them
+
+use nalgebra::Vector3;
+
+use crate::core::camera::Camera;
+use crate::core::colour::Colour;
+use crate::core::film::SplatBuffer;
+use crate::core::ray::Ray;
+use crate::error::{Error, Result};
+use crate::geometry::hittable::{HitRecord, Hittable};
+use crate::geometry::hittable_list::HittableList;
+use crate::integrators::ambient_occlusion::AmbientOcclusion;
+use crate::integrators::bdpt::BDPT;
+use crate::integrators::direct_lighting::DirectLighting;
+use crate::integrators::nee_path_tracer::NEEPathTracer;
+use crate::integrators::path_tracer::PathTracer;
+use crate::integrators::photon_mapper::PhotonMapper;
+use crate::util::interval::Interval;
+use crate::util::rng::Rng;
+use crate::util::vector_math::random_f32;
+
+/// What an integrator needs to know about the scene it is rendering.
+pub struct RenderContext<'a> {
+    pub world: &'a dyn Hittable,
+    /// The emissive objects of the world, for integrators that sample lights directly.
+    pub lights: &'a HittableList,
+    /// The colour of rays that hit nothing.
+    pub background: Colour,
+    /// The most bounces a path may take.
+    pub max_depth: u32,
+    /// The number of bounces after which paths are ended early by Russian roulette.
+    pub russian_roulette_depth: u32,
+    /// The camera the rays come from, for integrators that trace paths back to it.
+    pub camera: &'a Camera,
+    /// Where contributions to pixels other than the one being rendered go.
+    pub splats: &'a SplatBuffer,
+}
+
+pub trait Integrator: Send + Sync {
+    /// The name used on the command line and in scene files.
+    fn name(&self) -> &'static str;
+
+    /// An estimate of the light arriving along `ray`.
+    fn ray_colour(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng) -> Colour;
+
+    /// Whether `ray_colour` also traces a light path each time it is called and splats what it finds
+    /// into `RenderContext::splats`, so the camera adds those to the film.
+    fn splats(&self) -> bool {
+        false
+    }
+
+    /// How many passes over the image a render takes, with the samples per pixel shared out between them.
+    fn passes(&self, _samples_per_pixel: u32) -> u32 {
+        1
+    }
+
+    /// Called before each pass, for integrators that work something out from the scene first (like a photon map).
+    fn start_pass(&self, _ctx: &RenderContext, _pass: u32) {}
+}
+
+/// Looks an integrator up by the name used on the command line and in scene files.
+pub fn integrator_from_name(name: &str) -> Result<Box<dyn Integrator>> {
+    match name {
+        "path" => Ok(Box::new(PathTracer)),
+        "nee" => Ok(Box::new(NEEPathTracer)),
+        "ao" => Ok(Box::new(AmbientOcclusion::new())),
+        "direct" => Ok(Box::new(DirectLighting)),
+        "bdpt" => Ok(Box::new(BDPT)),
+        "photon" => Ok(Box::new(PhotonMapper::new())),
+        _ => Err(Error::InvalidSettings(format!("unknown integrator '{}' (expected path, nee, ao, direct, bdpt or photon)", name))),
+    }
+}
+
+/// Next-event estimation: the light arriving at a hit straight from a point picked on one of the lights,
+/// weighted against finding the same light with a bounce (multiple importance sampling).
+pub fn sample_lights(ray: &Ray, rec: &HitRecord, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+    // normalised, so the shadow ray's t_min is the same small distance as for any other ray
+    let Some(direction) = ctx.lights.random(&rec.p, rng).try_normalize(0.0) else {return Colour::new()};
+    let light_pdf = ctx.lights.pdf_value(&rec.p, &direction, rng);
+    if light_pdf.is_nan() || light_pdf <= 0.0 {return Colour::new()}
+
+    let bsdf = rec.mat.eval(ray, rec, &direction);
+    if bsdf.0 == Vector3::zeros() {return Colour::new()}
+
+    // the shadow ray picks up whatever it hits first, which is only bright if it's the light
+    let shadow_ray = Ray::new_from(rec.p, direction, ray.time());
+    let Some(light_rec) = ctx.world.hit(&shadow_ray, &Interval::new(0.001, f32::INFINITY), rng) else {return Colour::new()};
+    let emitted = light_rec.mat.emitted(light_rec.u, light_rec.v, light_rec.p);
+
+    let weight = power_heuristic(light_pdf, rec.mat.pdf(ray, rec, &direction));
+    return Colour(bsdf.0.component_mul(&emitted.0) * (weight / light_pdf));
+}
+
+/// The light `rec` emits back along `ray`. `bsdf_pdf` is the density the bounce that sent the ray was sampled with,
+/// or None if it came from the camera or a delta lobe (and so couldn't have been found by sampling the lights instead);
+/// otherwise the emission is weighted against having sampled the light directly from where the ray left.
+pub fn weighted_emission(ray: &Ray, rec: &HitRecord, bsdf_pdf: Option<f32>, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+    let mut emitted = rec.mat.emitted(rec.u, rec.v, rec.p);
+    if let Some(bsdf_pdf) = bsdf_pdf {
+        let light_pdf = ctx.lights.pdf_value(&ray.origin(), &ray.direction(), rng);
+        emitted.0 *= power_heuristic(bsdf_pdf, light_pdf);
+    }
+    return emitted;
+}
+
+/// Russian roulette: randomly ends a path, with a higher chance the less light it can still carry, and scales up
+/// `throughput` to make up for the paths that were ended. Returns false if this path should stop.
+pub fn russian_roulette(throughput: &mut Colour, rng: &mut Rng) -> bool {
+    // paths that can still carry all of the light they started with always go on
+    let survival = throughput.0.max();
+    if survival >= 1.0 {return true}
+    if survival <= 0.0 || random_f32(rng) >= survival {return false}
+    throughput.0 /= survival;
+    return true;
+}
+
+/// Veach's power heuristic (with beta = 2) for weighting a sample taken with density `pdf` against another strategy's `other_pdf`.
+pub fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let (a, b) = (pdf * pdf, other_pdf * other_pdf);
+    // the other strategy can't produce this sample at all
+    if b <= 0.0 {return 1.0}
+    return a / (a + b);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_round_trip() {
+        for name in ["path", "nee", "ao", "direct", "bdpt", "photon"] {
+            assert_eq!(integrator_from_name(name).unwrap().name(), name);
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_invalid_settings() {
+        for name in ["", "Path", "mlt", "path "] {
+            assert!(matches!(integrator_from_name(name), Err(Error::InvalidSettings(_))), "'{}' was accepted", name);
+        }
+    }
+}
@@ -0,0 +1,8 @@
+pub mod integrator;
+pub mod ambient_occlusion;
+pub mod bdpt;
+pub mod direct_lighting;
+pub mod nee_path_tracer;
+pub mod path_tracer;
+pub mod photon_map;
+pub mod photon_mapper;
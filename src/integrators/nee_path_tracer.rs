@@ -0,0 +1,118 @@
+// path tracing with next-event estimation: every diffuse hit also traces a shadow ray to a point on a light
+
+use nalgebra::Vector3;
+
+use crate::core::colour::Colour;
+use crate::core::ray::Ray;
+use crate::integrators::integrator::{russian_roulette, sample_lights, weighted_emission, Integrator, RenderContext};
+use crate::util::interval::Interval;
+use crate::util::rng::Rng;
+
+/// Path tracing that samples the lights directly at every non-delta hit and weights those samples against
+/// finding the lights by bouncing, with multiple importance sampling. With no lights it is the naive path tracer.
+pub struct NEEPathTracer;
+
+impl Integrator for NEEPathTracer {
+    fn name(&self) -> &'static str {
+        "nee"
+    }
+
+    fn ray_colour(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+        let mut colour = Vector3::zeros();
+        // what the rest of the path's light is multiplied by on its way back to the camera
+        let mut throughput = Colour::new_from(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+        // as for weighted_emission: the camera ray can't have been found by sampling a light
+        let mut bsdf_pdf = None;
+
+        for bounce in 0..ctx.max_depth {
+            let Some(hit_rec) = ctx.world.hit(&ray, &Interval::new(0.001, f32::INFINITY), rng) else {
+                colour += throughput.0.component_mul(&ctx.background.0);
+                break;
+            };
+
+            let emitted = weighted_emission(&ray, &hit_rec, bsdf_pdf, ctx, rng);
+            colour += throughput.0.component_mul(&emitted.0);
+
+            let Some(sample) = hit_rec.mat.sample(&ray, &hit_rec, rng) else {break};
+
+            // lights can only be sampled from non-delta materials, mirrors and glass have to find them by bouncing.
+            // On the last bounce there's no room left for the light's own segment.
+            let last_bounce = bounce + 1 == ctx.max_depth;
+            if !last_bounce && !sample.is_delta && !ctx.lights.objects.is_empty() {
+                let direct = sample_lights(&ray, &hit_rec, ctx, rng);
+                colour += throughput.0.component_mul(&direct.0);
+            }
+
+            throughput.0.component_mul_assign(&sample.attenuation.0);
+            bsdf_pdf = if sample.is_delta {None} else {Some(sample.pdf)};
+            ray = sample.ray;
+
+            if bounce + 1 >= ctx.russian_roulette_depth && !russian_roulette(&mut throughput, rng) {break}
+        }
+
+        return Colour(colour);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+    use crate::core::camera::Camera;
+    use crate::core::film::SplatBuffer;
+    use crate::geometry::hittable_list::HittableList;
+    use crate::geometry::quad::Quad;
+    use crate::geometry::sphere::Sphere;
+    use crate::integrators::path_tracer::PathTracer;
+    use crate::materials::diffuse_light::DiffuseLight;
+    use crate::materials::lambertian::Lambertian;
+    use crate::materials::metal::Metal;
+
+    // the mean and standard error of `integrator`'s estimate for a ray onto a grey floor, next to a brushed metal
+    // sphere, under a square light
+    fn estimate(integrator: &dyn Integrator) -> (f64, f64) {
+        let mut world = HittableList::new();
+        let light = Quad::new(Point3::new(-1.0, 2.0, -1.0), Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 2.0), Box::new(DiffuseLight::new_from(Colour::new_from(4.0, 4.0, 4.0))));
+        world.add(Box::new(light.clone()));
+        world.add(Box::new(Quad::new(Point3::new(-4.0, 0.0, -4.0), Vector3::new(8.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 8.0), Box::new(Lambertian::new_from(Colour::new_from(0.7, 0.7, 0.7))))));
+        world.add(Box::new(Sphere::new(Point3::new(0.8, 0.5, 0.0), 0.5, Box::new(Metal::new_from(Colour::new_from(0.9, 0.9, 0.9), 0.3)))));
+        let mut lights = HittableList::new();
+        lights.add(Box::new(light));
+        let camera = Camera::new();
+        let splats = SplatBuffer::new(1, 1);
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: Colour::new(),
+            max_depth: 16,
+            russian_roulette_depth: 3,
+            camera: &camera,
+            splats: &splats,
+        };
+
+        let ray = Ray::new_from(Point3::new(-2.0, 1.5, 0.0), Vector3::new(1.5, -1.5, 0.1), 0.0);
+        let mut rng = Rng::new(42);
+        let n = 20_000;
+        let (mut sum, mut sum_squared) = (0.0, 0.0);
+        for _ in 0..n {
+            let value = integrator.ray_colour(&ray, &ctx, &mut rng).0.x as f64;
+            sum += value;
+            sum_squared += value * value;
+        }
+        let mean = sum / n as f64;
+        let variance = sum_squared / n as f64 - mean * mean;
+        return (mean, (variance / n as f64).sqrt());
+    }
+
+    #[test]
+    fn light_sampling_keeps_the_mean() {
+        let (path, path_error) = estimate(&PathTracer);
+        let (nee, nee_error) = estimate(&NEEPathTracer);
+        assert!(path > 0.1, "{}", path);
+        assert!((nee - path).abs() < 4.0 * (nee_error * nee_error + path_error * path_error).sqrt(),
+                "{} ± {} with light sampling, {} ± {} without", nee, nee_error, path, path_error);
+        // sampling the light is the point: it should be far less noisy
+        assert!(nee_error < path_error, "{} vs {}", nee_error, path_error);
+    }
+}
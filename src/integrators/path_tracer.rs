@@ -0,0 +1,99 @@
+// the path tracer from the books: follow one scattered ray per bounce and only find lights by hitting them
+
+use nalgebra::Vector3;
+
+use crate::core::colour::Colour;
+use crate::core::ray::Ray;
+use crate::integrators::integrator::{russian_roulette, Integrator, RenderContext};
+use crate::util::interval::Interval;
+use crate::util::rng::Rng;
+
+/// Naive path tracing, with no light sampling. Unbiased but slow to find small lights;
+/// mostly useful as a reference for the other integrators.
+pub struct PathTracer;
+
+impl Integrator for PathTracer {
+    fn name(&self) -> &'static str {
+        "path"
+    }
+
+    fn ray_colour(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+        let mut colour = Vector3::zeros();
+        // what the rest of the path's light is multiplied by on its way back to the camera
+        let mut throughput = Colour::new_from(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+
+        for bounce in 0..ctx.max_depth {
+            let Some(hit_rec) = ctx.world.hit(&ray, &Interval::new(0.001, f32::INFINITY), rng) else {
+                colour += throughput.0.component_mul(&ctx.background.0);
+                break;
+            };
+
+            let emitted = hit_rec.mat.emitted(hit_rec.u, hit_rec.v, hit_rec.p);
+            colour += throughput.0.component_mul(&emitted.0);
+
+            let Some(sample) = hit_rec.mat.sample(&ray, &hit_rec, rng) else {break};
+            throughput.0.component_mul_assign(&sample.attenuation.0);
+            ray = sample.ray;
+
+            if bounce + 1 >= ctx.russian_roulette_depth && !russian_roulette(&mut throughput, rng) {break}
+        }
+
+        return Colour(colour);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+    use crate::core::camera::Camera;
+    use crate::core::film::SplatBuffer;
+    use crate::geometry::hittable_list::HittableList;
+    use crate::geometry::quad::Quad;
+    use crate::materials::lambertian::Lambertian;
+
+    // the mean and standard error of the path tracer's estimate for a ray between a grey floor and ceiling,
+    // which light from a white sky mostly reaches after bouncing between them a few times
+    fn estimate(russian_roulette_depth: u32) -> (f64, f64) {
+        let mut world = HittableList::new();
+        let grey = Colour::new_from(0.8, 0.8, 0.8);
+        world.add(Box::new(Quad::new(Point3::new(-4.0, 0.0, -4.0), Vector3::new(8.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 8.0), Box::new(Lambertian::new_from(grey.clone())))));
+        world.add(Box::new(Quad::new(Point3::new(-4.0, 1.0, -4.0), Vector3::new(8.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 8.0), Box::new(Lambertian::new_from(grey)))));
+        let lights = HittableList::new();
+        let camera = Camera::new();
+        let splats = SplatBuffer::new(1, 1);
+        let ctx = RenderContext {
+            world: &world,
+            lights: &lights,
+            background: Colour::new_from(1.0, 1.0, 1.0),
+            max_depth: 64,
+            russian_roulette_depth,
+            camera: &camera,
+            splats: &splats,
+        };
+
+        let ray = Ray::new_from(Point3::new(0.0, 0.5, 0.0), Vector3::new(1.0, -0.3, 0.2), 0.0);
+        let mut rng = Rng::new(42);
+        let n = 10_000;
+        let (mut sum, mut sum_squared) = (0.0, 0.0);
+        for _ in 0..n {
+            let value = PathTracer.ray_colour(&ray, &ctx, &mut rng).0.x as f64;
+            sum += value;
+            sum_squared += value * value;
+        }
+        let mean = sum / n as f64;
+        let variance = sum_squared / n as f64 - mean * mean;
+        return (mean, (variance / n as f64).sqrt());
+    }
+
+    #[test]
+    fn russian_roulette_keeps_the_mean() {
+        let (without, without_error) = estimate(64);
+        let (with, with_error) = estimate(1);
+        // some of the sky reaches the ray, most of it after a few bounces, when roulette has started ending paths
+        assert!(without > 0.05 && without < 0.8, "{}", without);
+        assert!((with - without).abs() < 4.0 * (with_error * with_error + without_error * without_error).sqrt(),
+                "{} ± {} with Russian roulette, {} ± {} without", with, with_error, without, without_error);
+    }
+}
@@ -0,0 +1,149 @@
+// a kd-tree of photons: where light from the lights landed on diffuse surfaces, for photon mapping to look up
+
+use nalgebra::{Point3, Vector3};
+
+use crate::core::colour::Colour;
+
+/// Light that arrived at a point on a surface, after at least one bounce on its way from a light.
+#[derive(Clone, Debug)]
+pub struct Photon {
+    pub p: Point3<f32>,
+    /// The direction the photon arrived from (pointing away from the surface).
+    pub incoming: Vector3<f32>,
+    /// The surface normal on the side the photon arrived on, so photons on the back of a thin wall aren't gathered from its front.
+    pub normal: Vector3<f32>,
+    /// The photon's flux.
+    pub power: Colour,
+}
+
+/// A balanced kd-tree over the photons, stored implicitly: each range of the array is split at its middle
+/// photon, on the axis the range is widest along, with the photons below the split before it and those above after.
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    // the axis the photon at the same index splits its range on
+    axes: Vec<u8>,
+}
+
+impl PhotonMap {
+    pub fn new(mut photons: Vec<Photon>) -> Self {
+        let mut axes = vec![0; photons.len()];
+        build(&mut photons, &mut axes);
+        return Self {photons, axes};
+    }
+
+    /// An empty map, for before the first photons have been traced.
+    pub fn empty() -> Self {
+        return Self::new(Vec::new());
+    }
+
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// Calls `f` with every photon within `radius` of `point`.
+    pub fn for_each_near(&self, point: &Point3<f32>, radius: f32, mut f: impl FnMut(&Photon)) {
+        self.search(0, self.photons.len(), point, radius * radius, &mut f);
+    }
+
+    fn search(&self, lo: usize, hi: usize, point: &Point3<f32>, radius_squared: f32, f: &mut impl FnMut(&Photon)) {
+        if lo >= hi {return}
+        let mid = lo + (hi - lo) / 2;
+        let photon = &self.photons[mid];
+        if (photon.p - point).norm_squared() <= radius_squared {f(photon)}
+
+        // the side the point is on first, then the other side only if the sphere crosses the splitting plane
+        let axis = self.axes[mid] as usize;
+        let offset = point[axis] - photon.p[axis];
+        let (near, far) = if offset < 0.0 {((lo, mid), (mid + 1, hi))} else {((mid + 1, hi), (lo, mid))};
+        self.search(near.0, near.1, point, radius_squared, f);
+        if offset * offset <= radius_squared {self.search(far.0, far.1, point, radius_squared, f)}
+    }
+}
+
+fn build(photons: &mut [Photon], axes: &mut [u8]) {
+    if photons.len() <= 1 {return}
+
+    let mut min = photons[0].p;
+    let mut max = photons[0].p;
+    for photon in photons.iter() {
+        min = min.inf(&photon.p);
+        max = max.sup(&photon.p);
+    }
+    let axis = (max - min).imax();
+
+    let mid = photons.len() / 2;
+    photons.select_nth_unstable_by(mid, |a, b| a.p[axis].total_cmp(&b.p[axis]));
+    axes[mid] = axis as u8;
+
+    let (below, rest) = photons.split_at_mut(mid);
+    let (axes_below, axes_rest) = axes.split_at_mut(mid);
+    build(below, axes_below);
+    build(&mut rest[1..], &mut axes_rest[1..]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::util::rng::Rng;
+
+    // photons tagged with their index in the red channel of their power, so the ones found can be told apart
+    fn photon(index: usize, p: Point3<f32>) -> Photon {
+        Photon {p, incoming: Vector3::new(0.0, 1.0, 0.0), normal: Vector3::new(0.0, 1.0, 0.0), power: Colour::new_from(index as f32, 0.0, 0.0)}
+    }
+
+    fn near(map: &PhotonMap, point: &Point3<f32>, radius: f32) -> Vec<usize> {
+        let mut found = Vec::new();
+        map.for_each_near(point, radius, |photon| found.push(photon.power.0.x as usize));
+        found.sort();
+        return found;
+    }
+
+    fn brute_force(photons: &[Photon], point: &Point3<f32>, radius: f32) -> Vec<usize> {
+        return (0..photons.len()).filter(|&i| (photons[i].p - point).norm_squared() <= radius * radius).collect();
+    }
+
+    fn random_point(rng: &mut Rng) -> Point3<f32> {
+        Point3::new(rng.next_f32(), rng.next_f32(), rng.next_f32()) * 10.0
+    }
+
+    #[test]
+    fn finds_the_same_photons_as_checking_every_one() {
+        let mut rng = Rng::new(1);
+        for count in [0, 1, 2, 3, 10, 1000] {
+            let mut photons: Vec<Photon> = (0..count).map(|i| photon(i, random_point(&mut rng))).collect();
+            // some photons on a flat wall and some on top of each other, so splits have ties
+            if count > 3 {
+                for i in 0..count / 4 {photons[i].p.z = 5.0}
+                photons[count - 1].p = photons[count - 2].p;
+            }
+            let map = PhotonMap::new(photons.clone());
+            assert_eq!(map.len(), count);
+            for _ in 0..200 {
+                let point = random_point(&mut rng);
+                let radius = rng.next_f32() * 4.0;
+                assert_eq!(near(&map, &point, radius), brute_force(&photons, &point, radius));
+            }
+            for photon in &photons {
+                assert!(near(&map, &photon.p, 0.0).contains(&(photon.power.0.x as usize)));
+            }
+        }
+    }
+
+    #[test]
+    fn empty_map_finds_nothing() {
+        let map = PhotonMap::empty();
+        assert!(map.is_empty());
+        assert!(near(&map, &Point3::origin(), 100.0).is_empty());
+    }
+
+    #[test]
+    fn one_photon_map_finds_it_only_within_the_radius() {
+        let map = PhotonMap::new(vec![photon(0, Point3::new(1.0, 2.0, 3.0))]);
+        assert_eq!(near(&map, &Point3::new(1.0, 2.0, 4.0), 1.0), vec![0]);
+        assert!(near(&map, &Point3::new(1.0, 2.0, 4.5), 1.0).is_empty());
+    }
+}
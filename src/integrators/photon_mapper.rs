@@ -0,0 +1,228 @@
+// progressive photon mapping: before each pass light is traced out from the lights (and the background) and
+// left in a photon map wherever it lands on a diffuse surface, then camera paths look up the photons near where they land
+
+use std::f32::consts::PI;
+use std::sync::RwLock;
+
+use nalgebra::{Point3, Vector3};
+use rayon::prelude::*;
+
+use crate::core::colour::Colour;
+use crate::core::ray::Ray;
+use crate::geometry::hittable::{HitRecord, Hittable};
+use crate::integrators::integrator::{russian_roulette, sample_lights, weighted_emission, Integrator, RenderContext};
+use crate::integrators::photon_map::{Photon, PhotonMap};
+use crate::materials::material::ScatterSample;
+use crate::util::interval::Interval;
+use crate::util::onb::ONB;
+use crate::util::rng::Rng;
+use crate::util::vector_math::{near_zero, random_f32, random_in_unit_disk, random_unit_vector};
+
+// how quickly the gather radius shrinks from pass to pass (Knaus and Zwicker's alpha); lower shrinks faster
+const ALPHA: f32 = 2.0 / 3.0;
+
+/// Progressive photon mapping (Knaus and Zwicker). Every sample per pixel is its own pass with a fresh photon map,
+/// gathered within a radius that shrinks a little each pass, so the blur and bias of the density estimate fade out as
+/// the passes are averaged. Camera paths follow mirrors, glass and volumes, and at the first diffuse surface add
+/// direct light (sampled as in `NEEPathTracer`) and the photons within the radius, which carry all of the indirect light.
+/// That makes it good at caustics: light focused by glass onto a diffuse surface, which the path tracers only find by chance.
+///
+/// Photons leave the scene's lights and a non-black background. The number per pass and the starting radius are
+/// `Camera::photons` and `Camera::photon_radius`.
+pub struct PhotonMapper {
+    pass: RwLock<PassPhotons>,
+}
+
+// the photon map for the current pass, the radius it is gathered within, and how many photon paths it was traced from
+struct PassPhotons {
+    map: PhotonMap,
+    radius: f32,
+    paths: u64,
+}
+
+impl PhotonMapper {
+    pub fn new() -> Self {
+        Self {
+            pass: RwLock::new(PassPhotons {map: PhotonMap::empty(), radius: 0.0, paths: 0}),
+        }
+    }
+
+    // follows one photon out from a light (or the background), recording it at every diffuse surface after the first hit
+    fn trace_photon(&self, ctx: &RenderContext, scene: &(Point3<f32>, f32), rng: &mut Rng, photons: &mut Vec<Photon>) {
+        let has_lights = !ctx.lights.objects.is_empty();
+        let has_background = ctx.background.0 != Vector3::zeros();
+        // with both, half of the photons come from each
+        let background_chance = match (has_lights, has_background) {
+            (true, true) => 0.5,
+            (false, true) => 1.0,
+            (_, false) => 0.0,
+        };
+        if background_chance == 0.0 && !has_lights {return}
+
+        let time = ctx.camera.sample_time(rng);
+        let (mut ray, power) = if random_f32(rng) < background_chance {
+            // the background shines in evenly from every direction: a random direction, from a random point on a disk
+            // as wide as the scene, facing it from outside
+            let (center, radius) = scene;
+            let direction = random_unit_vector(rng);
+            let disk = random_in_unit_disk(rng);
+            let origin = center + ONB::new(&direction).transform(&Vector3::new(disk.x * radius, disk.y * radius, -radius));
+            let flux = PI * radius * radius * 4.0 * PI / background_chance;
+            (Ray::new_from(origin, direction, time), Colour(ctx.background.0 * flux))
+        } else {
+            let Some((rec, pdf_pos)) = ctx.lights.sample_surface(rng) else {return};
+            if pdf_pos.is_nan() || pdf_pos <= 0.0 {return}
+
+            // as for light paths in BDPT: a cosine distributed direction, on either side of the light,
+            // so the cosine cancels and leaves 2 pi over the density of the point
+            let mut direction = rec.normal + random_unit_vector(rng);
+            if near_zero(direction) {direction = rec.normal}
+            if random_f32(rng) < 0.5 {direction = -direction}
+
+            let le = rec.mat.emitted(rec.u, rec.v, rec.p);
+            let flux = 2.0 * PI / (pdf_pos * (1.0 - background_chance));
+            (Ray::new_from(rec.p, direction, time), Colour(le.0 * flux))
+        };
+
+        let mut throughput = Colour::new_from(1.0, 1.0, 1.0);
+        for bounce in 0..ctx.max_depth {
+            let Some(rec) = ctx.world.hit(&ray, &Interval::new(0.001, f32::INFINITY), rng) else {break};
+            let Some(sample) = rec.mat.sample(&ray, &rec, rng) else {break};
+
+            // photons straight from a light aren't kept, camera paths find direct light by sampling the lights
+            if bounce > 0 && !sample.is_delta && !rec.mat.is_volumetric() {
+                photons.push(Photon {
+                    p: rec.p,
+                    incoming: -ray.direction().normalize(),
+                    normal: rec.normal,
+                    power: Colour(power.0.component_mul(&throughput.0)),
+                });
+            }
+
+            throughput.0.component_mul_assign(&sample.attenuation.0);
+            ray = sample.ray;
+
+            if bounce + 1 >= ctx.russian_roulette_depth && !russian_roulette(&mut throughput, rng) {break}
+        }
+    }
+
+    // the light reaching a diffuse hit straight from the lights or background: a light sample, and the BSDF sample
+    // `sample` followed one bounce, weighted against each other as in NEEPathTracer
+    fn direct_light(&self, ray: &Ray, rec: &HitRecord, sample: &ScatterSample, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+        let mut colour = Vector3::zeros();
+        if !ctx.lights.objects.is_empty() {colour += sample_lights(ray, rec, ctx, rng).0}
+
+        let found = match ctx.world.hit(&sample.ray, &Interval::new(0.001, f32::INFINITY), rng) {
+            Some(light_rec) => weighted_emission(&sample.ray, &light_rec, Some(sample.pdf), ctx, rng),
+            None => ctx.background.clone(),
+        };
+        colour += sample.attenuation.0.component_mul(&found.0);
+        return Colour(colour);
+    }
+
+    // the density estimate of the light reflected towards the camera from the photons around the hit
+    fn gather(&self, ray: &Ray, rec: &HitRecord, pass: &PassPhotons) -> Colour {
+        if pass.paths == 0 {return Colour::new()}
+
+        let mut reflected = Vector3::zeros();
+        pass.map.for_each_near(&rec.p, pass.radius, |photon| {
+            if photon.normal.dot(&rec.normal) <= 0.0 {return}
+            // eval includes the cosine, which the photon's flux already accounts for
+            let cos_theta = rec.normal.dot(&photon.incoming);
+            if cos_theta <= 0.0 {return}
+            let bsdf = rec.mat.eval(ray, rec, &photon.incoming);
+            reflected += bsdf.0.component_mul(&photon.power.0) / cos_theta;
+        });
+
+        return Colour(reflected / (PI * pass.radius * pass.radius * pass.paths as f32));
+    }
+}
+
+impl Default for PhotonMapper {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Integrator for PhotonMapper {
+    fn name(&self) -> &'static str {
+        "photon"
+    }
+
+    fn ray_colour(&self, ray: &Ray, ctx: &RenderContext, rng: &mut Rng) -> Colour {
+        let pass = self.pass.read().unwrap();
+        let mut colour = Vector3::zeros();
+        let mut throughput = Colour::new_from(1.0, 1.0, 1.0);
+        let mut ray = ray.clone();
+        let mut bsdf_pdf = None;
+
+        for bounce in 0..ctx.max_depth {
+            let Some(hit_rec) = ctx.world.hit(&ray, &Interval::new(0.001, f32::INFINITY), rng) else {
+                colour += throughput.0.component_mul(&ctx.background.0);
+                break;
+            };
+
+            let emitted = weighted_emission(&ray, &hit_rec, bsdf_pdf, ctx, rng);
+            colour += throughput.0.component_mul(&emitted.0);
+
+            let Some(sample) = hit_rec.mat.sample(&ray, &hit_rec, rng) else {break};
+            let last_bounce = bounce + 1 == ctx.max_depth;
+
+            // the first diffuse surface ends the path: the photons there stand in for everything further along
+            if !sample.is_delta && !hit_rec.mat.is_volumetric() {
+                if !last_bounce {
+                    let direct = self.direct_light(&ray, &hit_rec, &sample, ctx, rng);
+                    colour += throughput.0.component_mul(&direct.0);
+                }
+                let indirect = self.gather(&ray, &hit_rec, &pass);
+                colour += throughput.0.component_mul(&indirect.0);
+                break;
+            }
+
+            // volumes are path traced through, sampling the lights from inside them
+            if !last_bounce && !sample.is_delta && !ctx.lights.objects.is_empty() {
+                let direct = sample_lights(&ray, &hit_rec, ctx, rng);
+                colour += throughput.0.component_mul(&direct.0);
+            }
+
+            throughput.0.component_mul_assign(&sample.attenuation.0);
+            bsdf_pdf = if sample.is_delta {None} else {Some(sample.pdf)};
+            ray = sample.ray;
+
+            if bounce + 1 >= ctx.russian_roulette_depth && !russian_roulette(&mut throughput, rng) {break}
+        }
+
+        return Colour(colour);
+    }
+
+    fn passes(&self, samples_per_pixel: u32) -> u32 {
+        samples_per_pixel
+    }
+
+    fn start_pass(&self, ctx: &RenderContext, pass: u32) {
+        let paths = ctx.camera.photons_per_pass();
+
+        // the bounding sphere of the scene, for photons from the background to start outside of
+        let bbox = ctx.world.bounding_box();
+        let min = Point3::new(bbox.x.min, bbox.y.min, bbox.z.min);
+        let max = Point3::new(bbox.x.max, bbox.y.max, bbox.z.max);
+        let scene = (nalgebra::center(&min, &max), (max - min).norm() / 2.0);
+
+        let photons: Vec<Photon> = (0..paths).into_par_iter()
+            .flat_map_iter(|i| {
+                let mut rng = Rng::for_light_path(ctx.camera.seed, pass as u64, i);
+                let mut photons = Vec::new();
+                self.trace_photon(ctx, &scene, &mut rng, &mut photons);
+                photons
+            })
+            .collect();
+
+        // r_(i+1)^2 = r_i^2 (i + alpha) / (i + 1), which shrinks slowly enough for the estimate to still converge
+        let mut radius_squared = ctx.camera.initial_photon_radius().powi(2);
+        for i in 1..=pass {
+            radius_squared *= (i as f32 + ALPHA) / (i as f32 + 1.0);
+        }
+
+        *self.pass.write().unwrap() = PassPhotons {map: PhotonMap::new(photons), radius: radius_squared.sqrt(), paths};
+    }
+}
@@ -0,0 +1,102 @@
+//! A path tracer following Ray Tracing: The Next Week.
+//!
+//! Build a world out of `Hittable`s (spheres, quads, triangles and triangle meshes, boxes,
+//! volumes and their transforms) with `Material`s and `Texture`s, frame it with a `Camera`, and
+//! call `Camera::render` to get a `Film` of linear radiance, estimated by the camera's `Integrator`
+//! (see `integrators`) from random numbers picked by its `Sampler` (see `samplers`), that can be
+//! tonemapped and encoded with the `output` module. The built-in scenes live in `scenes`, and
+//! scenes can also be loaded from TOML files with `scene_file`, with models imported from OBJ
+//! files by `loaders`.
+
+pub mod apertures;
+pub mod core;
+pub mod error;
+pub mod filters;
+pub mod geometry;
+pub mod integrators;
+pub mod loaders;
+pub mod materials;
+pub mod output;
+pub mod samplers;
+pub mod scene_file;
+pub mod scenes;
+pub mod textures;
+pub mod transforms;
+pub mod util;
+pub mod volumes;
+
+pub use crate::core::camera::Camera;
+pub use crate::core::colour::Colour;
+pub use crate::core::film::Film;
+pub use crate::core::ray::Ray;
+pub use crate::error::{Error, Result};
+pub use crate::filters::filter::Filter;
+pub use crate::geometry::hittable::{HitRecord, Hittable};
+pub use crate::integrators::integrator::Integrator;
+pub use crate::loaders::obj::load_obj;
+pub use crate::materials::material::Material;
+pub use crate::output::image_writer::ImageOutput;
+pub use crate::samplers::sampler::Sampler;
+pub use crate::scene_file::load_scene;
+pub use crate::scenes::Scene;
+pub use crate::textures::texture::Texture;
+pub use crate::util::rng::Rng;
+
+/// Everything needed to build and render a scene in code.
+pub mod prelude {
+    pub use crate::apertures::aperture::Aperture;
+    pub use crate::apertures::circular::CircularAperture;
+    pub use crate::apertures::image_aperture::ImageAperture;
+    pub use crate::apertures::polygonal::PolygonalAperture;
+    pub use crate::core::camera::Camera;
+    pub use crate::core::colour::Colour;
+    pub use crate::core::film::Film;
+    pub use crate::core::physical_camera::PhysicalCamera;
+    pub use crate::core::projection::Projection;
+    pub use crate::core::shutter::ShutterCurve;
+    pub use crate::core::stereo::{StereoLayout, StereoRig};
+    pub use crate::filters::box_filter::BoxFilter;
+    pub use crate::filters::filter::Filter;
+    pub use crate::filters::gaussian::GaussianFilter;
+    pub use crate::filters::lanczos::LanczosFilter;
+    pub use crate::filters::mitchell::MitchellFilter;
+    pub use crate::filters::tent::TentFilter;
+    pub use crate::geometry::bvh::BVHNode;
+    pub use crate::geometry::hittable::Hittable;
+    pub use crate::geometry::hittable_list::HittableList;
+    pub use crate::geometry::quad::{make_box, Quad};
+    pub use crate::geometry::sphere::Sphere;
+    pub use crate::geometry::triangle::Triangle;
+    pub use crate::geometry::triangle_mesh::TriangleMesh;
+    pub use crate::integrators::ambient_occlusion::AmbientOcclusion;
+    pub use crate::integrators::bdpt::BDPT;
+    pub use crate::integrators::direct_lighting::DirectLighting;
+    pub use crate::integrators::integrator::Integrator;
+    pub use crate::integrators::nee_path_tracer::NEEPathTracer;
+    pub use crate::integrators::path_tracer::PathTracer;
+    pub use crate::integrators::photon_mapper::PhotonMapper;
+    pub use crate::loaders::obj::{load_obj, load_obj_meshes};
+    pub use crate::materials::dielectric::Dielectric;
+    pub use crate::materials::diffuse_light::DiffuseLight;
+    pub use crate::materials::isotropic::Isotropic;
+    pub use crate::materials::lambertian::Lambertian;
+    pub use crate::materials::material::Material;
+    pub use crate::materials::metal::Metal;
+    pub use crate::output::image_writer::ImageOutput;
+    pub use crate::samplers::halton::Halton;
+    pub use crate::samplers::independent::Independent;
+    pub use crate::samplers::sampler::Sampler;
+    pub use crate::samplers::sobol::Sobol;
+    pub use crate::samplers::stratified::Stratified;
+    pub use crate::scenes::Scene;
+    pub use crate::textures::checkered_texture::CheckerTexture;
+    pub use crate::textures::image_texture::ImageTexture;
+    pub use crate::textures::noise_texture::NoiseTexture;
+    pub use crate::textures::solid_colour::SolidColour;
+    pub use crate::textures::texture::Texture;
+    pub use crate::transforms::rotate_y::RotateY;
+    pub use crate::transforms::translate::Translate;
+    pub use crate::util::rng::Rng;
+    pub use crate::volumes::constant_medium::ConstantMedium;
+    pub use nalgebra::{Point3, Vector3};
+}
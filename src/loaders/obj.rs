@@ -0,0 +1,188 @@
+// loads Wavefront OBJ models (and their MTL material libraries) as triangle meshes
+//
+// MTL materials are mapped onto the materials the renderer has:
+// - an emission colour (Ke) makes a DiffuseLight
+// - transparency (d < 1, or one of the transparent illumination models) makes a Dielectric with index Ni
+// - a specular colour (Ks) brighter than the diffuse one makes a Metal, fuzzier the lower the exponent Ns
+// - anything else is Lambertian, textured with map_Kd if there is one and coloured by Kd otherwise
+
+use std::path::Path;
+
+use nalgebra::{Point3, Vector3};
+
+use crate::core::colour::Colour;
+use crate::error::{Error, Result};
+use crate::geometry::hittable_list::HittableList;
+use crate::geometry::triangle_mesh::TriangleMesh;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::material::Material;
+use crate::materials::metal::Metal;
+use crate::textures::image_texture::ImageTexture;
+use crate::textures::rtw_image::RTWImage;
+
+/// Loads the OBJ file at `path` as one hittable per triangle, ready to add to a world or BVH.
+pub fn load_obj<P: AsRef<Path>>(path: P) -> Result<HittableList> {
+    let mut triangles = HittableList::new();
+    for mesh in load_obj_meshes(path)? {
+        triangles.add_list(mesh.into_hittable_list()?);
+    }
+    Ok(triangles)
+}
+
+/// Loads the OBJ file at `path` as one triangle mesh per object (and per material within an object).
+/// Meshes with vertex normals are smooth shaded.
+pub fn load_obj_meshes<P: AsRef<Path>>(path: P) -> Result<Vec<TriangleMesh>> {
+    let path = path.as_ref();
+    let options = tobj::LoadOptions {
+        single_index: true,
+        triangulate: true,
+        ignore_points: true,
+        ignore_lines: true,
+    };
+
+    let (models, mtl_result) = tobj::load_obj(path, &options)
+        .map_err(|e| Error::ObjLoad(format!("could not load OBJ file '{}': {}", path.display(), e)))?;
+    let mtl_materials = mtl_result
+        .map_err(|e| Error::ObjLoad(format!("could not load the materials of OBJ file '{}': {}", path.display(), e)))?;
+
+    // texture paths in an MTL file are relative to it, and the MTL file is found next to the OBJ file
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let materials = mtl_materials.iter()
+        .map(|mtl| convert_material(mtl, dir))
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut meshes = Vec::new();
+    for model in models {
+        let mesh = model.mesh;
+        if mesh.indices.is_empty() {continue}
+
+        let mat = match mesh.material_id.and_then(|id| materials.get(id)) {
+            Some(mat) => mat.clone(),
+            None => default_material(),
+        };
+
+        let positions = mesh.positions.chunks_exact(3).map(|p| Point3::new(p[0], p[1], p[2])).collect::<Vec<_>>();
+        let indices = mesh.indices.chunks_exact(3).map(|t| [t[0] as usize, t[1] as usize, t[2] as usize]).collect();
+        let vertices = positions.len();
+
+        let mut triangle_mesh = TriangleMesh::new(positions, indices, mat);
+        if mesh.normals.len() == 3*vertices {
+            triangle_mesh.normals = mesh.normals.chunks_exact(3).map(|n| Vector3::new(n[0], n[1], n[2])).collect();
+            triangle_mesh.smooth = true;
+        }
+        if mesh.texcoords.len() == 2*vertices {
+            triangle_mesh.uvs = mesh.texcoords.chunks_exact(2).map(|uv| (uv[0], uv[1])).collect();
+        }
+        meshes.push(triangle_mesh);
+    }
+
+    if meshes.is_empty() {
+        return Err(Error::ObjLoad(format!("OBJ file '{}' has no faces", path.display())));
+    }
+    Ok(meshes)
+}
+
+fn default_material() -> Box<dyn Material> {
+    Box::new(Lambertian::new_from(Colour::new_from(0.8, 0.8, 0.8)))
+}
+
+fn convert_material(mtl: &tobj::Material, dir: &Path) -> Result<Box<dyn Material>> {
+    let colour = |c: [f32; 3]| Colour::new_from(c[0], c[1], c[2]);
+    let brightest = |c: [f32; 3]| c[0].max(c[1]).max(c[2]);
+
+    if let Some(ke) = mtl.emissive.filter(|ke| brightest(*ke) > 0.0) {
+        return Ok(Box::new(DiffuseLight::new_from(colour(ke))));
+    }
+
+    let transparent = mtl.dissolve.is_some_and(|d| d < 1.0) || matches!(mtl.illumination_model, Some(4 | 6 | 7 | 9));
+    if transparent {
+        return Ok(Box::new(Dielectric::new_from(mtl.optical_density.unwrap_or(1.5))));
+    }
+
+    let diffuse = mtl.diffuse.unwrap_or([0.8, 0.8, 0.8]);
+    if let Some(specular) = mtl.specular {
+        if mtl.diffuse_texture.is_none() && brightest(specular) > brightest(diffuse) {
+            // a rough match between the Phong exponent and the fuzz of a metal
+            let fuzz = (2.0 / (mtl.shininess.unwrap_or(0.0).max(0.0) + 2.0)).sqrt();
+            return Ok(Box::new(Metal::new_from(colour(specular), fuzz)));
+        }
+    }
+
+    if let Some(filename) = &mtl.diffuse_texture {
+        // look next to the MTL file first, then wherever the other images are searched for
+        let image = RTWImage::new_from(dir.join(filename)).or_else(|_| RTWImage::new_from(filename))?;
+        return Ok(Box::new(Lambertian::new_from_tex(Box::new(ImageTexture::new_from_image(image)))));
+    }
+
+    Ok(Box::new(Lambertian::new_from(colour(diffuse))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::ray::Ray;
+    use crate::geometry::hittable::{HitRecord, Hittable};
+    use crate::util::interval::Interval;
+    use crate::util::rng::Rng;
+
+    // materials.obj has a triangle per material in materials.mtl, each lying in the y = 0 plane
+    fn hit_each_material() -> Vec<HitRecord> {
+        let meshes = load_obj_meshes(Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes/models/materials.obj")).unwrap();
+        let ray = Ray::new_from(Point3::new(0.1, 1.0, -0.4), Vector3::new(0.1, -1.0, 0.1), 0.0);
+        meshes.into_iter()
+            .map(|mesh| mesh.into_hittable_list().unwrap().hit(&ray, &Interval::new(0.001, f32::INFINITY), &mut Rng::new(1)).unwrap())
+            .collect()
+    }
+
+    // the same random numbers give the same scattered rays as the material the MTL entry should have become
+    fn assert_scatters_like(rec: &HitRecord, expected: &dyn Material) {
+        let ray = Ray::new_from(rec.p + Vector3::new(-0.1, 1.0, -0.1), Vector3::new(0.1, -1.0, 0.1), 0.0);
+        for seed in 0..16 {
+            let got = rec.mat.sample(&ray, rec, &mut Rng::new(seed)).unwrap();
+            let want = expected.sample(&ray, rec, &mut Rng::new(seed)).unwrap();
+            assert!((got.ray.direction() - want.ray.direction()).norm() < 1e-6, "scattered {:?}, expected {:?}", got.ray.direction(), want.ray.direction());
+            assert!((got.attenuation.0 - want.attenuation.0).norm() < 1e-6, "attenuation {:?}, expected {:?}", got.attenuation.0, want.attenuation.0);
+            assert_eq!(got.is_delta, want.is_delta);
+        }
+    }
+
+    #[test]
+    fn emission_makes_a_light() {
+        let recs = hit_each_material();
+        let lamp = &recs[0];
+        assert!(lamp.mat.is_emissive());
+        assert!((lamp.mat.emitted(lamp.u, lamp.v, lamp.p).0 - Vector3::new(4.0, 3.0, 2.0)).norm() < 1e-6);
+        assert!(recs[1..].iter().all(|rec| !rec.mat.is_emissive()));
+    }
+
+    #[test]
+    fn transparency_makes_glass_with_the_optical_density() {
+        let recs = hit_each_material();
+        // d < 1
+        assert_scatters_like(&recs[1], &Dielectric::new_from(1.33));
+        // a transparent illumination model, with d left at 1
+        assert_scatters_like(&recs[2], &Dielectric::new_from(2.0));
+    }
+
+    #[test]
+    fn bright_specular_makes_a_metal_with_fuzz_from_the_exponent() {
+        let recs = hit_each_material();
+        // Ns 48 gives a fuzz of sqrt(2 / 50)
+        assert_scatters_like(&recs[3], &Metal::new_from(Colour::new_from(0.8, 0.7, 0.6), 0.2));
+        // a specular colour dimmer than the diffuse one stays diffuse
+        assert_scatters_like(&recs[5], &Lambertian::new_from(Colour::new_from(0.2, 0.4, 0.6)));
+    }
+
+    #[test]
+    fn diffuse_map_is_found_next_to_the_mtl_file() {
+        // swatch.png is only in scenes/models, so it can only be found relative to the MTL file;
+        // it's a red pixel on the left and a blue one on the right
+        let mut rec = hit_each_material().swap_remove(4);
+        rec.u = 0.25;
+        assert_scatters_like(&rec, &Lambertian::new_from(Colour::new_from(1.0, 0.0, 0.0)));
+        rec.u = 0.75;
+        assert_scatters_like(&rec, &Lambertian::new_from(Colour::new_from(0.0, 0.0, 1.0)));
+    }
+}
@@ -1,30 +1,123 @@
-mod scenes;
-mod core;
-mod geometry;
-mod materials;
-mod textures;
-mod transforms;
-mod util;
-mod volumes;
-
-use crate::scenes::{bouncing_spheres, checkered_spheres, cornell_box, cornell_smoke, earth, final_scene, perlin_spheres, quads, quick_earth_test, simple_light, test_inner_spheres_quick};
-
-pub fn main() -> Result<(), ()> {
-
-    match 8 {
-        1 => bouncing_spheres(),
-        2 => checkered_spheres(),
-        3 => earth(),
-        4 => quick_earth_test(),
-        5 => perlin_spheres(),
-        6 => quads(),
-        7 => simple_light(),
-        8 => cornell_box(),
-        9 => cornell_smoke(),
-        10 => final_scene(800.0, 10000, 40),
-        11 => final_scene(400.0, 20, 4),
-        12 => test_inner_spheres_quick(),
-        _ => {todo!()}   
+mod cli;
+
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use clap::Parser;
+
+use rusty_raytracer::core::checkpoint::{render_hash, Checkpoint};
+use rusty_raytracer::core::stereo::StereoLayout;
+use rusty_raytracer::output::image_writer::ImageOutput;
+use rusty_raytracer::output::tonemap::tonemapper_from_name;
+use rusty_raytracer::scene_file::load_scene;
+use rusty_raytracer::scenes::{find_scene, SCENES};
+use rusty_raytracer::util::rng::Rng;
+use rusty_raytracer::{Error, Film, Result};
+
+use crate::cli::Cli;
+
+// samples per pixel between checkpoints, if the render isn't already split into passes
+const CHECKPOINT_PASS_SPP: u32 = 16;
+
+pub fn main() -> ExitCode {
+
+    let cli = Cli::parse();
+
+    match run(&cli) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: &Cli) -> Result<()> {
+
+    if cli.list {
+        let name_width = SCENES.iter().map(|entry| entry.name.len()).max().unwrap_or(0);
+        for entry in SCENES {
+            println!("{:<width$}  {}", entry.name, entry.description, width = name_width);
+        }
+        return Ok(());
     }
-    
-}
\ No newline at end of file
+
+    // check the output format before spending time on the render
+    let image_output = |path: PathBuf| -> Result<ImageOutput> {
+        let mut output = ImageOutput::new(path, cli.bit_depth)?;
+        output.tonemapper = tonemapper_from_name(&cli.tonemap)?;
+        Ok(output)
+    };
+    let output = image_output(cli.output.clone())?;
+    let heatmap_output = match &cli.heatmap {
+        Some(path) => Some(ImageOutput::new(path.clone(), cli.bit_depth)?),
+        None => None,
+    };
+
+    if let Some(threads) = cli.threads {
+        rayon::ThreadPoolBuilder::new().num_threads(threads).build_global()
+            .map_err(|e| Error::InvalidSettings(format!("could not start {} render threads: {}", threads, e)))?;
+    }
+    let mut rng = Rng::new(cli.seed);
+
+    // anything ending in .toml is a scene file, otherwise look the name up in the registry
+    let mut scene = if cli.scene.ends_with(".toml") {
+        load_scene(&cli.scene, &mut rng)?
+    } else {
+        let Some(entry) = find_scene(&cli.scene) else {
+            return Err(Error::InvalidSettings(format!("unknown scene '{}' (use --list to see the available scenes)", cli.scene)));
+        };
+        (entry.build)(&mut rng)?
+    };
+    scene.camera.seed = cli.seed;
+    cli.apply_overrides(&mut scene.camera)?;
+
+    // a stereo rig's views can each go to their own image
+    let eye_outputs = match scene.camera.stereo {
+        Some(stereo) if stereo.layout == StereoLayout::Separate => {
+            Some([image_output(eye_path(&cli.output, "left"))?, image_output(eye_path(&cli.output, "right"))?])
+        }
+        _ => None,
+    };
+
+    // a checkpoint is only resumed with the same scene and settings: a scene file's text or a built-in scene's name
+    // (whose random placement comes from the seed, which the camera settings cover)
+    let scene_source = if cli.scene.ends_with(".toml") {std::fs::read(&cli.scene)?} else {cli.scene.as_bytes().to_vec()};
+    if cli.checkpoint.is_some() && scene.camera.samples_per_pass == 0 {scene.camera.samples_per_pass = CHECKPOINT_PASS_SPP}
+    let hash = render_hash(&scene_source, scene.camera.settings_hash());
+
+    // the image (and checkpoint) is written after every pass, so a progressive render can be watched (or stopped) as it goes
+    let on_pass = |film: &Film, samples_done: u32| {
+        match &eye_outputs {
+            Some(eye_outputs) => {
+                let pixels = film.resolve();
+                let views = StereoLayout::Separate.views(film.width(), film.height());
+                for (eye_output, view) in eye_outputs.iter().zip(&views) {eye_output.write_region(&pixels, film.width(), view)?}
+            }
+            None => output.write(film)?,
+        }
+        if let Some(path) = &cli.checkpoint {Checkpoint::write(path, hash, samples_done, film)?}
+        Ok(())
+    };
+    let film = match &cli.checkpoint {
+        Some(path) if cli.resume => {
+            let checkpoint = Checkpoint::read(path, hash)?;
+            println!("Resuming from {} samples per pixel", checkpoint.samples_done);
+            scene.resume(checkpoint, on_pass)?
+        }
+        _ => scene.render_progressive(on_pass)?,
+    };
+    if let Some(heatmap_output) = &heatmap_output {heatmap_output.write(&film.sample_heatmap())?}
+
+    Ok(())
+}
+
+// the output's name with `_eye` added before its extension, for a stereo rig's separate images
+fn eye_path(path: &Path, eye: &str) -> PathBuf {
+    let stem = path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default();
+    let name = match path.extension() {
+        Some(extension) => format!("{}_{}.{}", stem, eye, extension.to_string_lossy()),
+        None => format!("{}_{}", stem, eye),
+    };
+    return path.with_file_name(name);
+}
@@ -1,6 +1,7 @@
+use crate::util::rng::Rng;
 use nalgebra::Vector3;
 
-use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::Material, core::ray::Ray, util::vector_math::{reflect, refract, random_f32}};
+use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::{Material, ScatterSample}, core::ray::Ray, util::vector_math::{reflect, refract, random_f32}};
 
 pub struct Dielectric {
     refraction_index: f32,
@@ -29,7 +30,7 @@ impl Dielectric {
 }
 
 impl Material for Dielectric {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Colour, Ray)> {
+    fn sample(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterSample> {
         
         let attenuation = Colour::new_from(1.0, 1.0, 1.0);
         let ri;
@@ -49,12 +50,12 @@ impl Material for Dielectric {
         let cannot_refract = ri * sin_theta > 1.0;
         let direction: Vector3<f32>;
 
-        if cannot_refract || (self.reflectance(cos_theta, ri) > random_f32()) {direction = reflect(&unit_direction, &rec.normal)}
+        if cannot_refract || (self.reflectance(cos_theta, ri) > random_f32(rng)) {direction = reflect(&unit_direction, &rec.normal)}
         else {direction = refract(&unit_direction, &rec.normal, ri)}        
 
         let scattered = Ray::new_from(rec.p, direction, r_in.time());
         //println!("dielectric scattered: {:?}", scattered);
-        Some((attenuation, scattered))
+        Some(ScatterSample { ray: scattered, attenuation, pdf: 0.0, is_delta: true })
     }
     fn clone_box(&self) -> Box<dyn Material + Send + Sync> {
         Box::new(self.clone())
@@ -29,12 +29,22 @@ impl DiffuseLight {
     
 }
 
+impl Default for DiffuseLight {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 
 impl Material for DiffuseLight {
 
     fn emitted(&self, u: f32, v: f32, p: Point3<f32>) -> Colour {
         return self.tex.value(u, v, &p);
     }
+
+    fn is_emissive(&self) -> bool {
+        true
+    }
     
     fn clone_box(&self) -> Box<dyn Material + Send + Sync> {
         Box::new(self.clone())
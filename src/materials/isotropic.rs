@@ -1,4 +1,6 @@
-use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::Material, core::ray::Ray, textures::solid_colour::SolidColour, textures::texture::Texture, util::vector_math::random_unit_vector};
+use crate::util::rng::Rng;
+use nalgebra::Vector3;
+use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::{Material, ScatterSample}, core::ray::Ray, textures::solid_colour::SolidColour, textures::texture::Texture, util::vector_math::random_unit_vector};
 
 pub struct Isotropic {
     tex: Box<dyn Texture>,
@@ -27,19 +29,39 @@ impl Isotropic {
     
 }
 
+impl Default for Isotropic {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 
 impl Material for Isotropic {
 
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Colour, Ray)> {
-        //let mut scatter_direction = rec.normal + random_unit_vector();
-        
-        //if near_zero(scatter_direction) {scatter_direction = rec.normal}// + random_unit_vector()}
-        
-        let scattered = Ray::new_from(rec.p, random_unit_vector(), r_in.time());
+    fn sample(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterSample> {
+        let scattered = Ray::new_from(rec.p, random_unit_vector(rng), r_in.time());
         let attenuation = self.tex.value(rec.u, rec.v, &rec.p);//self.albedo.clone();
-        //println!("lambertian scattered: {:?}", scattered);
         
-        Some((attenuation, scattered))
+        Some(ScatterSample {
+            ray: scattered,
+            attenuation,
+            pdf: 1.0 / (4.0 * std::f32::consts::PI),
+            is_delta: false,
+        })
+    }
+
+    // scattering is the same in every direction, and there's no surface for a cosine term
+    fn eval(&self, _r_in: &Ray, rec: &HitRecord, _direction: &Vector3<f32>) -> Colour {
+        let albedo = self.tex.value(rec.u, rec.v, &rec.p);
+        return Colour(albedo.0 / (4.0 * std::f32::consts::PI));
+    }
+
+    fn pdf(&self, _r_in: &Ray, _rec: &HitRecord, _direction: &Vector3<f32>) -> f32 {
+        return 1.0 / (4.0 * std::f32::consts::PI);
+    }
+
+    fn is_volumetric(&self) -> bool {
+        true
     }
     
     fn clone_box(&self) -> Box<dyn Material + Send + Sync> {
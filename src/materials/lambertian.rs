@@ -1,4 +1,6 @@
-use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::Material, util::vector_math::{near_zero, random_unit_vector}, core::ray::Ray, textures::solid_colour::SolidColour, textures::texture::Texture};
+use crate::util::rng::Rng;
+use nalgebra::Vector3;
+use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::{Material, ScatterSample}, util::vector_math::{near_zero, random_unit_vector}, core::ray::Ray, textures::solid_colour::SolidColour, textures::texture::Texture};
 
 
 pub struct Lambertian {
@@ -27,17 +29,40 @@ impl Lambertian {
     }
 }
 
+impl Default for Lambertian {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Material for Lambertian {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Colour, Ray)> {
-        let mut scatter_direction = rec.normal + random_unit_vector();
+    fn sample(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterSample> {
+        // the normal plus a random unit vector is cosine distributed about the normal
+        let mut scatter_direction = rec.normal + random_unit_vector(rng);
         
         if near_zero(scatter_direction) {scatter_direction = rec.normal}// + random_unit_vector()}
         
         let scattered = Ray::new_from(rec.p, scatter_direction, r_in.time());
         let attenuation = self.tex.value(rec.u, rec.v, &rec.p);//self.albedo.clone();
         //println!("lambertian scattered: {:?}", scattered);
-        Some((attenuation, scattered))
+        Some(ScatterSample {
+            pdf: self.pdf(r_in, rec, &scatter_direction),
+            ray: scattered,
+            attenuation,
+            is_delta: false,
+        })
+    }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: &Vector3<f32>) -> Colour {
+        let albedo = self.tex.value(rec.u, rec.v, &rec.p);
+        return Colour(albedo.0 * self.pdf(r_in, rec, direction));
+    }
+
+    fn pdf(&self, _r_in: &Ray, rec: &HitRecord, direction: &Vector3<f32>) -> f32 {
+        let cos_theta = rec.normal.dot(&direction.normalize());
+        return if cos_theta < 0.0 {0.0} else {cos_theta / std::f32::consts::PI};
     }
+
     fn clone_box(&self) -> Box<dyn Material + Send + Sync> {
         Box::new(self.clone())
     }
@@ -1,17 +1,56 @@
+use crate::util::rng::Rng;
 use dyn_clone::DynClone;
-use nalgebra::Point3;
+use nalgebra::{Point3, Vector3};
 
 use crate::{core::colour::Colour, geometry::hittable::HitRecord, core::ray::Ray};
 
+/// A scattered ray picked by `Material::sample`.
+pub struct ScatterSample {
+    pub ray: Ray,
+    /// What the path's colour is multiplied by: the BSDF times the cosine, over the pdf.
+    pub attenuation: Colour,
+    /// The density (over solid angle) the direction was picked with.
+    pub pdf: f32,
+    /// Set for delta lobes (mirrors and glass), which only scatter into one direction. Their `eval` and `pdf`
+    /// are zero for any other direction, so the lights can't be sampled from them and the pdf above means nothing.
+    pub is_delta: bool,
+}
+
 pub trait Material: Send + Sync + DynClone{
 
-    fn scatter(&self, _r_in: &Ray, _rec: &HitRecord) -> Option<(Colour, Ray)> {
+    /// Picks a direction to scatter the incoming ray into, or None if the ray is absorbed.
+    fn sample(&self, _r_in: &Ray, _rec: &HitRecord, _rng: &mut Rng) -> Option<ScatterSample> {
         None
     }
 
+    /// The BSDF (or phase function, for volumes) times the cosine with the normal, for light leaving along `direction`.
+    fn eval(&self, _r_in: &Ray, _rec: &HitRecord, _direction: &Vector3<f32>) -> Colour {
+        Colour::new()
+    }
+
+    /// The density (over solid angle) of `sample` picking `direction`.
+    fn pdf(&self, _r_in: &Ray, _rec: &HitRecord, _direction: &Vector3<f32>) -> f32 {
+        0.0
+    }
+
+    /// The attenuation and ray from `sample`, for renderers that don't need the density.
+    fn scatter(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<(Colour, Ray)> {
+        self.sample(r_in, rec, rng).map(|sample| (sample.attenuation, sample.ray))
+    }
+
     fn emitted(&self, _u: f32, _v: f32, _p: Point3<f32>) -> Colour {
         return Colour::new();
     }
+
+    fn is_emissive(&self) -> bool {
+        false
+    }
+
+    /// True for phase functions, which scatter at points inside a volume rather than on a surface,
+    /// so no cosine with the (meaningless) normal applies when light arrives or leaves.
+    fn is_volumetric(&self) -> bool {
+        false
+    }
     fn clone_box(&self) -> Box<dyn Material + Send + Sync>;
 }
 
@@ -1,4 +1,7 @@
-use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::Material, core::ray::Ray};
+use std::f32::consts::PI;
+use nalgebra::Vector3;
+use crate::util::rng::Rng;
+use crate::{core::colour::Colour, geometry::hittable::HitRecord, materials::material::{Material, ScatterSample}, core::ray::Ray};
 use crate::util::vector_math::{ random_unit_vector, reflect};
 
 
@@ -25,21 +28,51 @@ impl Metal {
     
 }
 
+impl Default for Metal {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Material for Metal {
-    fn scatter(&self, r_in: &Ray, rec: &HitRecord) -> Option<(Colour, Ray)> {
+    // a mirror (no fuzz) is a delta lobe; fuzzed reflections spread over a cone about the mirror direction
+    fn sample(&self, r_in: &Ray, rec: &HitRecord, rng: &mut Rng) -> Option<ScatterSample> {
         
         let mut reflected = reflect(&r_in.direction(), &rec.normal);
-        reflected = reflected.normalize() + (self.fuzz*random_unit_vector());
+        reflected = reflected.normalize() + (self.fuzz*random_unit_vector(rng));
 
         let scattered = Ray::new_from(rec.p, reflected, r_in.time());
         let attenuation = self.albedo.clone();
         if scattered.direction().dot(&rec.normal) > 0.0 {
             //println!("metal scattered: {:?}", scattered);
-            Some((attenuation, scattered))
+            if self.fuzz == 0.0 {
+                return Some(ScatterSample { ray: scattered, attenuation, pdf: 0.0, is_delta: true });
+            }
+            Some(ScatterSample { pdf: self.pdf(r_in, rec, &reflected), ray: scattered, attenuation, is_delta: false })
         } else {
             None
         }
     }
+
+    fn eval(&self, r_in: &Ray, rec: &HitRecord, direction: &Vector3<f32>) -> Colour {
+        if direction.dot(&rec.normal) <= 0.0 {return Colour::new()}
+        return Colour(self.albedo.0 * self.pdf(r_in, rec, direction));
+    }
+
+    // the mirror direction plus fuzz times a random unit vector lands on a sphere of radius fuzz about the mirror
+    // direction, so the density of a direction is that of the points where it crosses the sphere (two of them, or
+    // one at fuzz 1), each 1 / (4 pi fuzz^2) over area, turned into solid angle by t^2 / |cos| at the crossing
+    fn pdf(&self, r_in: &Ray, rec: &HitRecord, direction: &Vector3<f32>) -> f32 {
+        if self.fuzz == 0.0 {return 0.0}
+        let mirror = reflect(&r_in.direction(), &rec.normal).normalize();
+        let cos_theta = mirror.dot(&direction.normalize());
+        let fuzz_squared = self.fuzz * self.fuzz;
+        let discriminant = cos_theta * cos_theta - 1.0 + fuzz_squared;
+        // directions sampled at the edge of the cone can round to just outside it
+        if cos_theta <= 0.0 || discriminant < -1e-5 {return 0.0}
+        return (2.0 * cos_theta * cos_theta - 1.0 + fuzz_squared) / (2.0 * PI * self.fuzz * discriminant.max(1e-8).sqrt());
+    }
+
     fn clone_box(&self) -> Box<dyn Material + Send + Sync> {
         Box::new(self.clone())
     }
@@ -52,4 +85,40 @@ impl Clone for Metal {
             fuzz: self.fuzz.clone(),
         }
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra::Point3;
+
+    // directions drawn from the lobe, weighted by one over their density, measure the solid angle the lobe covers:
+    // the cone of directions within asin(fuzz) of the mirror direction
+    #[test]
+    fn pdf_matches_sampled_directions() {
+        let fuzz = 0.6;
+        let metal = Metal::new_from(Colour::new_from(1.0, 1.0, 1.0), fuzz);
+        let rec = HitRecord::new_from(Point3::origin(), Vector3::new(0.0, 0.0, 1.0), Box::new(metal.clone()), 1.0);
+        let r_in = Ray::new_from(Point3::new(0.0, 0.0, 1.0), Vector3::new(0.0, 0.0, -1.0), 0.0);
+        let mut rng = Rng::new(1);
+        let n = 200_000;
+        let mut total = 0.0;
+        for _ in 0..n {
+            let sample = metal.sample(&r_in, &rec, &mut rng).unwrap();
+            assert!(!sample.is_delta);
+            assert!((sample.pdf - metal.pdf(&r_in, &rec, &sample.ray.direction())).abs() <= 1e-4 * sample.pdf);
+            total += 1.0 / sample.pdf as f64;
+        }
+        let cone = 2.0 * std::f64::consts::PI * (1.0 - (1.0 - (fuzz * fuzz) as f64).sqrt());
+        assert!((total / n as f64 - cone).abs() < 0.01 * cone, "{} vs {}", total / n as f64, cone);
+    }
+
+    #[test]
+    fn mirror_is_delta() {
+        let metal = Metal::new_from(Colour::new_from(1.0, 1.0, 1.0), 0.0);
+        let rec = HitRecord::new_from(Point3::origin(), Vector3::new(0.0, 0.0, 1.0), Box::new(metal.clone()), 1.0);
+        let r_in = Ray::new_from(Point3::new(-1.0, 0.0, 1.0), Vector3::new(1.0, 0.0, -1.0), 0.0);
+        let sample = metal.sample(&r_in, &rec, &mut Rng::new(1)).unwrap();
+        assert!(sample.is_delta);
+        assert!((sample.ray.direction().normalize() - Vector3::new(1.0, 0.0, 1.0).normalize()).norm() < 1e-6);
+    }
+}
@@ -0,0 +1,111 @@
+// encoders turn the resolved (linear) pixels into an image file
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use image::{ImageBuffer, Rgb, Rgb32FImage, RgbImage};
+
+use crate::core::colour::{linear_to_gamma, Colour};
+use crate::error::{Error, Result};
+use crate::util::interval::Interval;
+
+pub trait Encoder: Send + Sync {
+    /// Writes `pixels` (linear colour, row by row from the top left) to `path`.
+    fn encode(&self, pixels: &[Colour], width: usize, height: usize, path: &Path) -> Result<()>;
+}
+
+/// 8-bit binary (P6) PPM, gamma corrected.
+pub struct PpmEncoder;
+
+impl Encoder for PpmEncoder {
+    fn encode(&self, pixels: &[Colour], width: usize, height: usize, path: &Path) -> Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let header = format!("P6\n{} {}\n255\n", width, height);
+        file.write_all(header.as_bytes())?;
+
+        for pixel in &pixels[..width*height] {
+            file.write_all(&gamma_encode(pixel, 255.999).map(|c| c as u8))?;
+        }
+        file.flush()?;
+        Ok(())
+    }
+}
+
+/// 8 or 16-bit PNG, gamma corrected.
+pub struct PngEncoder {
+    pub bit_depth: u8,
+}
+
+impl Encoder for PngEncoder {
+    fn encode(&self, pixels: &[Colour], width: usize, height: usize, path: &Path) -> Result<()> {
+        if self.bit_depth == 16 {
+            let img: ImageBuffer<Rgb<u16>, Vec<u16>> = ImageBuffer::from_fn(width as u32, height as u32, |i, j| {
+                Rgb(gamma_encode(&pixels[j as usize*width + i as usize], 65535.999).map(|c| c as u16))
+            });
+            img.save_with_format(path, image::ImageFormat::Png)?;
+            Ok(())
+        }
+        else {
+            let img = RgbImage::from_fn(width as u32, height as u32, |i, j| {
+                Rgb(gamma_encode(&pixels[j as usize*width + i as usize], 255.999).map(|c| c as u8))
+            });
+            img.save_with_format(path, image::ImageFormat::Png)?;
+            Ok(())
+        }
+    }
+}
+
+/// Radiance HDR, linear radiance.
+pub struct HdrEncoder;
+
+impl Encoder for HdrEncoder {
+    fn encode(&self, pixels: &[Colour], width: usize, height: usize, path: &Path) -> Result<()> {
+        float_image(pixels, width, height).save_with_format(path, image::ImageFormat::Hdr)?;
+        Ok(())
+    }
+}
+
+/// OpenEXR (32-bit float), linear radiance.
+pub struct ExrEncoder;
+
+impl Encoder for ExrEncoder {
+    fn encode(&self, pixels: &[Colour], width: usize, height: usize, path: &Path) -> Result<()> {
+        float_image(pixels, width, height).save_with_format(path, image::ImageFormat::OpenExr)?;
+        Ok(())
+    }
+}
+
+/// Picks the encoder from the file extension. `bit_depth` only matters for PNG (8 or 16).
+pub fn encoder_for_path(path: &Path, bit_depth: u8) -> Result<Box<dyn Encoder>> {
+    let extension = path.extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+
+    match (extension.as_str(), bit_depth) {
+        ("ppm", 8) => Ok(Box::new(PpmEncoder)),
+        ("png", 8 | 16) => Ok(Box::new(PngEncoder { bit_depth })),
+        ("hdr", _) => Ok(Box::new(HdrEncoder)),
+        ("exr", _) => Ok(Box::new(ExrEncoder)),
+        ("ppm" | "png", _) => Err(Error::InvalidSettings(format!("unsupported bit depth {} for .{} output", bit_depth, extension))),
+        _ => Err(Error::InvalidSettings(format!("unsupported output format '{}' (expected .ppm, .png, .hdr or .exr)", path.display()))),
+    }
+}
+
+fn float_image(pixels: &[Colour], width: usize, height: usize) -> Rgb32FImage {
+    Rgb32FImage::from_fn(width as u32, height as u32, |i, j| {
+        let colour = &pixels[j as usize*width + i as usize];
+        Rgb([colour.r(), colour.g(), colour.b()].map(finite_or_zero))
+    })
+}
+
+/// Gamma corrects (gamma = 2.0) and scales each channel to [0, max].
+fn gamma_encode(pixel: &Colour, max: f32) -> [f32; 3] {
+    let intensity = Interval::new(0.0, 1.0);
+    [pixel.r(), pixel.g(), pixel.b()].map(|c| max*intensity.clamp(linear_to_gamma(finite_or_zero(c))))
+}
+
+fn finite_or_zero(x: f32) -> f32 {
+    if x.is_finite() {x} else {0.0}
+}
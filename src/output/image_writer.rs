@@ -0,0 +1,44 @@
+// writes a rendered film to disk: resolve the pixels, tonemap them, then encode
+
+use std::path::PathBuf;
+
+use crate::core::colour::Colour;
+use crate::core::film::{Film, Tile};
+use crate::error::Result;
+use crate::output::encoder::{encoder_for_path, Encoder};
+use crate::output::tonemap::{Identity, Tonemapper};
+
+/// Where and how the rendered image is written.
+pub struct ImageOutput {
+    pub path: PathBuf,
+    pub encoder: Box<dyn Encoder>,
+    pub tonemapper: Box<dyn Tonemapper>,
+}
+
+impl ImageOutput {
+    /// An output whose encoder is chosen by the file extension, with no tonemapping.
+    pub fn new(path: PathBuf, bit_depth: u8) -> Result<Self> {
+        let encoder = encoder_for_path(&path, bit_depth)?;
+        Ok(Self {
+            path,
+            encoder,
+            tonemapper: Box::new(Identity),
+        })
+    }
+
+    pub fn write(&self, film: &Film) -> Result<()> {
+        let mut pixels = film.resolve();
+        self.tonemapper.apply(&mut pixels);
+        self.encoder.encode(&pixels, film.width(), film.height(), &self.path)
+    }
+
+    /// Writes just the part `region` of an image `width` pixels wide, such as one eye's view from a stereo rig.
+    /// Takes the film's resolved pixels, so writing several regions only resolves it once.
+    pub fn write_region(&self, pixels: &[Colour], width: usize, region: &Tile) -> Result<()> {
+        let mut pixels: Vec<_> = pixels.chunks_exact(width).skip(region.y).take(region.height)
+            .flat_map(|row| row[region.x..region.x + region.width].iter().cloned())
+            .collect();
+        self.tonemapper.apply(&mut pixels);
+        self.encoder.encode(&pixels, region.width, region.height, &self.path)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod encoder;
+pub mod image_writer;
+pub mod tonemap;
\ No newline at end of file
@@ -0,0 +1,53 @@
+// tonemappers squash linear radiance into a displayable range before the image is encoded
+
+use crate::core::colour::Colour;
+use crate::error::{Error, Result};
+
+pub trait Tonemapper: Send + Sync {
+    fn map(&self, colour: &Colour) -> Colour;
+
+    fn apply(&self, pixels: &mut [Colour]) {
+        for pixel in pixels.iter_mut() {
+            *pixel = self.map(pixel);
+        }
+    }
+}
+
+/// Leaves the colour alone. It doesn't clamp anything: the PNG and PPM encoders clip to [0, 1] themselves,
+/// and the HDR formats keep the full range.
+pub struct Identity;
+
+impl Tonemapper for Identity {
+    fn map(&self, colour: &Colour) -> Colour {
+        colour.clone()
+    }
+}
+
+/// Reinhard's global operator, c / (1 + c), per channel.
+pub struct Reinhard;
+
+impl Tonemapper for Reinhard {
+    fn map(&self, colour: &Colour) -> Colour {
+        Colour(colour.0.map(|c| c / (1.0 + c)))
+    }
+}
+
+/// Krzysztof Narkowicz's fit of the ACES filmic curve.
+pub struct AcesFilmic;
+
+impl Tonemapper for AcesFilmic {
+    fn map(&self, colour: &Colour) -> Colour {
+        let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+        Colour(colour.0.map(|x| ((x*(a*x + b)) / (x*(c*x + d) + e)).clamp(0.0, 1.0)))
+    }
+}
+
+/// Looks a tonemapper up by the name used on the command line.
+pub fn tonemapper_from_name(name: &str) -> Result<Box<dyn Tonemapper>> {
+    match name {
+        "none" => Ok(Box::new(Identity)),
+        "reinhard" => Ok(Box::new(Reinhard)),
+        "aces" => Ok(Box::new(AcesFilmic)),
+        _ => Err(Error::InvalidSettings(format!("unknown tonemapper '{}' (expected none, reinhard or aces)", name))),
+    }
+}
@@ -0,0 +1,63 @@
+// the Halton sampler: dimension d is the radical inverse of the sample index in the d-th prime base,
+// with its digits scrambled differently for every pixel
+
+use std::sync::OnceLock;
+
+use crate::samplers::sampler::{hash_to_f32, SampleId, Sampler, ONE_MINUS_EPSILON};
+use crate::util::rng::splitmix64;
+
+// dimensions past this many primes fall back to independent random numbers
+const MAX_DIMENSIONS: usize = 1000;
+
+/// Halton points, with each pixel's copy of the sequence randomised by scrambling its digits (a random shift of each digit,
+/// chosen by the digits before it). Low dimensions use small bases and are spread very evenly; higher ones use larger bases,
+/// which need more samples before they fill in.
+pub struct Halton;
+
+impl Sampler for Halton {
+    fn name(&self) -> &'static str {
+        "halton"
+    }
+
+    fn get_1d(&self, id: &SampleId, dim: u32) -> f32 {
+        let hash = id.pixel_hash(&[dim as u64]);
+        let Some(&base) = primes().get(dim as usize) else {return hash_to_f32(splitmix64(hash ^ id.index as u64))};
+        return scrambled_radical_inverse(base as u64, id.index as u64, hash);
+    }
+
+    fn get_2d(&self, id: &SampleId, dim: u32) -> (f32, f32) {
+        (self.get_1d(id, dim), self.get_1d(id, dim + 1))
+    }
+}
+
+// the digits of `index` in `base`, mirrored about the radix point, with each digit shifted (mod base) by an amount hashed
+// from `hash` and the digits before it. It goes on past the index's last digit so the zeros after it are scrambled too.
+fn scrambled_radical_inverse(base: u64, mut index: u64, hash: u64) -> f32 {
+    let inv_base = 1.0 / base as f64;
+    let mut inv_base_n = 1.0;
+    let mut reversed: u64 = 0;
+    let mut digits = 0;
+    // enough digits to fill an f32
+    while inv_base_n > 1.0 / 16_777_216.0 {
+        let shift = splitmix64(hash ^ splitmix64(reversed ^ (digits << 56))) % base;
+        let digit = (index % base + shift) % base;
+        reversed = reversed * base + digit;
+        index /= base;
+        inv_base_n *= inv_base;
+        digits += 1;
+    }
+    return ((reversed as f64 * inv_base_n) as f32).min(ONE_MINUS_EPSILON);
+}
+
+fn primes() -> &'static [u32] {
+    static PRIMES: OnceLock<Vec<u32>> = OnceLock::new();
+    PRIMES.get_or_init(|| {
+        let mut primes: Vec<u32> = Vec::with_capacity(MAX_DIMENSIONS);
+        let mut candidate = 2;
+        while primes.len() < MAX_DIMENSIONS {
+            if primes.iter().take_while(|&&p| p * p <= candidate).all(|&p| candidate % p != 0) {primes.push(candidate)}
+            candidate += 1;
+        }
+        primes
+    })
+}
@@ -0,0 +1,20 @@
+// the independent sampler: every number is its own uniform random number, as with no sampler at all
+
+use crate::samplers::sampler::{hash_to_f32, SampleId, Sampler};
+
+/// Uniform random numbers with nothing spread out, for comparing the other samplers against.
+pub struct Independent;
+
+impl Sampler for Independent {
+    fn name(&self) -> &'static str {
+        "independent"
+    }
+
+    fn get_1d(&self, id: &SampleId, dim: u32) -> f32 {
+        hash_to_f32(id.pixel_hash(&[id.index as u64, dim as u64]))
+    }
+
+    fn get_2d(&self, id: &SampleId, dim: u32) -> (f32, f32) {
+        (self.get_1d(id, dim), self.get_1d(id, dim + 1))
+    }
+}
@@ -0,0 +1,165 @@
+// samplers pick the random numbers for camera samples. Rather than drawing each number independently they
+// spread a pixel's samples out evenly, dimension by dimension, so images converge faster at low sample counts
+
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::samplers::halton::Halton;
+use crate::samplers::independent::Independent;
+use crate::samplers::sobol::Sobol;
+use crate::samplers::stratified::Stratified;
+use crate::util::rng::splitmix64;
+
+/// Which camera sample a sampler is asked for: sample `index` of the `count` taken for pixel `pixel`,
+/// in a render seeded with `seed`.
+#[derive(Clone, Copy, Debug)]
+pub struct SampleId {
+    pub seed: u64,
+    pub pixel: u64,
+    pub index: u32,
+    pub count: u32,
+}
+
+impl SampleId {
+    /// A hash of the pixel and seed (but not the sample index) mixed with `values`, for samplers to randomise with.
+    pub fn pixel_hash(&self, values: &[u64]) -> u64 {
+        let mut hash = splitmix64(self.seed ^ splitmix64(self.pixel));
+        for value in values {
+            hash = splitmix64(hash ^ value);
+        }
+        return hash;
+    }
+}
+
+/// The numbers for each camera sample, one dimension at a time. A camera sample's `Rng` hands out dimension 0, 1, 2...
+/// in the order they're asked for: the pixel offset, then the lens, the time, and the numbers each bounce uses.
+/// The values for one dimension across a pixel's samples are what a sampler spreads out, and `get_2d` spreads a pair
+/// of dimensions out together, for things picked with two numbers (like a point in the pixel).
+pub trait Sampler: Send + Sync {
+    /// The name used on the command line and in scene files.
+    fn name(&self) -> &'static str;
+
+    /// Dimension `dim` of the sample, in [0, 1).
+    fn get_1d(&self, id: &SampleId, dim: u32) -> f32;
+
+    /// Dimensions `dim` and `dim + 1` of the sample, in [0, 1).
+    fn get_2d(&self, id: &SampleId, dim: u32) -> (f32, f32);
+
+    /// Whether a sample's numbers depend on how many samples its pixel takes (`SampleId::count`), so a render
+    /// can't be carried on to more samples per pixel than it was started with.
+    fn depends_on_count(&self) -> bool {
+        false
+    }
+}
+
+/// Looks a sampler up by the name used on the command line and in scene files.
+pub fn sampler_from_name(name: &str) -> Result<Arc<dyn Sampler>> {
+    match name {
+        "independent" => Ok(Arc::new(Independent)),
+        "stratified" => Ok(Arc::new(Stratified)),
+        "halton" => Ok(Arc::new(Halton)),
+        "sobol" => Ok(Arc::new(Sobol)),
+        _ => Err(Error::InvalidSettings(format!("unknown sampler '{}' (expected independent, stratified, halton or sobol)", name))),
+    }
+}
+
+/// The largest f32 below 1, for keeping samples that round up in range.
+pub const ONE_MINUS_EPSILON: f32 = 1.0 - f32::EPSILON / 2.0;
+
+/// A uniform float in [0, 1) from the top 24 bits of a hash.
+pub fn hash_to_f32(hash: u64) -> f32 {
+    (hash >> 40) as f32 * (1.0 / 16_777_216.0)
+}
+
+/// A random permutation of 0..n, picked by `seed`: the position `i` is moved to (Kensler, "Correlated Multi-Jittered
+/// Sampling"). Each step is invertible on the smallest power of two above n, and values past n are walked on until they land in range.
+pub fn permutation_element(mut i: u32, n: u32, seed: u32) -> u32 {
+    let mut w = n.wrapping_sub(1);
+    w |= w >> 1;
+    w |= w >> 2;
+    w |= w >> 4;
+    w |= w >> 8;
+    w |= w >> 16;
+    loop {
+        i ^= seed;
+        i = i.wrapping_mul(0xe170893d);
+        i ^= seed >> 16;
+        i ^= (i & w) >> 4;
+        i ^= seed >> 8;
+        i = i.wrapping_mul(0x0929eb3f);
+        i ^= seed >> 23;
+        i ^= (i & w) >> 1;
+        i = i.wrapping_mul(1 | seed >> 27);
+        i = i.wrapping_mul(0x6935fa69);
+        i ^= (i & w) >> 11;
+        i = i.wrapping_mul(0x74dcb303);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0x9e501cc3);
+        i ^= (i & w) >> 2;
+        i = i.wrapping_mul(0xc860a3df);
+        i &= w;
+        i ^= i >> 5;
+        if i < n {break}
+    }
+    return (i.wrapping_add(seed)) % n;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permutation_element_is_a_bijection() {
+        for n in [1, 3, 5, 6, 7, 10, 13, 100, 257, 1000] {
+            for seed in [0, 1, 0x9e3779b9, 0xdeadbeef] {
+                let mut seen = vec![false; n as usize];
+                for i in 0..n {
+                    let j = permutation_element(i, n, seed) as usize;
+                    assert!(j < n as usize && !seen[j], "{} is not a permutation of 0..{} with seed {}", i, n, seed);
+                    seen[j] = true;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn samples_stay_in_the_unit_interval() {
+        for name in ["independent", "stratified", "halton", "sobol"] {
+            let sampler = sampler_from_name(name).unwrap();
+            for count in [1, 7, 64] {
+                for index in 0..count {
+                    for pixel in 0..8 {
+                        let id = SampleId {seed: 5, pixel, index, count};
+                        for dim in 0..12 {
+                            let x = sampler.get_1d(&id, dim);
+                            let (y, z) = sampler.get_2d(&id, dim);
+                            for value in [x, y, z] {
+                                assert!((0.0..1.0).contains(&value), "{} gave {} for {:?} dimension {}", name, value, id, dim);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn hash_to_f32_stays_below_one() {
+        assert_eq!(hash_to_f32(0), 0.0);
+        assert!(hash_to_f32(u64::MAX) < 1.0);
+    }
+
+    #[test]
+    fn names_round_trip() {
+        for name in ["independent", "stratified", "halton", "sobol"] {
+            assert_eq!(sampler_from_name(name).unwrap().name(), name);
+        }
+    }
+
+    #[test]
+    fn unknown_names_are_invalid_settings() {
+        for name in ["", "Sobol", "random"] {
+            assert!(matches!(sampler_from_name(name), Err(Error::InvalidSettings(_))), "'{}' was accepted", name);
+        }
+    }
+}
@@ -0,0 +1,93 @@
+// the Sobol sampler: the first two dimensions of the Sobol sequence, Owen scrambled, with a differently
+// scrambled and shuffled copy for each pair of dimensions (Burley, "Practical Hash-based Owen Scrambling")
+
+use crate::samplers::sampler::{SampleId, Sampler, ONE_MINUS_EPSILON};
+
+/// Owen-scrambled Sobol points. A pixel's first 2^k samples always cover each pair of dimensions evenly (a (0, k, 2)-net),
+/// so it converges fastest with a power of two samples per pixel. Each pair of dimensions gets its own random
+/// scramble and order, which keeps the pairs from lining up with each other.
+pub struct Sobol;
+
+impl Sampler for Sobol {
+    fn name(&self) -> &'static str {
+        "sobol"
+    }
+
+    fn get_1d(&self, id: &SampleId, dim: u32) -> f32 {
+        let hash = id.pixel_hash(&[dim as u64]);
+        let index = nested_uniform_scramble(id.index, hash as u32);
+        let x = nested_uniform_scramble(index.reverse_bits(), (hash >> 32) as u32);
+        return to_f32(x);
+    }
+
+    fn get_2d(&self, id: &SampleId, dim: u32) -> (f32, f32) {
+        let hash = id.pixel_hash(&[dim as u64, 2]);
+        let index = nested_uniform_scramble(id.index, hash as u32);
+        let (x, y) = sobol_2d(index);
+        let y_hash = id.pixel_hash(&[dim as u64, 3]) as u32;
+        return (to_f32(nested_uniform_scramble(x, (hash >> 32) as u32)), to_f32(nested_uniform_scramble(y, y_hash)));
+    }
+}
+
+// point `index` of the first two dimensions of the Sobol sequence, as 32 bit fractions. The first is the index's bits
+// reversed (van der Corput); the second's direction numbers come from the polynomial x + 1, each one the last xor itself shifted down one
+fn sobol_2d(mut index: u32) -> (u32, u32) {
+    let x = index.reverse_bits();
+    let mut y = 0;
+    let mut direction = 1 << 31;
+    while index != 0 {
+        if index & 1 != 0 {y ^= direction}
+        index >>= 1;
+        direction ^= direction >> 1;
+    }
+    return (x, y);
+}
+
+// Owen scrambling of a 32 bit fraction: every bit is flipped or not depending on the bits above it, as a random
+// function of `seed`. Laine and Karras' hash does this for the bits below instead, so it's applied to the bits reversed.
+fn nested_uniform_scramble(x: u32, seed: u32) -> u32 {
+    return laine_karras_permutation(x.reverse_bits(), seed).reverse_bits();
+}
+
+// multiplying by an even number only carries upwards, so each bit only depends on the bits below it
+fn laine_karras_permutation(mut x: u32, seed: u32) -> u32 {
+    x = x.wrapping_add(seed);
+    x ^= x.wrapping_mul(0x6c50b47c);
+    x ^= x.wrapping_mul(0xb82f1e52);
+    x ^= x.wrapping_mul(0xc7afe638);
+    x ^= x.wrapping_mul(0x8d22f6e6);
+    return x;
+}
+
+fn to_f32(x: u32) -> f32 {
+    ((x >> 8) as f32 * (1.0 / 16_777_216.0)).min(ONE_MINUS_EPSILON)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // each elementary interval of a (0, k, 2)-net (2^a by 2^(k - a) equal boxes over the unit square) holds one point
+    #[test]
+    fn first_power_of_two_points_fill_each_elementary_interval_once() {
+        for k in 0..=8 {
+            let count = 1u32 << k;
+            for pixel in 0..4 {
+                for dim in [0, 2, 5] {
+                    let points: Vec<(f32, f32)> = (0..count)
+                        .map(|index| Sobol.get_2d(&SampleId {seed: 7, pixel, index, count}, dim))
+                        .collect();
+                    for a in 0..=k {
+                        let (columns, rows) = (1u32 << a, 1u32 << (k - a));
+                        let mut filled = vec![false; count as usize];
+                        for &(x, y) in &points {
+                            let cell = ((y * rows as f32) as u32 * columns + (x * columns as f32) as u32) as usize;
+                            assert!(!filled[cell], "two points in one of the {}x{} intervals with {} points", columns, rows, count);
+                            filled[cell] = true;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,59 @@
+// the stratified (jittered) sampler: each dimension is cut into as many strata as there are samples,
+// and every sample takes a random point in a different one
+
+use crate::samplers::sampler::{hash_to_f32, permutation_element, SampleId, Sampler, ONE_MINUS_EPSILON};
+
+/// Jittered stratified sampling. One dimension is cut into `count` equal strata; pairs of dimensions into a grid of
+/// about sqrt(count) by sqrt(count) cells (some left empty if count isn't square). Which sample gets which stratum is
+/// shuffled differently in each dimension and pixel, so the dimensions don't line up with each other.
+pub struct Stratified;
+
+impl Sampler for Stratified {
+    fn name(&self) -> &'static str {
+        "stratified"
+    }
+
+    fn depends_on_count(&self) -> bool {
+        true
+    }
+
+    fn get_1d(&self, id: &SampleId, dim: u32) -> f32 {
+        let count = id.count.max(1);
+        let stratum = permutation_element(id.index % count, count, id.pixel_hash(&[dim as u64]) as u32);
+        let jitter = hash_to_f32(id.pixel_hash(&[id.index as u64, dim as u64, 1]));
+        return ((stratum as f32 + jitter) / count as f32).min(ONE_MINUS_EPSILON);
+    }
+
+    fn get_2d(&self, id: &SampleId, dim: u32) -> (f32, f32) {
+        let count = id.count.max(1);
+        let columns = (count as f32).sqrt().ceil() as u32;
+        let rows = count.div_ceil(columns);
+        let cell = permutation_element(id.index % count, columns * rows, id.pixel_hash(&[dim as u64, 2]) as u32);
+
+        let jitter_x = hash_to_f32(id.pixel_hash(&[id.index as u64, dim as u64, 3]));
+        let jitter_y = hash_to_f32(id.pixel_hash(&[id.index as u64, dim as u64, 4]));
+        let x = ((cell % columns) as f32 + jitter_x) / columns as f32;
+        let y = ((cell / columns) as f32 + jitter_y) / rows as f32;
+        return (x.min(ONE_MINUS_EPSILON), y.min(ONE_MINUS_EPSILON));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_1d_puts_one_sample_in_each_stratum() {
+        for count in [1, 2, 5, 16, 33] {
+            for dim in 0..4 {
+                let mut filled = vec![false; count as usize];
+                for index in 0..count {
+                    let x = Stratified.get_1d(&SampleId {seed: 3, pixel: 11, index, count}, dim);
+                    let stratum = (x * count as f32) as usize;
+                    assert!(!filled[stratum], "two samples in stratum {} of {}", stratum, count);
+                    filled[stratum] = true;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,863 @@
+// loads scenes from TOML files, so scenes can be written without touching any Rust
+//
+// a scene file has a [camera] table, named [textures.*] and [materials.*] tables, and an
+// [[objects]] array. See the files in the scenes/ folder for examples.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use nalgebra::{Point3, Vector3};
+use serde::Deserialize;
+use toml::Spanned;
+
+use crate::apertures::aperture::{aperture_from_blades, Aperture};
+use crate::apertures::circular::CircularAperture;
+use crate::apertures::image_aperture::ImageAperture;
+use crate::core::camera::Camera;
+use crate::core::colour::Colour;
+use crate::core::physical_camera::PhysicalCamera;
+use crate::core::projection::projection_from_name;
+use crate::core::shutter::shutter_curve_from_name;
+use crate::core::stereo::{stereo_layout_from_name, StereoRig};
+use crate::error::Result;
+use crate::filters::filter::filter_from_name;
+use crate::geometry::bvh::BVHNode;
+use crate::geometry::hittable::Hittable;
+use crate::geometry::hittable_list::HittableList;
+use crate::geometry::quad::{make_box, Quad};
+use crate::geometry::sphere::Sphere;
+use crate::geometry::triangle::Triangle;
+use crate::integrators::integrator::integrator_from_name;
+use crate::loaders::obj::load_obj_meshes;
+use crate::materials::dielectric::Dielectric;
+use crate::materials::diffuse_light::DiffuseLight;
+use crate::materials::isotropic::Isotropic;
+use crate::materials::lambertian::Lambertian;
+use crate::materials::material::Material;
+use crate::materials::metal::Metal;
+use crate::samplers::sampler::sampler_from_name;
+use crate::scenes::Scene;
+use crate::textures::checkered_texture::CheckerTexture;
+use crate::textures::image_texture::ImageTexture;
+use crate::textures::noise_texture::NoiseTexture;
+use crate::textures::rtw_image::RTWImage;
+use crate::textures::solid_colour::SolidColour;
+use crate::textures::texture::Texture;
+use crate::transforms::rotate_y::RotateY;
+use crate::transforms::translate::Translate;
+use crate::util::rng::Rng;
+use crate::volumes::constant_medium::ConstantMedium;
+
+/// An error in a scene file, with the (1-based) line and column it was found at.
+#[derive(Debug)]
+pub struct SceneFileError {
+    pub path: Option<PathBuf>,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl SceneFileError {
+    fn at(source: &str, offset: usize, message: String) -> Self {
+        let before = &source[..offset.min(source.len())];
+        let line = before.matches('\n').count() + 1;
+        let column = before.chars().rev().take_while(|&c| c != '\n').count() + 1;
+        Self {
+            path: None,
+            line,
+            column,
+            message,
+        }
+    }
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(path) = &self.path {
+            write!(f, "{}: ", path.display())?;
+        }
+        write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+    }
+}
+
+impl std::error::Error for SceneFileError {}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SceneDesc {
+    #[serde(default)]
+    camera: CameraDesc,
+    #[serde(default)]
+    textures: HashMap<String, Spanned<TextureTable>>,
+    #[serde(default)]
+    materials: HashMap<String, Spanned<MaterialTable>>,
+    #[serde(default)]
+    objects: Vec<Spanned<ObjectTable>>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+struct CameraDesc {
+    aspect_ratio: Option<f32>,
+    image_width: Option<f32>,
+    samples_per_pixel: Option<u32>,
+    adaptive_threshold: Option<f32>,
+    min_samples_per_pixel: Option<u32>,
+    samples_per_pass: Option<u32>,
+    max_depth: Option<u32>,
+    russian_roulette_depth: Option<u32>,
+    background: Option<[f32; 3]>,
+    vfov: Option<u32>,
+    projection: Option<Spanned<String>>,
+    ortho_height: Option<f32>,
+    lookfrom: Option<[f32; 3]>,
+    lookat: Option<[f32; 3]>,
+    vup: Option<[f32; 3]>,
+    lookfrom_end: Option<[f32; 3]>,
+    lookat_end: Option<[f32; 3]>,
+    vup_end: Option<[f32; 3]>,
+    shutter_open: Option<f32>,
+    shutter_close: Option<f32>,
+    shutter_curve: Option<Spanned<String>>,
+    defocus_angle: Option<f32>,
+    focus_dist: Option<f32>,
+    aperture: Option<Spanned<ApertureTable>>,
+    cat_eye: Option<f32>,
+    physical: Option<PhysicalDesc>,
+    stereo: Option<StereoDesc>,
+    integrator: Option<Spanned<String>>,
+    filter: Option<Spanned<String>>,
+    filter_radius: Option<Spanned<f32>>,
+    sampler: Option<Spanned<String>>,
+    photons: Option<u32>,
+    photon_radius: Option<f32>,
+}
+
+/// Either an inline `[r, g, b]` colour or the name of a texture from the [textures] table.
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+enum TextureRef {
+    Colour([f32; 3]),
+    Named(String),
+}
+
+/// A physical camera's settings; any left out take `PhysicalCamera::new`'s.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PhysicalDesc {
+    focal_length: Option<f32>,
+    f_number: Option<f32>,
+    shutter_speed: Option<f32>,
+    iso: Option<f32>,
+    sensor_width: Option<f32>,
+    metres_per_unit: Option<f32>,
+}
+
+/// A stereo rig's settings; any left out take `StereoRig::new`'s.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StereoDesc {
+    interocular: Option<f32>,
+    convergence: Option<f32>,
+    layout: Option<Spanned<String>>,
+}
+
+// tables with a `type` key are read into a struct with every key any of their types take, and then checked against
+// the keys their type takes. serde's internally tagged enums would have to buffer the whole table to find its type
+// first, and errors would then point at the start of the table rather than at the value that's wrong.
+
+type FileResult<T> = std::result::Result<T, SceneFileError>;
+
+// a table's `type` and where its keys' values are, for checking them against the ones its type takes
+struct Keys<'a> {
+    source: &'a str,
+    kind: &'a Spanned<String>,
+    present: Vec<(&'static str, Range<usize>)>,
+}
+
+impl<'a> Keys<'a> {
+    fn new(source: &'a str, kind: &'a Spanned<String>, present: Vec<Option<(&'static str, Range<usize>)>>) -> Self {
+        Self {source, kind, present: present.into_iter().flatten().collect()}
+    }
+
+    // errors on the first key the table's type doesn't take
+    fn allow(&self, allowed: &[&str]) -> FileResult<()> {
+        for (key, span) in &self.present {
+            if !allowed.contains(key) {
+                return Err(SceneFileError::at(self.source, span.start, format!("a {} doesn't take `{}`", self.kind.get_ref(), key)));
+            }
+        }
+        Ok(())
+    }
+
+    fn required<'v, T>(&self, key: &str, value: &'v Option<Spanned<T>>) -> FileResult<&'v T> {
+        value.as_ref().map(|value| value.get_ref())
+            .ok_or_else(|| SceneFileError::at(self.source, self.kind.span().start, format!("a {} needs `{}`", self.kind.get_ref(), key)))
+    }
+
+    fn unknown_type(&self, what: &str, expected: &str) -> SceneFileError {
+        SceneFileError::at(self.source, self.kind.span().start, format!("unknown {} type '{}' (expected {})", what, self.kind.get_ref(), expected))
+    }
+}
+
+fn key<T>(name: &'static str, value: &Option<Spanned<T>>) -> Option<(&'static str, Range<usize>)> {
+    value.as_ref().map(|value| (name, value.span()))
+}
+
+fn optional<T: Clone>(value: &Option<Spanned<T>>) -> Option<T> {
+    value.as_ref().map(|value| value.get_ref().clone())
+}
+
+/// The shape of the camera's aperture, for its defocus blur.
+enum ApertureDesc {
+    Circle,
+    Polygon { blades: u32, rotation: Option<f32> },
+    Image { filename: String },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ApertureTable {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    blades: Option<Spanned<u32>>,
+    rotation: Option<Spanned<f32>>,
+    filename: Option<Spanned<String>>,
+}
+
+impl ApertureTable {
+    fn desc(&self, source: &str) -> FileResult<ApertureDesc> {
+        let keys = Keys::new(source, &self.kind, vec![key("blades", &self.blades), key("rotation", &self.rotation), key("filename", &self.filename)]);
+        match self.kind.get_ref().as_str() {
+            "circle" => {
+                keys.allow(&[])?;
+                Ok(ApertureDesc::Circle)
+            }
+            "polygon" => {
+                keys.allow(&["blades", "rotation"])?;
+                Ok(ApertureDesc::Polygon { blades: *keys.required("blades", &self.blades)?, rotation: optional(&self.rotation) })
+            }
+            "image" => {
+                keys.allow(&["filename"])?;
+                Ok(ApertureDesc::Image { filename: keys.required("filename", &self.filename)?.clone() })
+            }
+            _ => Err(keys.unknown_type("aperture", "circle, polygon or image")),
+        }
+    }
+}
+
+enum TextureDesc {
+    Solid { colour: [f32; 3] },
+    Checker { scale: f32, even: TextureRef, odd: TextureRef },
+    Image { filename: String },
+    Noise { scale: f32 },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TextureTable {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    colour: Option<Spanned<[f32; 3]>>,
+    scale: Option<Spanned<f32>>,
+    even: Option<Spanned<TextureRef>>,
+    odd: Option<Spanned<TextureRef>>,
+    filename: Option<Spanned<String>>,
+}
+
+impl TextureTable {
+    fn desc(&self, source: &str) -> FileResult<TextureDesc> {
+        let keys = Keys::new(source, &self.kind, vec![
+            key("colour", &self.colour), key("scale", &self.scale), key("even", &self.even), key("odd", &self.odd), key("filename", &self.filename),
+        ]);
+        match self.kind.get_ref().as_str() {
+            "solid" => {
+                keys.allow(&["colour"])?;
+                Ok(TextureDesc::Solid { colour: *keys.required("colour", &self.colour)? })
+            }
+            "checker" => {
+                keys.allow(&["scale", "even", "odd"])?;
+                Ok(TextureDesc::Checker {
+                    scale: *keys.required("scale", &self.scale)?,
+                    even: keys.required("even", &self.even)?.clone(),
+                    odd: keys.required("odd", &self.odd)?.clone(),
+                })
+            }
+            "image" => {
+                keys.allow(&["filename"])?;
+                Ok(TextureDesc::Image { filename: keys.required("filename", &self.filename)?.clone() })
+            }
+            "noise" => {
+                keys.allow(&["scale"])?;
+                Ok(TextureDesc::Noise { scale: *keys.required("scale", &self.scale)? })
+            }
+            _ => Err(keys.unknown_type("texture", "solid, checker, image or noise")),
+        }
+    }
+}
+
+enum MaterialDesc {
+    Lambertian { albedo: TextureRef },
+    Metal { albedo: [f32; 3], fuzz: f32 },
+    Dielectric { refraction_index: f32 },
+    DiffuseLight { emit: TextureRef },
+    Isotropic { albedo: TextureRef },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct MaterialTable {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    albedo: Option<Spanned<TextureRef>>,
+    fuzz: Option<Spanned<f32>>,
+    refraction_index: Option<Spanned<f32>>,
+    emit: Option<Spanned<TextureRef>>,
+}
+
+impl MaterialTable {
+    fn desc(&self, source: &str) -> FileResult<MaterialDesc> {
+        let keys = Keys::new(source, &self.kind, vec![
+            key("albedo", &self.albedo), key("fuzz", &self.fuzz), key("refraction_index", &self.refraction_index), key("emit", &self.emit),
+        ]);
+        match self.kind.get_ref().as_str() {
+            "lambertian" => {
+                keys.allow(&["albedo"])?;
+                Ok(MaterialDesc::Lambertian { albedo: keys.required("albedo", &self.albedo)?.clone() })
+            }
+            "metal" => {
+                keys.allow(&["albedo", "fuzz"])?;
+                let TextureRef::Colour(albedo) = keys.required("albedo", &self.albedo)? else {
+                    let offset = self.albedo.as_ref().map_or(0, |albedo| albedo.span().start);
+                    return Err(SceneFileError::at(source, offset, "a metal's albedo must be an [r, g, b] colour".to_string()));
+                };
+                Ok(MaterialDesc::Metal { albedo: *albedo, fuzz: optional(&self.fuzz).unwrap_or(0.0) })
+            }
+            "dielectric" => {
+                keys.allow(&["refraction_index"])?;
+                Ok(MaterialDesc::Dielectric { refraction_index: *keys.required("refraction_index", &self.refraction_index)? })
+            }
+            "diffuse_light" => {
+                keys.allow(&["emit"])?;
+                Ok(MaterialDesc::DiffuseLight { emit: keys.required("emit", &self.emit)?.clone() })
+            }
+            "isotropic" => {
+                keys.allow(&["albedo"])?;
+                Ok(MaterialDesc::Isotropic { albedo: keys.required("albedo", &self.albedo)?.clone() })
+            }
+            _ => Err(keys.unknown_type("material", "lambertian, metal, dielectric, diffuse_light or isotropic")),
+        }
+    }
+}
+
+struct ObjectDesc {
+    shape: ShapeDesc,
+    /// Applied in order, so `[{ rotate_y = 15.0 }, { translate = [...] }]` rotates first.
+    transforms: Vec<TransformDesc>,
+}
+
+enum ShapeDesc {
+    Sphere { center: [f32; 3], center2: Option<[f32; 3]>, radius: f32, material: String },
+    Quad { q: [f32; 3], u: [f32; 3], v: [f32; 3], material: String },
+    Triangle { a: [f32; 3], b: [f32; 3], c: [f32; 3], material: String },
+    Box { a: [f32; 3], b: [f32; 3], material: String },
+    Obj { filename: String, material: Option<String> },
+    Group { objects: Vec<ObjectDesc> },
+    ConstantMedium { boundary: Box<ObjectDesc>, density: f32, albedo: TextureRef },
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ObjectTable {
+    #[serde(rename = "type")]
+    kind: Spanned<String>,
+    center: Option<Spanned<[f32; 3]>>,
+    center2: Option<Spanned<[f32; 3]>>,
+    radius: Option<Spanned<f32>>,
+    q: Option<Spanned<[f32; 3]>>,
+    u: Option<Spanned<[f32; 3]>>,
+    v: Option<Spanned<[f32; 3]>>,
+    a: Option<Spanned<[f32; 3]>>,
+    b: Option<Spanned<[f32; 3]>>,
+    c: Option<Spanned<[f32; 3]>>,
+    filename: Option<Spanned<String>>,
+    material: Option<Spanned<String>>,
+    objects: Option<Spanned<Vec<ObjectTable>>>,
+    boundary: Option<Spanned<Box<ObjectTable>>>,
+    density: Option<Spanned<f32>>,
+    albedo: Option<Spanned<TextureRef>>,
+    #[serde(default)]
+    transforms: Vec<TransformDesc>,
+}
+
+impl ObjectTable {
+    fn desc(&self, source: &str) -> FileResult<ObjectDesc> {
+        let keys = Keys::new(source, &self.kind, vec![
+            key("center", &self.center), key("center2", &self.center2), key("radius", &self.radius),
+            key("q", &self.q), key("u", &self.u), key("v", &self.v), key("a", &self.a), key("b", &self.b), key("c", &self.c),
+            key("filename", &self.filename), key("material", &self.material), key("objects", &self.objects),
+            key("boundary", &self.boundary), key("density", &self.density), key("albedo", &self.albedo),
+        ]);
+        let shape = match self.kind.get_ref().as_str() {
+            "sphere" => {
+                keys.allow(&["center", "center2", "radius", "material"])?;
+                ShapeDesc::Sphere {
+                    center: *keys.required("center", &self.center)?,
+                    center2: optional(&self.center2),
+                    radius: *keys.required("radius", &self.radius)?,
+                    material: keys.required("material", &self.material)?.clone(),
+                }
+            }
+            "quad" => {
+                keys.allow(&["q", "u", "v", "material"])?;
+                ShapeDesc::Quad {
+                    q: *keys.required("q", &self.q)?,
+                    u: *keys.required("u", &self.u)?,
+                    v: *keys.required("v", &self.v)?,
+                    material: keys.required("material", &self.material)?.clone(),
+                }
+            }
+            "triangle" => {
+                keys.allow(&["a", "b", "c", "material"])?;
+                ShapeDesc::Triangle {
+                    a: *keys.required("a", &self.a)?,
+                    b: *keys.required("b", &self.b)?,
+                    c: *keys.required("c", &self.c)?,
+                    material: keys.required("material", &self.material)?.clone(),
+                }
+            }
+            "box" => {
+                keys.allow(&["a", "b", "material"])?;
+                ShapeDesc::Box {
+                    a: *keys.required("a", &self.a)?,
+                    b: *keys.required("b", &self.b)?,
+                    material: keys.required("material", &self.material)?.clone(),
+                }
+            }
+            "obj" => {
+                keys.allow(&["filename", "material"])?;
+                ShapeDesc::Obj { filename: keys.required("filename", &self.filename)?.clone(), material: optional(&self.material) }
+            }
+            "group" => {
+                keys.allow(&["objects"])?;
+                let objects = keys.required("objects", &self.objects)?.iter().map(|object| object.desc(source)).collect::<FileResult<_>>()?;
+                ShapeDesc::Group { objects }
+            }
+            "constant_medium" => {
+                keys.allow(&["boundary", "density", "albedo"])?;
+                ShapeDesc::ConstantMedium {
+                    boundary: Box::new(keys.required("boundary", &self.boundary)?.desc(source)?),
+                    density: *keys.required("density", &self.density)?,
+                    albedo: keys.required("albedo", &self.albedo)?.clone(),
+                }
+            }
+            _ => return Err(keys.unknown_type("object", "sphere, quad, triangle, box, obj, group or constant_medium")),
+        };
+        Ok(ObjectDesc { shape, transforms: self.transforms.clone() })
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "snake_case", deny_unknown_fields)]
+enum TransformDesc {
+    RotateY(f32),
+    Translate([f32; 3]),
+}
+
+/// Reads and builds the scene described by the TOML file at `path`, drawing any random
+/// texture state (Perlin noise) from `rng`. OBJ models are looked for relative to the file's directory.
+pub fn load_scene<P: AsRef<Path>>(path: P, rng: &mut Rng) -> Result<Scene> {
+    let path = path.as_ref();
+    let source = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("could not read scene file '{}': {}", path.display(), e)))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let scene = parse_scene_in(&source, dir, rng).map_err(|mut e| {
+        e.path = Some(path.to_path_buf());
+        e
+    })?;
+    Ok(scene)
+}
+
+/// Builds a scene from the contents of a TOML scene file, with OBJ models relative to the working directory.
+pub fn parse_scene(source: &str, rng: &mut Rng) -> std::result::Result<Scene, SceneFileError> {
+    parse_scene_in(source, Path::new(""), rng)
+}
+
+/// Builds a scene from the contents of a TOML scene file, with OBJ models relative to `dir`.
+pub fn parse_scene_in(source: &str, dir: &Path, rng: &mut Rng) -> std::result::Result<Scene, SceneFileError> {
+    let desc: SceneDesc = toml::from_str(source).map_err(|e| {
+        let offset = e.span().map_or(0, |span| span.start);
+        SceneFileError::at(source, offset, e.message().to_string())
+    })?;
+
+    let texture_descs = desc.textures.iter()
+        .map(|(name, texture)| Ok((name.clone(), Spanned::new(texture.span(), texture.get_ref().desc(source)?))))
+        .collect::<FileResult<HashMap<_, _>>>()?;
+    let mut builder = Builder {
+        source,
+        dir,
+        texture_descs: &texture_descs,
+        textures: HashMap::new(),
+        materials: HashMap::new(),
+        rng,
+    };
+
+    for (name, mat) in &desc.materials {
+        let built = builder.build_material(&mat.get_ref().desc(source)?)
+            .map_err(|msg| SceneFileError::at(source, mat.span().start, format!("in material '{}': {}", name, msg)))?;
+        builder.materials.insert(name.clone(), built);
+    }
+
+    let mut world = HittableList::new();
+    let mut lights = HittableList::new();
+    for object in &desc.objects {
+        let (built, light) = builder.build_object(&object.get_ref().desc(source)?)
+            .map_err(|msg| SceneFileError::at(source, object.span().start, msg))?;
+        world.add(built);
+        if let Some(light) = light {lights.add(light)}
+    }
+    let world_bbox = BVHNode::from_hittable_list(world)
+        .map_err(|e| SceneFileError::at(source, source.len(), e.to_string()))?;
+    let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
+
+    Ok(Scene { world: sync_world, lights, camera: build_camera(source, &desc.camera)? })
+}
+
+fn point(p: [f32; 3]) -> Point3<f32> {
+    Point3::new(p[0], p[1], p[2])
+}
+
+fn vector(v: [f32; 3]) -> Vector3<f32> {
+    Vector3::new(v[0], v[1], v[2])
+}
+
+fn colour(c: [f32; 3]) -> Colour {
+    Colour::new_from(c[0], c[1], c[2])
+}
+
+fn apply_transform(transform: &TransformDesc, object: Box<dyn Hittable>) -> Box<dyn Hittable> {
+    match transform {
+        TransformDesc::RotateY(angle) => Box::new(RotateY::new(object, *angle)),
+        TransformDesc::Translate(offset) => Box::new(Translate::new(object, vector(*offset))),
+    }
+}
+
+fn build_camera(source: &str, desc: &CameraDesc) -> std::result::Result<Camera, SceneFileError> {
+    let mut cam = Camera::new();
+
+    if let Some(aspect_ratio) = desc.aspect_ratio {cam.aspect_ratio = aspect_ratio}
+    if let Some(image_width) = desc.image_width {cam.image_width = image_width}
+    if let Some(samples_per_pixel) = desc.samples_per_pixel {cam.samples_per_pixel = samples_per_pixel}
+    if let Some(adaptive_threshold) = desc.adaptive_threshold {cam.adaptive_threshold = adaptive_threshold}
+    if let Some(min_samples_per_pixel) = desc.min_samples_per_pixel {cam.min_samples_per_pixel = min_samples_per_pixel}
+    if let Some(samples_per_pass) = desc.samples_per_pass {cam.samples_per_pass = samples_per_pass}
+    if let Some(max_depth) = desc.max_depth {cam.max_depth = max_depth}
+    if let Some(russian_roulette_depth) = desc.russian_roulette_depth {cam.russian_roulette_depth = russian_roulette_depth}
+    if let Some(background) = desc.background {cam.background = colour(background)}
+    if let Some(vfov) = desc.vfov {cam.vfov = vfov}
+    if let Some(projection) = &desc.projection {
+        cam.projection = projection_from_name(projection.get_ref())
+            .map_err(|e| SceneFileError::at(source, projection.span().start, e.to_string()))?;
+    }
+    if let Some(ortho_height) = desc.ortho_height {cam.ortho_height = ortho_height}
+    if let Some(lookfrom) = desc.lookfrom {cam.lookfrom = point(lookfrom)}
+    if let Some(lookat) = desc.lookat {cam.lookat = point(lookat)}
+    if let Some(vup) = desc.vup {cam.vup = vector(vup)}
+    if let Some(lookfrom_end) = desc.lookfrom_end {cam.lookfrom_end = Some(point(lookfrom_end))}
+    if let Some(lookat_end) = desc.lookat_end {cam.lookat_end = Some(point(lookat_end))}
+    if let Some(vup_end) = desc.vup_end {cam.vup_end = Some(vector(vup_end))}
+    if let Some(shutter_open) = desc.shutter_open {cam.shutter_open = shutter_open}
+    if let Some(shutter_close) = desc.shutter_close {cam.shutter_close = shutter_close}
+    if let Some(shutter_curve) = &desc.shutter_curve {
+        cam.shutter_curve = shutter_curve_from_name(shutter_curve.get_ref())
+            .map_err(|e| SceneFileError::at(source, shutter_curve.span().start, e.to_string()))?;
+    }
+    if let Some(defocus_angle) = desc.defocus_angle {cam.defocus_angle = defocus_angle}
+    if let Some(focus_dist) = desc.focus_dist {cam.focus_dist = focus_dist}
+    if let Some(aperture) = &desc.aperture {
+        let built = match aperture.get_ref().desc(source)? {
+            ApertureDesc::Circle => Ok(Box::new(CircularAperture) as Box<dyn Aperture>),
+            ApertureDesc::Polygon { blades, rotation } => aperture_from_blades(blades, rotation.unwrap_or(0.0)),
+            ApertureDesc::Image { filename } => ImageAperture::load(&filename).map(|image| Box::new(image) as Box<dyn Aperture>),
+        };
+        cam.aperture = built.map_err(|e| SceneFileError::at(source, aperture.span().start, e.to_string()))?;
+    }
+    if let Some(cat_eye) = desc.cat_eye {cam.cat_eye = cat_eye}
+    if let Some(desc) = &desc.physical {
+        let mut physical = PhysicalCamera::new();
+        if let Some(focal_length) = desc.focal_length {physical.focal_length = focal_length}
+        if let Some(f_number) = desc.f_number {physical.f_number = f_number}
+        if let Some(shutter_speed) = desc.shutter_speed {physical.shutter_speed = shutter_speed}
+        if let Some(iso) = desc.iso {physical.iso = iso}
+        if let Some(sensor_width) = desc.sensor_width {physical.sensor_width = sensor_width}
+        if let Some(metres_per_unit) = desc.metres_per_unit {physical.metres_per_unit = metres_per_unit}
+        cam.physical = Some(physical);
+    }
+    if let Some(desc) = &desc.stereo {
+        let mut stereo = StereoRig::new();
+        if let Some(interocular) = desc.interocular {stereo.interocular = interocular}
+        if let Some(convergence) = desc.convergence {stereo.convergence = convergence}
+        if let Some(layout) = &desc.layout {
+            stereo.layout = stereo_layout_from_name(layout.get_ref())
+                .map_err(|e| SceneFileError::at(source, layout.span().start, e.to_string()))?;
+        }
+        cam.stereo = Some(stereo);
+    }
+    if let Some(integrator) = &desc.integrator {
+        cam.integrator = integrator_from_name(integrator.get_ref())
+            .map_err(|e| SceneFileError::at(source, integrator.span().start, e.to_string()))?;
+    }
+    if desc.filter.is_some() || desc.filter_radius.is_some() {
+        let radius = desc.filter_radius.as_ref().map(|radius| *radius.get_ref());
+        let (name, offset) = match &desc.filter {
+            Some(filter) => (filter.get_ref().as_str(), filter.span().start),
+            None => (cam.filter.name(), desc.filter_radius.as_ref().map_or(0, |radius| radius.span().start)),
+        };
+        cam.filter = filter_from_name(name, radius).map_err(|e| SceneFileError::at(source, offset, e.to_string()))?;
+    }
+    if let Some(sampler) = &desc.sampler {
+        cam.sampler = sampler_from_name(sampler.get_ref())
+            .map_err(|e| SceneFileError::at(source, sampler.span().start, e.to_string()))?;
+    }
+    if let Some(photons) = desc.photons {cam.photons = photons}
+    if let Some(photon_radius) = desc.photon_radius {cam.photon_radius = photon_radius}
+
+    Ok(cam)
+}
+
+// errors while building are plain messages; the caller adds where in the file they came from
+type BuildResult<T> = std::result::Result<T, String>;
+
+// a built object, and the parts of it that are lights (with the same transforms) for the camera to sample
+type BuiltObject = (Box<dyn Hittable>, Option<Box<dyn Hittable>>);
+
+struct Builder<'a> {
+    source: &'a str,
+    // where the scene file is, for finding the files it refers to
+    dir: &'a Path,
+    texture_descs: &'a HashMap<String, Spanned<TextureDesc>>,
+    // textures are built on first use, so they can refer to each other in any order
+    textures: HashMap<String, Box<dyn Texture>>,
+    materials: HashMap<String, Box<dyn Material>>,
+    rng: &'a mut Rng,
+}
+
+impl Builder<'_> {
+    fn texture(&mut self, tex: &TextureRef) -> BuildResult<Box<dyn Texture>> {
+        self.texture_chain(tex, &mut Vec::new())
+    }
+
+    fn texture_chain(&mut self, tex: &TextureRef, chain: &mut Vec<String>) -> BuildResult<Box<dyn Texture>> {
+        let name = match tex {
+            TextureRef::Colour(c) => return Ok(Box::new(SolidColour::new_from_colour(colour(*c)))),
+            TextureRef::Named(name) => name,
+        };
+
+        if let Some(built) = self.textures.get(name) {
+            return Ok(built.clone_box());
+        }
+        if chain.contains(name) {
+            return Err(format!("texture '{}' refers to itself", name));
+        }
+        let Some(desc) = self.texture_descs.get(name) else {
+            return Err(format!("unknown texture '{}'", name));
+        };
+
+        chain.push(name.clone());
+        let built: Box<dyn Texture> = match desc.get_ref() {
+            TextureDesc::Solid { colour: c } => Box::new(SolidColour::new_from_colour(colour(*c))),
+            TextureDesc::Checker { scale, even, odd } => {
+                let even = self.texture_chain(even, chain)?;
+                let odd = self.texture_chain(odd, chain)?;
+                Box::new(CheckerTexture::new_from_textures(*scale, even, odd))
+            }
+            TextureDesc::Image { filename } => {
+                let image = RTWImage::new_from(filename).map_err(|_| {
+                    let loc = SceneFileError::at(self.source, desc.span().start, String::new());
+                    format!("could not load image '{}' for texture '{}' (defined at line {}, column {})", filename, name, loc.line, loc.column)
+                })?;
+                Box::new(ImageTexture::new_from_image(image))
+            }
+            TextureDesc::Noise { scale } => Box::new(NoiseTexture::new(*scale, self.rng)),
+        };
+        chain.pop();
+
+        self.textures.insert(name.clone(), built.clone_box());
+        Ok(built)
+    }
+
+    fn build_material(&mut self, desc: &MaterialDesc) -> BuildResult<Box<dyn Material>> {
+        let mat: Box<dyn Material> = match desc {
+            MaterialDesc::Lambertian { albedo } => Box::new(Lambertian::new_from_tex(self.texture(albedo)?)),
+            MaterialDesc::Metal { albedo, fuzz } => Box::new(Metal::new_from(colour(*albedo), *fuzz)),
+            MaterialDesc::Dielectric { refraction_index } => {
+                if refraction_index.is_nan() || *refraction_index <= 0.0 {return Err(format!("refraction index must be positive, got {}", refraction_index))}
+                Box::new(Dielectric::new_from(*refraction_index))
+            }
+            MaterialDesc::DiffuseLight { emit } => Box::new(DiffuseLight::new_from_tex(self.texture(emit)?)),
+            MaterialDesc::Isotropic { albedo } => Box::new(Isotropic::new_from_tex(self.texture(albedo)?)),
+        };
+        Ok(mat)
+    }
+
+    fn material(&self, name: &str) -> BuildResult<Box<dyn Material>> {
+        self.materials.get(name).cloned().ok_or_else(|| format!("unknown material '{}'", name))
+    }
+
+    fn build_object(&mut self, desc: &ObjectDesc) -> BuildResult<BuiltObject> {
+        let (mut object, mut light): BuiltObject = match &desc.shape {
+            ShapeDesc::Sphere { center, center2, radius, material } => {
+                let mat = self.material(material)?;
+                let sphere: Box<dyn Hittable> = match center2 {
+                    Some(center2) => Box::new(Sphere::new_moving(point(*center), point(*center2), *radius, mat)),
+                    None => Box::new(Sphere::new(point(*center), *radius, mat)),
+                };
+                self.with_light(sphere, material)?
+            }
+            ShapeDesc::Quad { q, u, v, material } => {
+                self.with_light(Box::new(Quad::new(point(*q), vector(*u), vector(*v), self.material(material)?)), material)?
+            }
+            ShapeDesc::Triangle { a, b, c, material } => {
+                self.with_light(Box::new(Triangle::new(point(*a), point(*b), point(*c), self.material(material)?).map_err(|e| e.to_string())?), material)?
+            }
+            ShapeDesc::Box { a, b, material } => {
+                self.with_light(Box::new(make_box(&point(*a), &point(*b), self.material(material)?)), material)?
+            }
+            ShapeDesc::Obj { filename, material } => {
+                let meshes = load_obj_meshes(self.dir.join(filename)).map_err(|e| e.to_string())?;
+                let mut triangles = HittableList::new();
+                let mut lights = HittableList::new();
+                for mut mesh in meshes {
+                    if let Some(material) = material {mesh.set_material(self.material(material)?)}
+                    let emissive = mesh.material().is_emissive();
+                    let mesh_triangles = mesh.into_hittable_list().map_err(|e| e.to_string())?;
+                    if emissive {lights.add_list(mesh_triangles.clone())}
+                    triangles.add_list(mesh_triangles);
+                }
+                let light: Option<Box<dyn Hittable>> = if lights.objects.is_empty() {None} else {Some(Box::new(lights))};
+                (Box::new(BVHNode::from_hittable_list(triangles).map_err(|e| e.to_string())?), light)
+            }
+            ShapeDesc::Group { objects } => {
+                let mut group = HittableList::new();
+                let mut lights = HittableList::new();
+                for child in objects {
+                    let (child, light) = self.build_object(child)?;
+                    group.add(child);
+                    if let Some(light) = light {lights.add(light)}
+                }
+                let light: Option<Box<dyn Hittable>> = if lights.objects.is_empty() {None} else {Some(Box::new(lights))};
+                (Box::new(BVHNode::from_hittable_list(group).map_err(|_| "group has no objects".to_string())?), light)
+            }
+            ShapeDesc::ConstantMedium { boundary, density, albedo } => {
+                if density.is_nan() || *density <= 0.0 {return Err(format!("constant medium density must be positive, got {}", density))}
+                let (boundary, _) = self.build_object(boundary)?;
+                (Box::new(ConstantMedium::new_from_tex(boundary, *density, self.texture(albedo)?)), None)
+            }
+        };
+
+        for transform in &desc.transforms {
+            object = apply_transform(transform, object);
+            light = light.map(|light| apply_transform(transform, light));
+        }
+
+        Ok((object, light))
+    }
+
+    // a copy of the object to sample as a light if its material glows
+    fn with_light(&self, object: Box<dyn Hittable>, material: &str) -> BuildResult<BuiltObject> {
+        let light = if self.material(material)?.is_emissive() {Some(object.clone())} else {None};
+        Ok((object, light))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::colour::Colour;
+    use crate::scenes;
+
+    fn scene_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("scenes").join(name)
+    }
+
+    // a few samples of a small image, which is enough to tell scenes apart
+    fn render_small(mut scene: Scene) -> Vec<Colour> {
+        scene.camera.image_width = 24.0;
+        scene.camera.samples_per_pixel = 4;
+        return scene.render().unwrap().resolve();
+    }
+
+    fn assert_same_film(file: &str, built_in: fn(&mut Rng) -> Result<Scene>) {
+        let from_file = load_scene(scene_path(file), &mut Rng::new(1)).unwrap();
+        let built = built_in(&mut Rng::new(1)).unwrap();
+        let (from_file, built) = (render_small(from_file), render_small(built));
+        assert_eq!(from_file.len(), built.len());
+        for (a, b) in from_file.iter().zip(&built) {
+            assert_eq!(a.0, b.0, "{} renders differently from the built-in scene", file);
+        }
+    }
+
+    #[test]
+    fn scene_files_match_the_built_in_scenes() {
+        assert_same_film("cornell_box.toml", |_| scenes::cornell_box());
+        assert_same_film("cornell_smoke.toml", |_| scenes::cornell_smoke());
+        assert_same_film("simple_light.toml", scenes::simple_light);
+    }
+
+    fn error_at(source: &str) -> (usize, usize, String) {
+        let Err(e) = parse_scene(source, &mut Rng::new(1)) else {panic!("{:?} parsed", source)};
+        return (e.line, e.column, e.message);
+    }
+
+    const SPHERE: &str = "[materials.white]\ntype = \"lambertian\"\nalbedo = [0.7, 0.7, 0.7]\n\n[[objects]]\ntype = \"sphere\"\n";
+
+    #[test]
+    fn malformed_value_is_reported_at_the_value() {
+        let (line, column, message) = error_at(&format!("{}center = [0.0, 0.0, 0.0]\nradius = \"x\"\nmaterial = \"white\"\n", SPHERE));
+        assert_eq!((line, column), (8, 10), "{}", message);
+
+        let (line, column, message) = error_at("[materials.steel]\ntype = \"metal\"\nalbedo = [0.7, 0.7, 0.7]\nfuzz = \"x\"\n");
+        assert_eq!((line, column), (4, 8), "{}", message);
+
+        let (line, column, message) = error_at("[[objects]]\ntype = \"group\"\nobjects = [\n    { type = \"sphere\", center = [0.0, 0.0, 0.0], radius = [1.0], material = \"white\" },\n]\n");
+        assert_eq!((line, column), (4, 59), "{}", message);
+    }
+
+    #[test]
+    fn unknown_key_is_reported_at_the_key() {
+        let (line, column, message) = error_at(&format!("{}center = [0.0, 0.0, 0.0]\nradiuss = 1.0\nmaterial = \"white\"\n", SPHERE));
+        assert_eq!((line, column), (8, 1), "{}", message);
+        assert!(message.contains("radiuss"), "{}", message);
+    }
+
+    #[test]
+    fn key_of_another_type_is_reported_at_its_value() {
+        let (line, column, message) = error_at(&format!("{}center = [0.0, 0.0, 0.0]\nradius = 1.0\nq = [1.0, 0.0, 0.0]\nmaterial = \"white\"\n", SPHERE));
+        assert_eq!((line, column, message.as_str()), (9, 5, "a sphere doesn't take `q`"));
+    }
+
+    #[test]
+    fn missing_key_and_unknown_type_are_reported_at_the_type() {
+        let (line, column, message) = error_at(&format!("{}center = [0.0, 0.0, 0.0]\nmaterial = \"white\"\n", SPHERE));
+        assert_eq!((line, column, message.as_str()), (6, 8, "a sphere needs `radius`"));
+
+        let (line, column, message) = error_at("[[objects]]\ntype = \"cube\"\n");
+        assert_eq!((line, column), (2, 8), "{}", message);
+    }
+
+    #[test]
+    fn obj_models_are_found_relative_to_the_scene_file() {
+        let source = "[[objects]]\ntype = \"obj\"\nfilename = \"models/materials.obj\"\n";
+        let scene = parse_scene_in(source, &scene_path(""), &mut Rng::new(1)).unwrap();
+        // the MTL file next to the model gave one of its triangles a light
+        assert!(!scene.lights.objects.is_empty());
+
+        let Err(e) = parse_scene_in(source, Path::new(env!("CARGO_MANIFEST_DIR")), &mut Rng::new(1)) else {panic!("found the model outside scenes/")};
+        assert!(e.message.contains("models/materials.obj"), "{}", e.message);
+    }
+}
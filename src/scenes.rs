@@ -5,6 +5,9 @@ use std::sync::Arc;
 use nalgebra::{Point3, Vector3};
 
 use crate::core::camera::Camera;
+use crate::core::checkpoint::Checkpoint;
+use crate::core::film::Film;
+use crate::error::Result;
 use crate::materials::dielectric::Dielectric;
 use crate::materials::diffuse_light::DiffuseLight;
 use crate::materials::material::Material;
@@ -13,6 +16,7 @@ use crate::textures::image_texture::ImageTexture;
 use crate::textures::noise_texture::NoiseTexture;
 use crate::transforms::rotate_y::RotateY;
 use crate::transforms::translate::Translate;
+use crate::util::rng::Rng;
 use crate::util::vector_math::{random_f32, random_f32_within, random_vec3, random_vec3_within};
 use crate::volumes::constant_medium::ConstantMedium;
 use crate::{core::colour::Colour, geometry::hittable_list::HittableList, materials::lambertian::Lambertian, textures::checkered_texture::CheckerTexture};
@@ -21,8 +25,64 @@ use crate::geometry::bvh::BVHNode;
 use crate::geometry::hittable::Hittable;
 use crate::geometry::quad::Quad;
 use crate::geometry::quad::make_box;
+use crate::geometry::triangle::Triangle;
+use crate::geometry::triangle_mesh::TriangleMesh;
+
+/// A built world together with the camera that frames it.
+pub struct Scene {
+    pub world: Arc<dyn Hittable + Send + Sync>,
+    /// The emissive objects of the world, sampled directly by the camera.
+    pub lights: HittableList,
+    pub camera: Camera,
+}
+
+impl Scene {
+    /// Renders the world through the scene's camera.
+    pub fn render(&mut self) -> Result<Film> {
+        self.camera.render(&self.world, &self.lights)
+    }
+
+    /// Renders the world through the scene's camera in passes, handing the film so far to `on_pass` after each.
+    pub fn render_progressive(&mut self, on_pass: impl FnMut(&Film, u32) -> Result<()>) -> Result<Film> {
+        self.camera.render_progressive(&self.world, &self.lights, on_pass)
+    }
+
+    /// Carries on a render of the scene saved in `checkpoint`, as `render_progressive`.
+    pub fn resume(&mut self, checkpoint: Checkpoint, on_pass: impl FnMut(&Film, u32) -> Result<()>) -> Result<Film> {
+        self.camera.resume(&self.world, &self.lights, checkpoint, on_pass)
+    }
+}
+
+/// An entry in the scene registry, used by the command line to find scenes by name.
+pub struct SceneEntry {
+    pub name: &'static str,
+    pub description: &'static str,
+    /// Builds the scene, drawing any randomly placed objects from `rng`.
+    pub build: fn(&mut Rng) -> Result<Scene>,
+}
 
-pub fn bouncing_spheres() -> Result<(), ()> {
+pub const SCENES: &[SceneEntry] = &[
+    SceneEntry { name: "bouncing_spheres", description: "Book 1 cover scene with moving spheres on a checkered ground", build: bouncing_spheres },
+    SceneEntry { name: "checkered_spheres", description: "Two large spheres with a checker texture", build: |_| checkered_spheres() },
+    SceneEntry { name: "earth", description: "Image-textured globe", build: |_| earth() },
+    SceneEntry { name: "quick_earth_test", description: "Low quality globe for checking the image texture quickly", build: |_| quick_earth_test() },
+    SceneEntry { name: "perlin_spheres", description: "Perlin noise marble spheres", build: perlin_spheres },
+    SceneEntry { name: "quads", description: "Five coloured quads around the camera", build: |_| quads() },
+    SceneEntry { name: "triangles", description: "Flat and smooth shaded triangle mesh spheres below an image-textured triangle", build: |_| triangles() },
+    SceneEntry { name: "simple_light", description: "Perlin spheres lit by a sphere and a quad light", build: simple_light },
+    SceneEntry { name: "cornell_box", description: "Cornell box with two rotated boxes", build: |_| cornell_box() },
+    SceneEntry { name: "cornell_smoke", description: "Cornell box with smoke and fog boxes", build: |_| cornell_smoke() },
+    SceneEntry { name: "final_scene", description: "Book 2 final scene at full quality (800px, 10000 spp, depth 40)", build: |rng| final_scene(800.0, 10000, 40, rng) },
+    SceneEntry { name: "final_scene_quick", description: "Book 2 final scene at preview quality (400px, 20 spp, depth 4)", build: |rng| final_scene(400.0, 20, 4, rng) },
+    SceneEntry { name: "test_inner_spheres_quick", description: "Debug view of the rotated sphere cluster from the final scene", build: test_inner_spheres_quick },
+];
+
+/// Looks up a registered scene by name.
+pub fn find_scene(name: &str) -> Option<&'static SceneEntry> {
+    SCENES.iter().find(|entry| entry.name == name)
+}
+
+pub fn bouncing_spheres(rng: &mut Rng) -> Result<Scene> {
         //World
         let mut world = HittableList::new();
     
@@ -33,8 +93,8 @@ pub fn bouncing_spheres() -> Result<(), ()> {
         
         for a in -11..11 {
             for b in -11..11 {
-                let choose_mat = random_f32();
-                let center = Point3::new((a as f32)+0.9*random_f32(), 0.2, (b as f32)+0.9*random_f32());
+                let choose_mat = random_f32(rng);
+                let center = Point3::new((a as f32)+0.9*random_f32(rng), 0.2, (b as f32)+0.9*random_f32(rng));
     
                 if (center - Point3::new(4.0, 0.2, 0.0)).len() as f32 > 0.9 {
     
@@ -42,19 +102,19 @@ pub fn bouncing_spheres() -> Result<(), ()> {
     
                     if choose_mat < 0.8 {
                         //diffuse
-                        let col_vec1 = random_vec3();
-                        let col_vec2 = random_vec3();
+                        let col_vec1 = random_vec3(rng);
+                        let col_vec2 = random_vec3(rng);
                         let alb_col = Vector3::new(col_vec1[0]*col_vec2[0], col_vec1[1]*col_vec2[1], col_vec1[2]*col_vec2[2]);
                         let albedo = Colour::new_from(alb_col[0], alb_col[1], alb_col[2]);
                         sphere_material = Box::new(Lambertian::new_from(albedo));
-                        let center2 = center + Vector3::new(0.0, random_f32(), 0.0);
+                        let center2 = center + Vector3::new(0.0, random_f32(rng), 0.0);
                         world.add(Box::new(Sphere::new_moving(center, center2, 0.2, sphere_material)));
                     }
                     else if choose_mat < 0.95 {
                         //metal
-                        let col_vec = random_vec3_within(0.5, 1.0);
+                        let col_vec = random_vec3_within(rng, 0.5, 1.0);
                         let albedo = Colour::new_from(col_vec[0], col_vec[1], col_vec[2]);
-                        let fuzz = random_f32_within(0.0, 0.5);
+                        let fuzz = random_f32_within(rng, 0.0, 0.5);
                         sphere_material = Box::new(Metal::new_from(albedo, fuzz));
                         world.add(Box::new(Sphere::new(center, 0.2, sphere_material)));
                     }
@@ -77,7 +137,7 @@ pub fn bouncing_spheres() -> Result<(), ()> {
         world.add(Box::new(Sphere::new(Point3::new(4.0, 1.0, 0.0), 1.0, material3)));
     
         println!("World has {} objects", &world.objects.len());
-        let world_bbox  = BVHNode::from_hittable_list(world);
+        let world_bbox  = BVHNode::from_hittable_list(world)?;
         let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
         //let sync_world = Arc::new(&world);
     
@@ -98,12 +158,12 @@ pub fn bouncing_spheres() -> Result<(), ()> {
         cam.defocus_angle = 0.6;
         cam.focus_dist    = 10.0;
     
-        let _ = cam.render(&sync_world);
-    
-        Ok(())
+        let lights = HittableList::new();
+
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn checkered_spheres() -> Result<(), ()> {
+pub fn checkered_spheres() -> Result<Scene> {
 
     //World
     let mut world = HittableList::new();
@@ -113,7 +173,7 @@ pub fn checkered_spheres() -> Result<(), ()> {
     world.add(Box::new(Sphere::new(Point3::new(0.0,-10.0,0.0), 10.0, ground_material.clone())));
     world.add(Box::new(Sphere::new(Point3::new(0.0,10.0,0.0), 10.0, ground_material.clone())));
 
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
 
@@ -133,22 +193,22 @@ pub fn checkered_spheres() -> Result<(), ()> {
     
         cam.defocus_angle = 0.0;
     
-        let _ = cam.render(&sync_world);
-    
-        Ok(())
+        let lights = HittableList::new();
+
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn earth() -> Result<(), ()> {
+pub fn earth() -> Result<Scene> {
 
     let mut world = HittableList::new();
 
-    let earth_texture = Box::new(ImageTexture::new_from_filename("earthmap.jpg"));
+    let earth_texture = Box::new(ImageTexture::new_from_filename("earthmap.jpg")?);
     println!("Loaded image: {}x{}", &earth_texture.image.width(), &earth_texture.image.height());
 
     let earth_surface = Box::new(Lambertian::new_from_tex(earth_texture));
     world.add(Box::new(Sphere::new(Point3::new(0.0,0.0,0.0), 2.0, earth_surface.clone())));
 
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
     let mut cam = Camera::new();
@@ -167,22 +227,22 @@ pub fn earth() -> Result<(), ()> {
 
     cam.defocus_angle = 0.0;
 
-    let _ = cam.render(&sync_world);
+    let lights = HittableList::new();
 
-    Ok(())
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn quick_earth_test() -> Result<(), ()> {
+pub fn quick_earth_test() -> Result<Scene> {
     // 1) Build a single textured sphere at the origin
     let mut world = HittableList::new();
-    let earth_tex = Box::new(ImageTexture::new_from_filename("earthmap.jpg"));
+    let earth_tex = Box::new(ImageTexture::new_from_filename("earthmap.jpg")?);
     let earth_mat = Box::new(Lambertian::new_from_tex(earth_tex));
     world.add(Box::new(Sphere::new(
         Point3::new(0.0, 0.0, 0.0),
         2.0,
         earth_mat.clone(),
     )));
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
 
     // 2) Configure a super‑low‑res camera
@@ -201,21 +261,22 @@ pub fn quick_earth_test() -> Result<(), ()> {
     cam.defocus_angle = 0.0;
 
     // 3) Render and inspect immediately
-    let _ = cam.render(&sync_world);
-    Ok(())
+    let lights = HittableList::new();
+
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn perlin_spheres() -> Result<(), ()> {
+pub fn perlin_spheres(rng: &mut Rng) -> Result<Scene> {
 
     //World
     let mut world = HittableList::new();
 
-    let pertext = Box::new(NoiseTexture::new(4.0));
+    let pertext = Box::new(NoiseTexture::new(4.0, rng));
     let ground_material = Box::new(Lambertian::new_from_tex(pertext));
     world.add(Box::new(Sphere::new(Point3::new(0.0,-1000.0,0.0), 1000.0, ground_material.clone())));
     world.add(Box::new(Sphere::new(Point3::new(0.0,2.0,0.0), 2.0, ground_material.clone())));
 
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
 
@@ -235,17 +296,17 @@ pub fn perlin_spheres() -> Result<(), ()> {
 
     cam.defocus_angle = 0.0;
 
-    let _ = cam.render(&sync_world);
+    let lights = HittableList::new();
 
-    Ok(())
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn quads() -> Result<(), ()> {
+pub fn quads() -> Result<Scene> {
 
     // Create World
     let mut world = HittableList::new();
 
-    //let pertext = Box::new(NoiseTexture::new(4.0));
+    //let pertext = Box::new(NoiseTexture::new(4.0, rng));
 
     // Materials
     let left_red = Box::new(Lambertian::new_from(Colour::new_from(1.0, 0.2, 0.2)));
@@ -262,7 +323,7 @@ pub fn quads() -> Result<(), ()> {
     world.add(Box::new(Quad::new(Point3::new(-2.0, -3.0, 5.0), Vector3::new(4.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -4.0), lower_teal)));
 
     // bounding boxes
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
 
@@ -283,26 +344,110 @@ pub fn quads() -> Result<(), ()> {
 
     cam.defocus_angle = 0.0;
 
-    let _ = cam.render(&sync_world);
+    let lights = HittableList::new();
+
+    Ok(Scene { world: sync_world, lights, camera: cam })
+}
+
+pub fn triangles() -> Result<Scene> {
+
+    // World
+    let mut world = HittableList::new();
+
+    let checker = Box::new(CheckerTexture::new_from_colours(0.5, Colour::new_from(0.2, 0.3, 0.1), Colour::new_from(0.9, 0.9, 0.9)));
+    world.add(Box::new(Quad::new(Point3::new(-10.0, 0.0, -10.0), Vector3::new(20.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 20.0), Box::new(Lambertian::new_from_tex(checker)))));
+
+    // the same low-poly sphere twice: faceted on the left, with interpolated normals on the right
+    let red = Box::new(Lambertian::new_from(Colour::new_from(0.8, 0.2, 0.2)));
+    let (positions, indices) = uv_sphere(Point3::new(-1.2, 1.0, 0.0), 1.0, 8, 12);
+    let flat = TriangleMesh::new(positions.clone(), indices.clone(), red);
+    world.add_list(flat.into_hittable_list()?);
+
+    let gold = Box::new(Metal::new_from(Colour::new_from(0.8, 0.6, 0.2), 0.0));
+    let (positions, indices) = uv_sphere(Point3::new(1.2, 1.0, 0.0), 1.0, 8, 12);
+    let mut smooth = TriangleMesh::new(positions, indices, gold);
+    smooth.compute_normals()?;
+    world.add_list(smooth.into_hittable_list()?);
+
+    // a lone triangle, image-textured through its barycentric coordinates
+    let earth_texture = Box::new(ImageTexture::new_from_filename("earthmap.jpg")?);
+    world.add(Box::new(Triangle::new(Point3::new(-1.5, 2.5, -2.0), Point3::new(1.5, 2.5, -2.0), Point3::new(-1.5, 4.5, -2.0), Box::new(Lambertian::new_from_tex(earth_texture)))?));
+
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
+    let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
+
+    // Camera
+    let mut cam = Camera::new();
+
+    cam.aspect_ratio      = 16.0 / 9.0;
+    cam.image_width       = 800.0;
+    cam.samples_per_pixel = 50;
+    cam.max_depth         = 10;
+    cam.background = Colour::new_from(0.7, 0.8, 1.0);
+
+    cam.vfov     = 35;
+    cam.lookfrom = Point3::new(0.0, 2.5, 8.0);
+    cam.lookat   = Point3::new(0.0, 1.8, 0.0);
+    cam.vup      = Vector3::new(0.0, 1.0, 0.0);
+
+    cam.defocus_angle = 0.0;
+
+    let lights = HittableList::new();
+
+    Ok(Scene { world: sync_world, lights, camera: cam })
+}
+
+fn uv_sphere(center: Point3<f32>, radius: f32, rings: usize, segments: usize) -> (Vec<Point3<f32>>, Vec<[usize; 3]>) {
+    // a sphere tessellated along lines of latitude and longitude, with the poles as single vertices
+    let mut positions = vec![center + Vector3::new(0.0, radius, 0.0)];
+    for ring in 1..rings {
+        let theta = std::f32::consts::PI * ring as f32 / rings as f32;
+        for segment in 0..segments {
+            let phi = 2.0 * std::f32::consts::PI * segment as f32 / segments as f32;
+            positions.push(center + radius * Vector3::new(theta.sin() * phi.cos(), theta.cos(), -theta.sin() * phi.sin()));
+        }
+    }
+    positions.push(center - Vector3::new(0.0, radius, 0.0));
+
+    let south = positions.len() - 1;
+    let vertex = |ring: usize, segment: usize| 1 + (ring - 1) * segments + segment % segments;
 
-    Ok(())
+    let mut indices = Vec::new();
+    for segment in 0..segments {
+        indices.push([0, vertex(1, segment), vertex(1, segment + 1)]);
+        indices.push([south, vertex(rings - 1, segment + 1), vertex(rings - 1, segment)]);
+    }
+    for ring in 1..rings - 1 {
+        for segment in 0..segments {
+            indices.push([vertex(ring, segment), vertex(ring + 1, segment), vertex(ring + 1, segment + 1)]);
+            indices.push([vertex(ring, segment), vertex(ring + 1, segment + 1), vertex(ring, segment + 1)]);
+        }
+    }
+
+    (positions, indices)
 }
 
-pub fn simple_light() -> Result<(), ()> {
+pub fn simple_light(rng: &mut Rng) -> Result<Scene> {
 
     //World
     let mut world = HittableList::new();
 
-    let pertext = Box::new(NoiseTexture::new(4.0));
+    let pertext = Box::new(NoiseTexture::new(4.0, rng));
     let ground_material = Box::new(Lambertian::new_from_tex(pertext));
     world.add(Box::new(Sphere::new(Point3::new(0.0,-1000.0,0.0), 1000.0, ground_material.clone())));
     world.add(Box::new(Sphere::new(Point3::new(0.0,2.0,0.0), 2.0, ground_material.clone())));
 
     let difflight = Box::new(DiffuseLight::new_from(Colour::new_from(4.0, 4.0, 4.0)));
-    world.add(Box::new(Sphere::new(Point3::new(0.0, 7.0, 0.0), 2.0, difflight.clone())));
-    world.add(Box::new(Quad::new(Point3::new(3.0, 1.0,  -2.0), Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 2.0, 0.0), difflight.clone())));
+    let sphere_light = Sphere::new(Point3::new(0.0, 7.0, 0.0), 2.0, difflight.clone());
+    let quad_light = Quad::new(Point3::new(3.0, 1.0,  -2.0), Vector3::new(2.0, 0.0, 0.0), Vector3::new(0.0, 2.0, 0.0), difflight.clone());
+
+    let mut lights = HittableList::new();
+    lights.add(Box::new(sphere_light.clone()));
+    lights.add(Box::new(quad_light.clone()));
+    world.add(Box::new(sphere_light));
+    world.add(Box::new(quad_light));
 
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
 
@@ -322,12 +467,10 @@ pub fn simple_light() -> Result<(), ()> {
 
     cam.defocus_angle = 0.0;
 
-    let _ = cam.render(&sync_world);
-
-    Ok(())
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn cornell_box() -> Result<(), ()> {
+pub fn cornell_box() -> Result<Scene> {
 
     //World
     let mut world = HittableList::new();
@@ -339,7 +482,10 @@ pub fn cornell_box() -> Result<(), ()> {
 
     world.add(Box::new(Quad::new(Point3::new(555.0, 0.0,  0.0), Vector3::new(0.0, 555.0, 0.0), Vector3::new(0.0, 0.0, 555.0), green.clone())));
     world.add(Box::new(Quad::new(Point3::new(0.0, 0.0,  0.0), Vector3::new(0.0, 555.0, 0.0), Vector3::new(0.0, 0.0, 555.0), red.clone())));
-    world.add(Box::new(Quad::new(Point3::new(343.0, 554.0,  332.0), Vector3::new(-130.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -105.0), light.clone())));
+    let ceiling_light = Quad::new(Point3::new(343.0, 554.0,  332.0), Vector3::new(-130.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -105.0), light.clone());
+    let mut lights = HittableList::new();
+    lights.add(Box::new(ceiling_light.clone()));
+    world.add(Box::new(ceiling_light));
     world.add(Box::new(Quad::new(Point3::new(0.0, 0.0,  0.0), Vector3::new(555.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 555.0), white.clone())));
     world.add(Box::new(Quad::new(Point3::new(555.0, 555.0,  555.0), Vector3::new(-555.0, 0.0, 0.0), Vector3::new(0.0, 0.0, -555.0), white.clone())));
     world.add(Box::new(Quad::new(Point3::new(0.0, 0.0,  555.0), Vector3::new(555.0, 0.0, 0.0), Vector3::new(0.0, 555.0, 0.0), white.clone())));
@@ -354,7 +500,7 @@ pub fn cornell_box() -> Result<(), ()> {
     let box2_translated = Box::new(Translate::new(box2_rotated.clone(), Vector3::new(130.0, 0.0, 65.0)));
     world.add(box2_translated);
 
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
 
@@ -374,12 +520,10 @@ pub fn cornell_box() -> Result<(), ()> {
 
     cam.defocus_angle = 0.0;
 
-    let _ = cam.render(&sync_world);
-
-    Ok(())
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn cornell_smoke() -> Result<(), ()> {
+pub fn cornell_smoke() -> Result<Scene> {
 
     //World
     let mut world = HittableList::new();
@@ -391,7 +535,10 @@ pub fn cornell_smoke() -> Result<(), ()> {
 
     world.add(Box::new(Quad::new(Point3::new(555.0, 0.0,  0.0), Vector3::new(0.0, 555.0, 0.0), Vector3::new(0.0, 0.0, 555.0), green.clone())));
     world.add(Box::new(Quad::new(Point3::new(0.0, 0.0,  0.0), Vector3::new(0.0, 555.0, 0.0), Vector3::new(0.0, 0.0, 555.0), red.clone())));
-    world.add(Box::new(Quad::new(Point3::new(113.0, 554.0,  127.0), Vector3::new(330.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 305.0), light.clone())));
+    let ceiling_light = Quad::new(Point3::new(113.0, 554.0,  127.0), Vector3::new(330.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 305.0), light.clone());
+    let mut lights = HittableList::new();
+    lights.add(Box::new(ceiling_light.clone()));
+    world.add(Box::new(ceiling_light));
     world.add(Box::new(Quad::new(Point3::new(0.0, 555.0,  0.0), Vector3::new(555.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 555.0), white.clone())));
     world.add(Box::new(Quad::new(Point3::new(000.0, 0.0,  000.0), Vector3::new(555.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 555.0), white.clone())));
     world.add(Box::new(Quad::new(Point3::new(0.0, 0.0,  555.0), Vector3::new(555.0, 0.0, 0.0), Vector3::new(0.0, 555.0, 0.0), white.clone())));
@@ -407,7 +554,7 @@ pub fn cornell_smoke() -> Result<(), ()> {
     world.add(Box::new(ConstantMedium::new_from_colour(box2_translated, 0.01, Colour::new_from(1.0, 1.0, 1.0))));
 
 
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
 
@@ -427,12 +574,10 @@ pub fn cornell_smoke() -> Result<(), ()> {
 
     cam.defocus_angle = 0.0;
 
-    let _ = cam.render(&sync_world);
-
-    Ok(())
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn final_scene(image_width: f32, samples_per_pixel: u32, max_depth: u32) -> Result<(), ()> {
+pub fn final_scene(image_width: f32, samples_per_pixel: u32, max_depth: u32, rng: &mut Rng) -> Result<Scene> {
 
     println!("In final_scene with image_width = {}, samples_per_pixel = {}, and max_depth = {}", image_width, samples_per_pixel, max_depth);
 
@@ -451,7 +596,7 @@ pub fn final_scene(image_width: f32, samples_per_pixel: u32, max_depth: u32) ->
             let y0 = 0.0;
             let z0 = -1000.0 + j as f32*w;           
             let x1 = x0 + w;
-            let y1 = random_f32_within(1.0, 101.0);
+            let y1 = random_f32_within(rng, 1.0, 101.0);
             let z1 = z0 + w;
 
             boxes1.add(Box::new(make_box(&Point3::new(x0, y0, z0), &Point3::new(x1, y1, z1), ground.clone())));
@@ -464,7 +609,10 @@ pub fn final_scene(image_width: f32, samples_per_pixel: u32, max_depth: u32) ->
     
     // Light
     let light = Box::new(DiffuseLight::new_from(Colour::new_from(7.0, 7.0, 7.0)));
-    world.add(Box::new(Quad::new(Point3::new(123.0, 554.0,  147.0), Vector3::new(300.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 265.0), light.clone())));
+    let ceiling_light = Quad::new(Point3::new(123.0, 554.0,  147.0), Vector3::new(300.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 265.0), light.clone());
+    let mut lights = HittableList::new();
+    lights.add(Box::new(ceiling_light.clone()));
+    world.add(Box::new(ceiling_light));
 
     //lambertian moving sphere
     let center1 = Point3::new(400.0, 400.0, 200.0);
@@ -490,12 +638,12 @@ pub fn final_scene(image_width: f32, samples_per_pixel: u32, max_depth: u32) ->
     //world.add(Box::new(ConstantMedium::new_from_colour(boundary2.clone(), 0.0001, Colour::new_from(1.0, 1.0, 1.0))));
     
     // planet earth
-    let earth_tex = Box::new(ImageTexture::new_from_filename("earthmap.jpg"));
+    let earth_tex = Box::new(ImageTexture::new_from_filename("earthmap.jpg")?);
     let earth_mat = Box::new(Lambertian::new_from_tex(earth_tex));
     world.add(Box::new(Sphere::new(Point3::new(400.0, 200.0, 400.0), 100.0, earth_mat.clone())));
 
     // perlin textured sphere
-    let pertext = Box::new(NoiseTexture::new(0.2));
+    let pertext = Box::new(NoiseTexture::new(0.2, rng));
     let pertext_material = Box::new(Lambertian::new_from_tex(pertext));
     world.add(Box::new(Sphere::new(Point3::new(220.0,280.0,300.0), 80.0, pertext_material.clone())));
 
@@ -505,15 +653,15 @@ pub fn final_scene(image_width: f32, samples_per_pixel: u32, max_depth: u32) ->
     let ns = 1000; // number of spheres
     // FIXME -- there's probaably a better way to loop here if you don't use j
     for _j in 0..ns {
-        boxes2.add(Box::new(Sphere::new(Point3::new(random_f32_within(0.0, 165.0), random_f32_within(0.0, 165.0), random_f32_within(0.0, 165.0)), 10.0, white.clone())));
+        boxes2.add(Box::new(Sphere::new(Point3::new(random_f32_within(rng, 0.0, 165.0), random_f32_within(rng, 0.0, 165.0), random_f32_within(rng, 0.0, 165.0)), 10.0, white.clone())));
     }
 
    
-    world.add(Box::new(Translate::new(Box::new(RotateY::new(Box::new(BVHNode::from_hittable_list(boxes2)), 15.0)), Vector3::new(-100.0, 270.0, 395.0))));
+    world.add(Box::new(Translate::new(Box::new(RotateY::new(Box::new(BVHNode::from_hittable_list(boxes2)?), 15.0)), Vector3::new(-100.0, 270.0, 395.0))));
 
 
     // make BVH from the world we've just created
-    let world_bbox  = BVHNode::from_hittable_list(world);
+    let world_bbox  = BVHNode::from_hittable_list(world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
     
 
@@ -534,25 +682,24 @@ pub fn final_scene(image_width: f32, samples_per_pixel: u32, max_depth: u32) ->
 
     cam.defocus_angle = 0.0;
 
-    let _ = cam.render(&sync_world);
-
-    Ok(())
+    Ok(Scene { world: sync_world, lights, camera: cam })
 }
 
-pub fn test_inner_spheres_quick() -> Result<(), ()> {
+pub fn test_inner_spheres_quick(rng: &mut Rng) -> Result<Scene> {
     // 1. Recompute just the cluster BVH (boxes2) and its transforms:
     let mut boxes2 = HittableList::new();
     // Light
     let light = Box::new(DiffuseLight::new_from(Colour::new_from(7.0, 7.0, 7.0)));
-    boxes2.add(Box::new(Quad::new(Point3::new(123.0, 554.0,  147.0), Vector3::new(300.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 265.0), light.clone())));
+    let light_quad = Quad::new(Point3::new(123.0, 554.0,  147.0), Vector3::new(300.0, 0.0, 0.0), Vector3::new(0.0, 0.0, 265.0), light.clone());
+    boxes2.add(Box::new(light_quad.clone()));
 
     let white = Box::new(Lambertian::new_from(Colour::new_from(0.73, 0.73, 0.73)));
     let ns = 1000;
     for _ in 0..ns {
         boxes2.add(Box::new(Sphere::new(
-            Point3::new(random_f32_within(0.0, 165.0),
-                        random_f32_within(0.0, 165.0),
-                        random_f32_within(0.0, 165.0)),
+            Point3::new(random_f32_within(rng, 0.0, 165.0),
+                        random_f32_within(rng, 0.0, 165.0),
+                        random_f32_within(rng, 0.0, 165.0)),
             10.0,
             white.clone(),
         )));
@@ -564,13 +711,13 @@ pub fn test_inner_spheres_quick() -> Result<(), ()> {
         raw_boxes2_bbox
     );
 
-    let inner_bvh = BVHNode::from_hittable_list(boxes2);
+    let inner_bvh = BVHNode::from_hittable_list(boxes2)?;
     let moved_bvh = Translate::new(
         Box::new(RotateY::new(Box::new(inner_bvh), 15.0)),
         Vector3::new(-100.0, 270.0, 395.0),
     );
 
-    let moved_bbox = moved_bvh.bounding_box();
+    let _moved_bbox = moved_bvh.bounding_box();
     // println!("[Debug] moved_bvh (RotateY + Translate) → {:?}\n", moved_bbox);
 
     
@@ -593,7 +740,7 @@ pub fn test_inner_spheres_quick() -> Result<(), ()> {
     cam.vup      = Vector3::new(0.0, 1.0, 0.0);
 
     // 4. Ray-trace *just* that “test_world”:
-    let world_bbox = BVHNode::from_hittable_list(test_world);
+    let world_bbox = BVHNode::from_hittable_list(test_world)?;
     let sync_world: Arc<dyn Hittable + Send + Sync> = Arc::new(world_bbox);
 
     /*
@@ -602,13 +749,19 @@ pub fn test_inner_spheres_quick() -> Result<(), ()> {
     let target = Point3::new(1.0, 352.0, 454.0);
     let dir = (target - origin).normalize();
     let test_ray = Ray::new_from(origin, dir, 0.0);
-    let hit_opt = sync_world.hit(&test_ray, &Interval::new(0.001, f32::INFINITY));
+    let hit_opt = sync_world.hit(&test_ray, &Interval::new(0.001, f32::INFINITY), rng);
     println!(">>>>> Single‐ray probe hit? {}", hit_opt.is_some());
     if let Some(rec) = hit_opt {
         println!("    Hit point = {:?},   t = {}", rec.p, rec.t);
     }
     */
 
-    _ = cam.render(&sync_world);  // should finish in <1s at 200×200×10 SPP
-    Ok(())
+    // the light is part of the cluster, so it's sampled where the cluster has been moved to
+    let mut lights = HittableList::new();
+    lights.add(Box::new(Translate::new(
+        Box::new(RotateY::new(Box::new(light_quad), 15.0)),
+        Vector3::new(-100.0, 270.0, 395.0),
+    )));
+
+    Ok(Scene { world: sync_world, lights, camera: cam })  // should finish in <1s at 200×200×10 SPP
 }
\ No newline at end of file
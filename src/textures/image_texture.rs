@@ -1,27 +1,35 @@
+use std::sync::Arc;
+
 use nalgebra::Point3;
 
 use crate::{
     core::colour::Colour, 
+    error::Result, 
     util::interval::Interval, 
     textures::rtw_image::RTWImage, 
     textures::texture::Texture
 };
 
 pub struct ImageTexture {
-    pub image: RTWImage,
+    // shared, so cloning the texture (which every hit does with its material) doesn't copy the image
+    pub image: Arc<RTWImage>,
 }
 
 impl ImageTexture {
-    pub fn new_from_filename(filename: &str) -> Self {
+    pub fn new_from_filename(filename: &str) -> Result<Self> {
+        Ok(Self::new_from_image(RTWImage::new_from(filename)?))
+    }
+
+    pub fn new_from_image(image: RTWImage) -> Self {
         Self {
-            image: RTWImage::new_from(filename).expect("failed to load image"),
+            image: Arc::new(image),
         }
     }
 }
 
 impl Texture for ImageTexture {
     fn value(&self, u1: f32, v1: f32, _p: &Point3<f32>) -> Colour {
-        if self.image.height() <= 0 {return Colour::new_from(0.0, 1.0, 1.0)};
+        if self.image.height() == 0 {return Colour::new_from(0.0, 1.0, 1.0)};
         if u1.is_nan() || v1.is_nan() { return Colour::new_from(1.0, 0.0, 1.0)}; // hot pink to highlight issues
         
         let u = Interval::new(0.0, 1.0).clamp(u1);
@@ -41,7 +49,7 @@ impl Texture for ImageTexture {
 impl Clone for ImageTexture {
     fn clone(&self) -> Self {
         Self {
-            image: self.image.clone(),
+            image: Arc::clone(&self.image),
         }
     }
 }
\ No newline at end of file
@@ -1,6 +1,6 @@
 use nalgebra::Point3;
 
-use crate::{core::colour::Colour, textures::perlin::Perlin, textures::texture::Texture};
+use crate::{core::colour::Colour, textures::perlin::Perlin, textures::texture::Texture, util::rng::Rng};
 
 pub struct NoiseTexture {
     noise: Perlin,
@@ -8,13 +8,13 @@ pub struct NoiseTexture {
 }
 
 impl NoiseTexture {
-    pub fn new(scale: f32) -> Self {
+    pub fn new(scale: f32, rng: &mut Rng) -> Self {
 
         // fix it so that you don't need init, ow you'll always need noise to be mutable
         //let noise = Perlin::new();
         //noise.init();
         Self {
-            noise: Perlin::new(),
+            noise: Perlin::new(rng),
             scale,
         }
     }
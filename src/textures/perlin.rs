@@ -3,7 +3,8 @@
 
 use nalgebra::{Point3, Vector3};
 
-use crate::{util::vector_math::{random_unit_vector, random_f32_within}};
+use crate::util::rng::Rng;
+use crate::util::vector_math::{random_unit_vector, random_f32_within};
 
 pub struct Perlin {
     point_count: usize, // 256;
@@ -15,7 +16,7 @@ pub struct Perlin {
 
 impl Perlin {
 
-    pub fn new() -> Self {
+    pub fn new(rng: &mut Rng) -> Self {
         let mut perlin = Self {
             point_count: 256,
             randvec: [Vector3::zeros(); 256],
@@ -23,19 +24,19 @@ impl Perlin {
             perm_y: [0; 256],
             perm_z: [0; 256],
         };
-        perlin.init();
+        perlin.init(rng);
         return perlin;
     }
 
-    pub fn init(&mut self) {
+    pub fn init(&mut self, rng: &mut Rng) {
 
         for i in 0..self.point_count {
-            self.randvec[i] = random_unit_vector();
+            self.randvec[i] = random_unit_vector(rng);
         }
 
-        Perlin::perlin_generate_perm(&mut self.perm_x, self.point_count);
-        Perlin::perlin_generate_perm(&mut self.perm_y, self.point_count);
-        Perlin::perlin_generate_perm(&mut self.perm_z, self.point_count);
+        Perlin::perlin_generate_perm(rng, &mut self.perm_x, self.point_count);
+        Perlin::perlin_generate_perm(rng, &mut self.perm_y, self.point_count);
+        Perlin::perlin_generate_perm(rng, &mut self.perm_z, self.point_count);
     }
 
     pub fn noise(&self, p: &Point3<f32>) -> f32 {
@@ -78,21 +79,20 @@ impl Perlin {
         */
     }
 
-    fn perlin_generate_perm(p: &mut [i32; 256], n: usize) {
+    fn perlin_generate_perm(rng: &mut Rng, p: &mut [i32; 256], n: usize) {
         for i in 0..n {
             p[i] = i as i32;
         }
 
         for i in (1..n).rev() {
-            // is there a nice rust function that does this? swap
-            let r = random_f32_within(0.0, (i+1) as f32);
+            let r = random_f32_within(rng, 0.0, (i+1) as f32);
             let j = r.floor() as usize;
-            let tmp = p[i as usize];
-            p[i as usize] = p[j];
-            p[j] = tmp;
+            p.swap(i, j);
         }
     }
 
+    // the interpolation used before perlin_interpolation, kept for comparison
+    #[allow(dead_code)]
     fn trilinear_interpolation(&self, c: [[[f32; 2]; 2]; 2], u: f32, v: f32, w: f32) -> f32 {
         let mut accum = 0.0;
         // the typing is a mess in this loop... fix at some point
@@ -153,8 +153,6 @@ impl Perlin {
         return accum.abs();
     }
 
-    fn clone_box(&self) -> Box<dyn Send + Sync> {return Box::new(self.clone());}
-
 }
 
 
@@ -10,6 +10,8 @@ use std::ptr;
 
 extern crate stb_image; // add `stb_image = "0.2"` to Cargo.toml
 
+use crate::error::{Error, Result};
+
 pub struct RTWImage {
     bytes_per_pixel: usize,      // always 3 (RGB)
     fdata: Vec<f32>,             // floating‐point pixels
@@ -33,7 +35,7 @@ impl RTWImage {
     }
 
     /// Try to load from filename, searching in RTW_IMAGES and up to 6 parent levels.
-    pub fn new_from<P: AsRef<Path>>(filename: P) -> Result<Self, String> {
+    pub fn new_from<P: AsRef<Path>>(filename: P) -> Result<Self> {
         let filename = filename.as_ref();
         let mut candidates = Vec::new();
 
@@ -46,22 +48,22 @@ impl RTWImage {
         candidates.push(Path::new("images").join(filename));
         // 3) up to six levels of ../images/
         for lvl in 1..=6 {
-            let mut p = Path::new(&"../".repeat(lvl)).join("images").join(filename);
+            let p = Path::new(&"../".repeat(lvl)).join("images").join(filename);
             candidates.push(p);
         }
 
         // Attempt loads
         for path in candidates {
-            if let Ok(mut img) = RTWImage::load(&path) {
+            if let Ok(img) = RTWImage::load(&path) {
                 return Ok(img);
             }
         }
 
-        Err(format!("ERROR: Could not load image file '{}'.", filename.display()))
+        Err(Error::ImageLoad(filename.display().to_string()))
     }
 
     /// Load the file at `path`, returning Err on stbi failure.
-    fn load(path: &Path) -> Result<Self, String> {
+    fn load(path: &Path) -> std::result::Result<Self, String> {
         // Prepare placeholders
         let mut img = RTWImage::new();
         let c_path = CString::new(path.to_string_lossy().as_ref())
@@ -143,6 +145,12 @@ impl RTWImage {
     pub fn height(&self) -> usize { self.height }
 }
 
+impl Default for RTWImage {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 // `Drop` isn’t needed because `Vec` frees itself.
 
 impl Clone for RTWImage {
@@ -1,6 +1,7 @@
 // struct for moving hittable objects
 
 
+use crate::util::rng::Rng;
 use crate::geometry::aabb::AABB;
 use crate::geometry::hittable::{Hittable, HitRecord};
 use crate::util::interval::Interval;
@@ -60,9 +61,19 @@ impl RotateY {
     }
 }
 
+impl RotateY {
+    fn to_object(&self, v: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::new((self.cos_theta*v.x) - (self.sin_theta*v.z), v.y, (self.sin_theta*v.x) + (self.cos_theta*v.z))
+    }
+
+    fn to_world(&self, v: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::new((self.cos_theta*v.x) + (self.sin_theta*v.z), v.y, (-1.0*self.sin_theta*v.x) + (self.cos_theta*v.z))
+    }
+}
+
 impl Hittable for RotateY {
 
-    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, ray_t: &Interval, rng: &mut Rng) -> Option<HitRecord> {
        
         // transform the ray from world space to object space
 
@@ -72,7 +83,7 @@ impl Hittable for RotateY {
         let rotated_r = Ray::new_from(origin, direction, ray.time());
         
         // is there an intersection in object space?
-        if let Some(mut hit_rec) = self.object.clone().hit(&rotated_r, ray_t) {
+        if let Some(mut hit_rec) = self.object.clone().hit(&rotated_r, ray_t, rng) {
             
             // transform the intersection from object space back to world space
 
@@ -86,5 +97,26 @@ impl Hittable for RotateY {
 
     fn bounding_box(&self) -> AABB {return self.bbox.clone();}
 
+    fn pdf_value(&self, origin: &Point3<f32>, direction: &Vector3<f32>, rng: &mut Rng) -> f32 {
+        let origin = Point3::from(self.to_object(&origin.coords));
+        return self.object.pdf_value(&origin, &self.to_object(direction), rng);
+    }
+
+    fn random(&self, origin: &Point3<f32>, rng: &mut Rng) -> Vector3<f32> {
+        let origin = Point3::from(self.to_object(&origin.coords));
+        return self.to_world(&self.object.random(&origin, rng));
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        let (mut rec, pdf) = self.object.sample_surface(rng)?;
+        rec.p = Point3::from(self.to_world(&rec.p.coords));
+        rec.normal = self.to_world(&rec.normal);
+        return Some((rec, pdf));
+    }
+
+    fn surface_pdf(&self, point: &Point3<f32>) -> f32 {
+        return self.object.surface_pdf(&Point3::from(self.to_object(&point.coords)));
+    }
+
     fn clone_box(&self) -> Box<dyn Hittable> {return Box::new((*self).clone());}
 }
\ No newline at end of file
@@ -1,10 +1,11 @@
 // struct for moving hittable objects
 
+use crate::util::rng::Rng;
 use crate::geometry::aabb::AABB;
 use crate::geometry::hittable::{Hittable, HitRecord};
 use crate::util::interval::Interval;
 use crate::core::ray::Ray;
-use nalgebra::Vector3;
+use nalgebra::{Point3, Vector3};
 
 #[derive(Clone)]
 
@@ -29,11 +30,11 @@ impl Translate {
 
 impl Hittable for Translate {
 
-    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, ray_t: &Interval, rng: &mut Rng) -> Option<HitRecord> {
        
         let offset_r = Ray::new_from(ray.origin() - self.offset, ray.direction(), ray.time());
 
-        if let Some(mut hit_rec) = self.object.clone().hit(&offset_r, ray_t) {
+        if let Some(mut hit_rec) = self.object.clone().hit(&offset_r, ray_t, rng) {
             hit_rec.p += self.offset; 
             return Some(hit_rec);
         }
@@ -42,5 +43,23 @@ impl Hittable for Translate {
 
     fn bounding_box(&self) -> AABB {return self.bbox.clone();}
 
+    fn pdf_value(&self, origin: &Point3<f32>, direction: &Vector3<f32>, rng: &mut Rng) -> f32 {
+        return self.object.pdf_value(&(origin - self.offset), direction, rng);
+    }
+
+    fn random(&self, origin: &Point3<f32>, rng: &mut Rng) -> Vector3<f32> {
+        return self.object.random(&(origin - self.offset), rng);
+    }
+
+    fn sample_surface(&self, rng: &mut Rng) -> Option<(HitRecord, f32)> {
+        let (mut rec, pdf) = self.object.sample_surface(rng)?;
+        rec.p += self.offset;
+        return Some((rec, pdf));
+    }
+
+    fn surface_pdf(&self, point: &Point3<f32>) -> f32 {
+        return self.object.surface_pdf(&(point - self.offset));
+    }
+
     fn clone_box(&self) -> Box<dyn Hittable> {return Box::new((*self).clone());}
 }
\ No newline at end of file
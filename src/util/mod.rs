@@ -1,2 +1,4 @@
 pub mod interval;
+pub mod onb;
+pub mod rng;
 pub mod vector_math;
\ No newline at end of file
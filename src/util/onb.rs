@@ -0,0 +1,30 @@
+// an orthonormal basis built around a given direction (w), for turning directions sampled
+// around the z axis into directions around that one
+
+use nalgebra::Vector3;
+
+#[derive(Clone, Debug)]
+pub struct ONB {
+    pub u: Vector3<f32>,
+    pub v: Vector3<f32>,
+    pub w: Vector3<f32>,
+}
+
+impl ONB {
+    pub fn new(n: &Vector3<f32>) -> Self {
+        let w = n.normalize();
+        let a = if w.x.abs() > 0.9 {Vector3::new(0.0, 1.0, 0.0)} else {Vector3::new(1.0, 0.0, 0.0)};
+        let v = w.cross(&a).normalize();
+        let u = w.cross(&v);
+        Self {
+            u,
+            v,
+            w,
+        }
+    }
+
+    /// Takes a vector given in this basis to world coordinates.
+    pub fn transform(&self, v: &Vector3<f32>) -> Vector3<f32> {
+        return (v.x * self.u) + (v.y * self.v) + (v.z * self.w);
+    }
+}
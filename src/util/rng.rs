@@ -0,0 +1,131 @@
+// a small seedable random number generator (PCG32, see pcg-random.org)
+// everything random in a render draws from one of these, so the same seed gives the same image
+
+use std::sync::Arc;
+
+use crate::samplers::sampler::{SampleId, Sampler};
+
+#[derive(Clone)]
+pub struct Rng {
+    state: u64,
+    inc: u64,
+    // for camera samples, where the numbers come from instead of the PCG stream
+    sample: Option<SampleStream>,
+}
+
+// a camera sample's dimensions, handed out in turn
+#[derive(Clone)]
+struct SampleStream {
+    sampler: Arc<dyn Sampler>,
+    id: SampleId,
+    dim: u32,
+}
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self::new_with_stream(seed, 0)
+    }
+
+    /// Generators with the same seed but different streams give independent sequences.
+    pub fn new_with_stream(seed: u64, stream: u64) -> Self {
+        let mut rng = Self {
+            state: 0,
+            inc: (stream << 1) | 1,
+            sample: None,
+        };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    /// A PCG generator for one camera sample. It only depends on the seed, the pixel and the
+    /// sample index, so a pixel comes out the same whichever thread renders it.
+    pub fn for_sample(seed: u64, pixel: u64, sample: u64) -> Self {
+        Self::new_with_stream(splitmix64(seed ^ splitmix64(pixel)), sample)
+    }
+
+    /// The generator for one camera sample, handing out the sample's dimensions from `sampler` in the order they're asked for.
+    pub fn for_camera_sample(sampler: &Arc<dyn Sampler>, id: SampleId) -> Self {
+        let mut rng = Self::for_sample(id.seed, id.pixel, id.index as u64);
+        rng.sample = Some(SampleStream {sampler: Arc::clone(sampler), id, dim: 0});
+        rng
+    }
+
+    /// The generator for one path traced out from the lights before a pass (like a photon). Like `for_sample`
+    /// it only depends on its inputs, and never starts where a camera sample's generator does.
+    pub fn for_light_path(seed: u64, pass: u64, path: u64) -> Self {
+        Self::new_with_stream(splitmix64(seed ^ splitmix64(!pass)), path)
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old.wrapping_mul(6364136223846793005).wrapping_add(self.inc);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        let rot = (old >> 59) as u32;
+        xorshifted.rotate_right(rot)
+    }
+
+    /// A uniform float in [0, 1).
+    pub fn next_f32(&mut self) -> f32 {
+        if let Some(sample) = &mut self.sample {
+            sample.dim += 1;
+            return sample.sampler.get_1d(&sample.id, sample.dim - 1);
+        }
+        // the top 24 bits fill an f32 mantissa exactly
+        (self.next_u32() >> 8) as f32 * (1.0 / 16_777_216.0)
+    }
+
+    /// Two uniform floats in [0, 1), for something picked with a pair of numbers (like a point on a square).
+    /// A sampler spreads these out together, so they cover the square evenly rather than just each side.
+    pub fn next_2d(&mut self) -> (f32, f32) {
+        if let Some(sample) = &mut self.sample {
+            sample.dim += 2;
+            return sample.sampler.get_2d(&sample.id, sample.dim - 2);
+        }
+        (self.next_f32(), self.next_f32())
+    }
+}
+
+/// Mixes the bits of `x` (the splitmix64 finaliser), for hashing seeds and indices together.
+pub fn splitmix64(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9E3779B97F4A7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+    z ^ (z >> 31)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn first(rng: &mut Rng, n: usize) -> Vec<u32> {
+        (0..n).map(|_| rng.next_u32()).collect()
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_numbers() {
+        assert_eq!(first(&mut Rng::new(7), 16), first(&mut Rng::new(7), 16));
+        assert_ne!(first(&mut Rng::new(7), 16), first(&mut Rng::new(8), 16));
+        assert_eq!(first(&mut Rng::for_sample(7, 3, 5), 16), first(&mut Rng::for_sample(7, 3, 5), 16));
+    }
+
+    #[test]
+    fn each_pixel_and_sample_gets_its_own_stream() {
+        let mut streams = Vec::new();
+        for pixel in 0..16 {
+            for sample in 0..16 {
+                streams.push(first(&mut Rng::for_sample(7, pixel, sample), 4));
+            }
+        }
+        for pass in 0..4 {
+            for path in 0..16 {
+                streams.push(first(&mut Rng::for_light_path(7, pass, path), 4));
+            }
+        }
+        let count = streams.len();
+        streams.sort();
+        streams.dedup();
+        assert_eq!(streams.len(), count);
+    }
+}
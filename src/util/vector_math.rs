@@ -1,16 +1,15 @@
 use std::f32;
 
 use nalgebra::Vector3;
-use rand::{rng, Rng};
 
+use crate::util::rng::Rng;
 
 pub fn degrees_to_radians(degrees: f32) -> f32 {
     degrees*f32::consts::PI / 180.0
 }
 
-pub fn random_f32() -> f32 {
-    let mut rng = rng();
-    rng.random()
+pub fn random_f32(rng: &mut Rng) -> f32 {
+    rng.next_f32()
 }
 /*
 pub fn random_u32() -> u32 {
@@ -32,42 +31,41 @@ pub fn random_u32_within(min: u32, max: u32) -> u32 {
     min + scaled as u32
 }
 */
-pub fn random_f32_within(min: f32, max: f32) -> f32 {
-    let mut rng = rng();
-    let random_f32: f32 = rng.random();
-    min + (max-min)*random_f32
+pub fn random_f32_within(rng: &mut Rng, min: f32, max: f32) -> f32 {
+    min + (max-min)*random_f32(rng)
 }
 
-pub fn random_vec3() -> Vector3<f32> {
+pub fn random_vec3(rng: &mut Rng) -> Vector3<f32> {
 
-    let x = random_f32();
-    let y = random_f32();
-    let z = random_f32();
+    let x = random_f32(rng);
+    let y = random_f32(rng);
+    let z = random_f32(rng);
     let random_vec = Vector3::new(x,y,z);
     random_vec
 }
 
-pub fn random_vec3_within(min: f32, max: f32) -> Vector3<f32> {
+pub fn random_vec3_within(rng: &mut Rng, min: f32, max: f32) -> Vector3<f32> {
     // there's got to be a better way to do this with nalgebra
-    let x = random_f32_within(min, max);
-    let y = random_f32_within(min, max);
-    let z = random_f32_within(min, max);
+    let x = random_f32_within(rng, min, max);
+    let y = random_f32_within(rng, min, max);
+    let z = random_f32_within(rng, min, max);
 
     let random_vec = Vector3::new(x,y,z);
     random_vec
 }
 
-pub fn random_unit_vector() -> Vector3<f32> {
-    // not sure abt 1e-160 bit
-    loop {
-        let p = random_vec3_within(-1.0, 1.0);
-        let lensq = p.norm_squared();
-        if (1e-8 < lensq) && (lensq <= 1.0) {return p.normalize()}
-    }
+pub fn random_unit_vector(rng: &mut Rng) -> Vector3<f32> {
+    // z is uniform on [-1, 1] for a uniform point on the sphere (Archimedes), and the angle around z is uniform too.
+    // Mapping a pair of numbers (rather than rejection sampling) lets samplers spread the directions out evenly
+    let (r1, r2) = rng.next_2d();
+    let z = 1.0 - 2.0 * r1;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let phi = 2.0 * f32::consts::PI * r2;
+    Vector3::new(r * phi.cos(), r * phi.sin(), z)
 }
 /*
 pub fn random_on_hemisphere(normal: &Vector3<f32>) -> Vector3<f32> {
-    let on_unit_sphere = random_unit_vector();
+    let on_unit_sphere = random_unit_vector(rng);
     if normal.dot(&on_unit_sphere) > 0.0 {return on_unit_sphere} else {return -on_unit_sphere}
 }
 */
@@ -88,9 +86,10 @@ pub fn refract(uv: &Vector3<f32>, n: &Vector3<f32>, etai_over_etat: f32) -> Vect
     r_out_perp + r_out_parallel
 }
 
-pub fn random_in_unit_disk() -> Vector3<f32> {
-    loop {
-        let p = Vector3::new(random_f32_within(-1.0, 1.0), random_f32_within(-1.0, 1.0), 0.0);
-        if p.norm_squared() < 1.0 {return p}
-    }   
+pub fn random_in_unit_disk(rng: &mut Rng) -> Vector3<f32> {
+    // the square root keeps the points evenly spread, as there is more disk further out
+    let (r1, r2) = rng.next_2d();
+    let r = r1.sqrt();
+    let theta = 2.0 * f32::consts::PI * r2;
+    Vector3::new(r * theta.cos(), r * theta.sin(), 0.0)
 }
\ No newline at end of file
@@ -1,8 +1,7 @@
 // this is a struct for a hittable constant density medium
 //  ie a basic struct for fog/mist/smoke
 
-use std::f32::INFINITY;
-
+use crate::util::rng::Rng;
 use crate::geometry::aabb::AABB;
 use crate::core::colour::Colour;
 use crate::geometry::hittable::{Hittable, HitRecord};
@@ -48,9 +47,9 @@ impl ConstantMedium {
 
 impl Hittable for ConstantMedium {
 
-    fn hit(&self, ray: &Ray, ray_t: &Interval) -> Option<HitRecord> {
-        if let Some(mut hit_rec1) = self.boundary.hit(ray, &Interval::universe()) {
-            if let Some(mut hit_rec2) = self.boundary.hit(ray, &Interval::new(hit_rec1.t+0.0001, INFINITY)) {
+    fn hit(&self, ray: &Ray, ray_t: &Interval, rng: &mut Rng) -> Option<HitRecord> {
+        if let Some(mut hit_rec1) = self.boundary.hit(ray, &Interval::universe(), rng) {
+            if let Some(mut hit_rec2) = self.boundary.hit(ray, &Interval::new(hit_rec1.t+0.0001, f32::INFINITY), rng) {
                 if hit_rec1.t < ray_t.min {hit_rec1.t = ray_t.min;}
                 if hit_rec2.t > ray_t.max {hit_rec2.t = ray_t.max;}
                 
@@ -58,9 +57,9 @@ impl Hittable for ConstantMedium {
 
                 if hit_rec1.t < 0.0 {hit_rec1.t = 0.0;}
 
-                let ray_length = ray.direction().len() as f32;
+                let ray_length = ray.direction().norm();
                 let distance_inside_boundary = (hit_rec2.t - hit_rec1.t)*ray_length;
-                let hit_distance = self.neg_inv_density * random_f32().ln();
+                let hit_distance = self.neg_inv_density * random_f32(rng).ln();
 
                 if hit_distance > distance_inside_boundary {return None;}
                 